@@ -23,12 +23,29 @@
 //! }
 //! ```
 use std::{
+    borrow::Cow,
     future::Future,
+    net::SocketAddr,
     pin::Pin,
+    sync::Arc,
     task::{ready, Context, Poll},
 };
 
-use http::{header::HeaderName, HeaderMap, HeaderValue, Request, Response};
+use axum::{
+    async_trait,
+    extract::{ConnectInfo, FromRequestParts},
+};
+#[cfg(feature = "serde")]
+use axum::{
+    body::Bytes,
+    extract::{DefaultBodyLimit, State},
+    routing::post,
+    Router,
+};
+use http::{
+    header::HeaderName, request::Parts, uri::Scheme, HeaderMap, HeaderValue, Method, Request,
+    Response, StatusCode,
+};
 use pin_project_lite::pin_project;
 use tower_service::Service;
 
@@ -61,24 +78,219 @@ pub use helmet_core::*;
 ///     axum::serve(listener, app).await.unwrap();
 /// }
 /// ```
-#[derive(Clone)]
+///
+/// # HTTPS detection
+///
+/// With [`Helmet::hsts_https_only`], `Strict-Transport-Security` is only sent to requests that arrived over HTTPS. axum doesn't tell whether a connection uses TLS, so a request counts as HTTPS when its URI has the `https` scheme, as HTTP/2 requests do, when it has the [`HttpsConnection`] extension, or when a [trusted proxy](Helmet::trusted_proxy) says so in the `Forwarded` or `X-Forwarded-Proto` header. The proxy is identified by [`ConnectInfo`], so serve the app with `into_make_service_with_connect_info::<SocketAddr>()`.
+///
+/// ```no_run
+/// use std::net::SocketAddr;
+///
+/// use axum::{routing::get, Router};
+/// use axum_helmet::{Helmet, HelmetLayer};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let app = Router::new()
+///         .route("/", get(|| async { "Hello, world!" }))
+///         .layer(HelmetLayer::new(
+///             Helmet::default().hsts_https_only(true).trusted_proxy("10.0.0.0/8"),
+///         ));
+///
+///     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+///     axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+///         .await
+///         .unwrap();
+/// }
+/// ```
+///
+/// An app terminating TLS itself, e.g. with `axum-server` and rustls, marks its requests with [`HttpsConnection`]. Added with `layer` after `HelmetLayer`, the extension is inserted before `HelmetLayer` sees the request.
+///
+/// ```no_run
+/// use axum::{routing::get, Extension, Router};
+/// use axum_helmet::{Helmet, HelmetLayer, HttpsConnection};
+///
+/// let app: Router = Router::new()
+///     .route("/", get(|| async { "Hello, world!" }))
+///     .layer(HelmetLayer::new(Helmet::default().hsts_https_only(true)))
+///     .layer(Extension(HttpsConnection));
+/// ```
+///
+/// # Local development
+///
+/// [`DevelopmentMode::Loopback`] only relaxes the headers for requests to loopback hosts that come from a loopback address, without `Forwarded` or `X-Forwarded-*` headers. The address is taken from [`ConnectInfo`] as well, without it the headers are never relaxed.
+///
+/// # Response overrides
+///
+/// Handlers can change the headers of a single response by inserting [`ResponseOverrides`] into the response extensions, e.g. to make one page embeddable.
+/// Only the overridden headers are rendered for the response. Overrides that can't be applied, see [`ResponseOverrides::validate`], are logged with `tracing` and the configured headers are sent instead.
+///
+/// ```no_run
+/// use axum::{response::{Html, IntoResponse}, routing::get, Extension, Router};
+/// use axum_helmet::{ContentSecurityPolicy, Helmet, HelmetLayer, ResponseOverrides};
+///
+/// async fn widget() -> impl IntoResponse {
+///     (
+///         Extension(
+///             ResponseOverrides::new()
+///                 .skip("X-Frame-Options")
+///                 .extend_content_security_policy(
+///                     ContentSecurityPolicy::new().frame_ancestors(vec!["https://partner.example.com"]),
+///                 ),
+///         ),
+///         Html("<div id=\"widget\"></div>"),
+///     )
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let app = Router::new()
+///         .route("/widget", get(widget))
+///         .layer(HelmetLayer::new(Helmet::default()));
+///
+///     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+///     axum::serve(listener, app).await.unwrap();
+/// }
+/// ```
+#[derive(Clone, Debug)]
 pub struct HelmetLayer {
+    headers: Arc<CompiledHeaders>,
+    development_mode: DevelopmentMode,
+    development_headers: Option<Arc<CompiledHeaders>>,
+    hsts_https_only: bool,
+    trusted_proxies: Arc<TrustedProxies>,
+    path_rules: PathRules,
+}
+
+// the compiled headers and development headers of each path rule
+type PathRules = Arc<
+    Vec<(
+        PathPattern,
+        Arc<CompiledHeaders>,
+        Option<Arc<CompiledHeaders>>,
+    )>,
+>;
+
+#[derive(Debug, Default)]
+struct CompiledHeaders {
+    // configuration the headers were compiled from
+    core: HelmetCore,
     headers: HeaderMap,
+    nonce_headers: Vec<(HeaderName, NonceTemplate, Option<Condition>)>,
+    // headers rendered for every response, by their index in `core`
+    timed_headers: Vec<(HeaderName, usize, Option<Condition>)>,
+    conditional_headers: Vec<(HeaderName, HeaderValue, Condition)>,
+    conflict_strategies: Arc<Vec<(HeaderName, ConflictStrategy)>>,
+    // headers not sent on every response
+    scopes: Arc<Vec<(HeaderName, HeaderScope)>>,
+}
+
+fn compile_headers(core: &HelmetCore) -> Result<CompiledHeaders, HelmetError> {
+    let compiled = core.compile()?;
+    let header_name = |name: &str| {
+        HeaderName::try_from(name).map_err(|_| HelmetError::InvalidHeaderName {
+            name: name.to_string(),
+        })
+    };
+
+    let mut conflict_strategies = Vec::new();
+    let mut scopes = Vec::new();
+    for (name, strategy, scope) in compiled.names {
+        let name = header_name(&name)?;
+        if scope != HeaderScope::Any {
+            scopes.push((name.clone(), scope));
+        }
+        conflict_strategies.push((name, strategy));
+    }
+    let mut headers = HeaderMap::new();
+    let mut nonce_headers = Vec::new();
+    let mut timed_headers = Vec::new();
+    let mut conditional_headers = Vec::new();
+    for header in compiled.headers {
+        let name = header_name(&header.name)?;
+        let value = match header.value {
+            CompiledValue::Nonce(template) => {
+                nonce_headers.push((name, template, header.condition));
+                continue;
+            }
+            CompiledValue::Timed(index) => {
+                timed_headers.push((name, index, header.condition));
+                continue;
+            }
+            CompiledValue::Fixed(value) => HeaderValue::try_from(value.as_str()).map_err(|_| {
+                HelmetError::InvalidHeaderValue {
+                    name: header.name.clone(),
+                    value: value.clone(),
+                }
+            })?,
+        };
+        match header.condition {
+            Some(condition) => conditional_headers.push((name, value, condition)),
+            None => {
+                headers.append(name, value);
+            }
+        }
+    }
+    Ok(CompiledHeaders {
+        core: core.clone(),
+        headers,
+        nonce_headers,
+        timed_headers,
+        conditional_headers,
+        conflict_strategies: Arc::new(conflict_strategies),
+        scopes: Arc::new(scopes),
+    })
 }
 
 impl HelmetLayer {
+    /// Create a new layer from the given `Helmet` configuration.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any header has an invalid name or value. Use [`HelmetLayer::try_new`] to handle the error instead.
     pub fn new(core: HelmetCore) -> Self {
-        let headers = core
-            .headers
-            .iter()
-            .map(|header| {
-                (
-                    HeaderName::try_from(header.name()).expect("invalid header name"),
-                    HeaderValue::try_from(header.value()).expect("invalid header value"),
-                )
+        Self::try_new(core).unwrap_or_else(|err| panic!("{}", err))
+    }
+
+    /// Create a new layer from the given `Helmet` configuration, returning an error naming the offending header if any header is invalid.
+    ///
+    /// ```
+    /// use axum_helmet::{Helmet, HelmetError, HelmetLayer, XPoweredBy};
+    ///
+    /// let layer = HelmetLayer::try_new(Helmet::new().add(XPoweredBy::new("PHP\n4.2.0")));
+    ///
+    /// assert!(matches!(
+    ///     layer,
+    ///     Err(HelmetError::InvalidHeaderValue { name, .. }) if name == "X-Powered-By"
+    /// ));
+    /// ```
+    pub fn try_new(core: HelmetCore) -> Result<Self, HelmetError> {
+        let development_mode = core.development_mode();
+        let compile = |core: &HelmetCore| -> Result<_, HelmetError> {
+            let headers = Arc::new(compile_headers(core)?);
+            let development_headers = match development_mode {
+                DevelopmentMode::Off => None,
+                _ => Some(Arc::new(compile_headers(&core.relaxed_for_development())?)),
+            };
+            Ok((headers, development_headers))
+        };
+        let (headers, development_headers) = compile(&core)?;
+        let path_rules = core
+            .path_rules()?
+            .into_iter()
+            .map(|(pattern, core)| {
+                let (headers, development_headers) = compile(&core)?;
+                Ok((pattern, headers, development_headers))
             })
-            .collect();
-        Self { headers }
+            .collect::<Result<_, HelmetError>>()?;
+        Ok(Self {
+            headers,
+            development_mode,
+            development_headers,
+            hsts_https_only: core.is_hsts_https_only(),
+            trusted_proxies: Arc::new(core.trusted_proxies()?),
+            path_rules: Arc::new(path_rules),
+        })
     }
 }
 
@@ -86,23 +298,62 @@ impl<S> tower::layer::Layer<S> for HelmetLayer {
     type Service = HelmetInner<S>;
 
     fn layer(&self, inner: S) -> Self::Service {
-        let header_map = self.headers.clone();
-
-        HelmetInner { header_map, inner }
+        HelmetInner {
+            headers: self.headers.clone(),
+            development_mode: self.development_mode,
+            development_headers: self.development_headers.clone(),
+            hsts_https_only: self.hsts_https_only,
+            trusted_proxies: self.trusted_proxies.clone(),
+            path_rules: self.path_rules.clone(),
+            inner,
+        }
     }
 }
 
 #[derive(Clone)]
 pub struct HelmetInner<S> {
-    header_map: HeaderMap,
+    headers: Arc<CompiledHeaders>,
+    development_mode: DevelopmentMode,
+    development_headers: Option<Arc<CompiledHeaders>>,
+    hsts_https_only: bool,
+    trusted_proxies: Arc<TrustedProxies>,
+    path_rules: PathRules,
     inner: S,
 }
 
 impl<S> HelmetInner<S> {
     pub fn new(inner: S) -> Self {
-        let header_map = HeaderMap::new();
+        Self {
+            headers: Arc::new(CompiledHeaders::default()),
+            development_mode: DevelopmentMode::Off,
+            development_headers: None,
+            hsts_https_only: false,
+            trusted_proxies: Arc::new(TrustedProxies::default()),
+            path_rules: Arc::new(Vec::new()),
+            inner,
+        }
+    }
+}
 
-        Self { header_map, inner }
+impl<S> HelmetInner<S> {
+    fn is_https<ReqBody>(&self, req: &Request<ReqBody>) -> bool {
+        // the last value, a proxy appends its own after the ones it received
+        let header = |name: &str| {
+            req.headers()
+                .get_all(name)
+                .iter()
+                .next_back()
+                .and_then(|value| value.to_str().ok())
+        };
+        self.trusted_proxies.is_https(
+            req.uri().scheme() == Some(&Scheme::HTTPS)
+                || req.extensions().get::<HttpsConnection>().is_some(),
+            req.extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip()),
+            header("forwarded"),
+            header("x-forwarded-proto"),
+        )
     }
 }
 
@@ -119,14 +370,101 @@ where
         self.inner.poll_ready(cx)
     }
 
-    fn call(&mut self, req: Request<ReqBody>) -> Self::Future {
+    fn call(&mut self, mut req: Request<ReqBody>) -> Self::Future {
+        let host = req
+            .headers()
+            .get(http::header::HOST)
+            .and_then(|value| value.to_str().ok())
+            .or_else(|| req.uri().host());
+        let (headers, development_headers) = self
+            .path_rules
+            .iter()
+            .find(|(pattern, _, _)| pattern.matches(req.uri().path()))
+            .map_or(
+                (&self.headers, &self.development_headers),
+                |(_, headers, development_headers)| (headers, development_headers),
+            );
+        let development = self.development_mode.applies_to(
+            host,
+            req.extensions()
+                .get::<ConnectInfo<SocketAddr>>()
+                .map(|ConnectInfo(addr)| addr.ip()),
+            req.headers()
+                .keys()
+                .any(|name| is_forwarding_header(name.as_str())),
+        );
+        let compiled = match development_headers {
+            Some(headers) if development => headers.clone(),
+            _ => headers.clone(),
+        };
+        let hsts = !self.hsts_https_only || self.is_https(&req);
+        let nonce = (!compiled.nonce_headers.is_empty()).then(Nonce::generate);
+        if let Some(nonce) = &nonce {
+            req.extensions_mut().insert(nonce.clone());
+        }
+        let (headers, conditional_headers) = response_headers(&compiled, nonce.as_ref(), hsts);
+        // kept for evaluating the header conditions against the response, overrides can't add
+        // conditions (see `ResponseOverrides::replace`), so only the configured headers need it
+        let request = (!conditional_headers.is_empty())
+            .then(|| (req.method().clone(), req.headers().clone()));
+
         ResponseFuture {
             future: self.inner.call(req),
-            headers: self.header_map.clone(),
+            headers,
+            conditional_headers,
+            request,
+            compiled,
+            nonce,
+            hsts,
         }
     }
 }
 
+// Headers to send with a response, split into the unconditional headers and the headers with a condition.
+// The nonce is rendered into the headers using it, headers changing over time get their current value and
+// `Strict-Transport-Security` is removed over HTTP.
+fn response_headers(
+    compiled: &CompiledHeaders,
+    nonce: Option<&Nonce>,
+    hsts: bool,
+) -> (HeaderMap, Vec<(HeaderName, HeaderValue, Condition)>) {
+    let mut headers = compiled.headers.clone();
+    let mut conditional_headers = compiled.conditional_headers.clone();
+    if let Some(nonce) = nonce {
+        for (name, template, condition) in compiled.nonce_headers.iter() {
+            let value =
+                HeaderValue::try_from(template.render(nonce)).expect("invalid header value");
+            match condition {
+                Some(condition) => {
+                    conditional_headers.push((name.clone(), value, condition.clone()))
+                }
+                None => {
+                    headers.append(name.clone(), value);
+                }
+            }
+        }
+    }
+    for (name, index, condition) in compiled.timed_headers.iter() {
+        let header = compiled
+            .core
+            .iter()
+            .nth(*index)
+            .expect("timed header of `core`");
+        let value = HeaderValue::try_from(header.value()).expect("invalid header value");
+        match condition {
+            Some(condition) => conditional_headers.push((name.clone(), value, condition.clone())),
+            None => {
+                headers.append(name.clone(), value);
+            }
+        }
+    }
+    if !hsts {
+        headers.remove(http::header::STRICT_TRANSPORT_SECURITY);
+        conditional_headers.retain(|(name, _, _)| name != http::header::STRICT_TRANSPORT_SECURITY);
+    }
+    (headers, conditional_headers)
+}
+
 pin_project! {
     /// Response future for [`SetResponseHeader`].
     #[derive(Debug)]
@@ -134,6 +472,11 @@ pin_project! {
         #[pin]
         future: F,
         headers: HeaderMap,
+        conditional_headers: Vec<(HeaderName, HeaderValue, Condition)>,
+        request: Option<(Method, HeaderMap)>,
+        compiled: Arc<CompiledHeaders>,
+        nonce: Option<Nonce>,
+        hsts: bool,
     }
 }
 
@@ -147,18 +490,270 @@ where
         let this = self.project();
         let mut res = ready!(this.future.poll(cx)?);
 
-        res.headers_mut()
-            .extend(this.headers.iter().map(|(k, v)| (k.clone(), v.clone())));
+        let compiled = &**this.compiled;
+        let mut headers = std::mem::take(this.headers);
+        let mut conditional_headers = std::mem::take(this.conditional_headers);
+        let mut conflict_strategies = Cow::Borrowed(compiled.conflict_strategies.as_ref());
+        let mut scopes = Cow::Borrowed(compiled.scopes.as_ref());
+
+        // invalid overrides are not applied, the configured headers are still sent
+        let overrides = res
+            .extensions_mut()
+            .remove::<ResponseOverrides>()
+            .filter(|overrides| match overrides.validate() {
+                Ok(()) => true,
+                Err(err) => {
+                    tracing::warn!("response overrides not applied: {}", err);
+                    false
+                }
+            });
+        if let Some(overrides) = &overrides {
+            // only the overridden headers are rendered, the others keep their compiled values
+            let (removed, added) = compiled.core.overridden_headers(overrides);
+            for name in removed {
+                if let Ok(name) = HeaderName::try_from(name) {
+                    headers.remove(&name);
+                    conditional_headers.retain(|(n, _, _)| *n != name);
+                }
+            }
+            for header in added {
+                let name = HeaderName::try_from(header.name()).expect("invalid header name");
+                if !conflict_strategies.iter().any(|(n, _)| *n == name) {
+                    let strategy = compiled.core.conflict_strategy_for(header.name());
+                    conflict_strategies.to_mut().push((name.clone(), strategy));
+                }
+                scopes.to_mut().retain(|(n, _)| *n != name);
+                let scope = compiled.core.scope_for(header.as_ref());
+                if scope != HeaderScope::Any {
+                    scopes.to_mut().push((name.clone(), scope));
+                }
+                let value = match (header.nonce_template(), this.nonce.as_ref()) {
+                    (Some(template), Some(nonce)) => template.render(nonce),
+                    _ => header.value(),
+                };
+                let value = HeaderValue::try_from(value).expect("invalid header value");
+                match header.condition() {
+                    Some(condition) => conditional_headers.push((name, value, condition.clone())),
+                    None => {
+                        headers.append(name, value);
+                    }
+                }
+            }
+            if !*this.hsts {
+                headers.remove(http::header::STRICT_TRANSPORT_SECURITY);
+                conditional_headers
+                    .retain(|(name, _, _)| name != http::header::STRICT_TRANSPORT_SECURITY);
+            }
+        }
+        if let Some((method, request_headers)) = this.request.take() {
+            let exchange = RequestResponse {
+                method: method.as_str(),
+                status: res.status().as_u16(),
+                request_header: |name: &str| {
+                    request_headers
+                        .get(name)
+                        .and_then(|value| value.to_str().ok())
+                },
+                response_header: |name: &str| {
+                    res.headers()
+                        .get(name)
+                        .and_then(|value| value.to_str().ok())
+                },
+            };
+            for (name, value, condition) in conditional_headers {
+                if condition.matches(&exchange) {
+                    headers.append(name, value);
+                }
+            }
+        }
+        let kind = ResponseKind::from_content_type(
+            res.headers()
+                .get(http::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok()),
+        );
+
+        // the strategies list every header name once, in the order the headers were added
+        for (name, strategy) in conflict_strategies.iter() {
+            // e.g. `Strict-Transport-Security` over HTTP, or `X-Frame-Options` on JSON
+            if !headers.contains_key(name)
+                || scopes
+                    .iter()
+                    .any(|(n, scope)| n == name && !scope.includes(kind))
+            {
+                continue;
+            }
+            match strategy {
+                ConflictStrategy::KeepExisting if res.headers().contains_key(name) => continue,
+                ConflictStrategy::Overwrite => {
+                    res.headers_mut().remove(name);
+                }
+                _ => {}
+            }
+            for value in headers.get_all(name) {
+                res.headers_mut().append(name.clone(), value.clone());
+            }
+        }
 
         Poll::Ready(Ok(res))
     }
 }
 
+/// Extractor for the CSP [`Nonce`] of the current request.
+///
+/// Only available when the `Content-Security-Policy` is configured with `script_nonce()` or `style_nonce()`, otherwise the request is rejected with `500 Internal Server Error`.
+///
+/// # Example
+///
+/// ```no_run
+/// use axum::{response::Html, routing::get, Router};
+/// use axum_helmet::{ContentSecurityPolicy, CspNonce, Helmet, HelmetLayer};
+///
+/// async fn index(CspNonce(nonce): CspNonce) -> Html<String> {
+///     Html(format!("<script nonce=\"{}\">alert(1)</script>", nonce))
+/// }
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let app = Router::new().route("/", get(index)).layer(HelmetLayer::new(
+///         Helmet::new().add(ContentSecurityPolicy::default().script_nonce()),
+///     ));
+///
+///     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+///     axum::serve(listener, app).await.unwrap();
+/// }
+/// ```
+#[derive(Clone, Debug)]
+pub struct CspNonce(pub Nonce);
+
+#[async_trait]
+impl<S> FromRequestParts<S> for CspNonce
+where
+    S: Send + Sync,
+{
+    type Rejection = (StatusCode, &'static str);
+
+    async fn from_request_parts(parts: &mut Parts, _state: &S) -> Result<Self, Self::Rejection> {
+        parts
+            .extensions
+            .get::<Nonce>()
+            .cloned()
+            .map(CspNonce)
+            .ok_or((
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "CSP nonce not found, make sure the Content-Security-Policy uses nonces",
+            ))
+    }
+}
+
+/// Request extension marking a request that arrived over a TLS connection, see [`Helmet::hsts_https_only`].
+///
+/// Over HTTP/1.1 axum can't tell whether the connection uses TLS. Insert it into the requests of a listener that terminates TLS itself, e.g. with [`Extension`](axum::Extension) as a layer, so `Strict-Transport-Security` is sent to them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HttpsConnection;
+
+#[cfg(feature = "serde")]
+/// Endpoint receiving violation reports.
+///
+/// Accepts `application/csp-report` reports, sent for the `report-uri` directive, and `application/reports+json` reports, sent for the `report-to` directive, and hands each report to a [`ReportSink`].
+///
+/// Besides CSP violations, the Reporting API is used for e.g. `Cross-Origin-Opener-Policy`, `Cross-Origin-Embedder-Policy` and `Permissions-Policy` violations, which are received as well.
+///
+/// Responds with `204 No Content` when the reports were received, `400 Bad Request` for malformed reports, `413 Payload Too Large` when the body exceeds the size limit and `415 Unsupported Media Type` for other content types.
+///
+/// # Example
+///
+/// ```no_run
+/// use axum::{routing::get, Router};
+/// use axum_helmet::{ContentSecurityPolicy, Helmet, HelmetLayer, ReportReceiver, ViolationReport};
+///
+/// #[tokio::main]
+/// async fn main() {
+///     let reports = ReportReceiver::new(|report: ViolationReport| {
+///         eprintln!("violation report: {:?}", report);
+///     });
+///
+///     let app = Router::new()
+///         .route("/", get(|| async { "Hello, world!" }))
+///         .merge(reports.into_router("/csp-reports"))
+///         .layer(HelmetLayer::new(
+///             Helmet::new()
+///                 .reporting_endpoint("csp", "/csp-reports")
+///                 .add(
+///                     ContentSecurityPolicy::default()
+///                         .report_to("csp")
+///                         .report_uri(vec!["/csp-reports"]),
+///                 ),
+///         ));
+///
+///     let listener = tokio::net::TcpListener::bind("0.0.0.0:3000").await.unwrap();
+///     axum::serve(listener, app).await.unwrap();
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ReportReceiver {
+    sink: Arc<dyn ReportSink>,
+    max_body_size: usize,
+}
+
+#[cfg(feature = "serde")]
+impl ReportReceiver {
+    /// Create a receiver handing reports to the given sink, accepting bodies up to 64 KiB.
+    pub fn new(sink: impl ReportSink) -> Self {
+        Self {
+            sink: Arc::new(sink),
+            max_body_size: 64 * 1024,
+        }
+    }
+
+    /// Set the maximum size of a report request body in bytes.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+
+    /// Create a router accepting reports with `POST` requests to `path`, to be merged into the application router.
+    pub fn into_router<S>(self, path: &str) -> Router<S>
+    where
+        S: Clone + Send + Sync + 'static,
+    {
+        Router::new()
+            .route(path, post(receive_reports))
+            .layer(DefaultBodyLimit::max(self.max_body_size))
+            .with_state(self.sink)
+    }
+}
+
+#[cfg(feature = "serde")]
+async fn receive_reports(
+    State(sink): State<Arc<dyn ReportSink>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> StatusCode {
+    let content_type = headers
+        .get(http::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+
+    match parse_reports(content_type, &body) {
+        Ok(reports) => {
+            for report in reports {
+                sink.report(report);
+            }
+            StatusCode::NO_CONTENT
+        }
+        Err(ReportError::UnsupportedContentType(_)) => StatusCode::UNSUPPORTED_MEDIA_TYPE,
+        Err(ReportError::InvalidBody(_)) => StatusCode::BAD_REQUEST,
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use axum::{routing::get, Router};
+    #[cfg(feature = "serde")]
+    use std::sync::Mutex;
+
+    use axum::{routing::get, Extension, Router};
     use axum_test::TestServer;
     use http::{header, HeaderValue};
 
@@ -192,4 +787,623 @@ mod tests {
             Some(&HeaderValue::from_static("1; mode=block"))
         );
     }
+
+    #[tokio::test]
+    async fn test_helmet_nonce() {
+        let test_app = Router::new()
+            .route(
+                "/",
+                get(|CspNonce(nonce): CspNonce| async move { nonce.to_string() }),
+            )
+            .layer(HelmetLayer::new(
+                Helmet::new().add(
+                    ContentSecurityPolicy::new()
+                        .script_src(vec!["'self'"])
+                        .script_nonce(),
+                ),
+            ));
+
+        let server = TestServer::new(test_app).expect("failed to create test server");
+
+        let res = server.get("/").await;
+        let nonce = res.text();
+
+        assert_eq!(
+            res.headers().get(header::CONTENT_SECURITY_POLICY).unwrap(),
+            &format!("script-src 'self' 'nonce-{}'", nonce)
+        );
+        assert_ne!(server.get("/").await.text(), nonce);
+    }
+
+    #[tokio::test]
+    async fn test_helmet_nonce_missing() {
+        let test_app = Router::new()
+            .route(
+                "/",
+                get(|CspNonce(nonce): CspNonce| async move { nonce.to_string() }),
+            )
+            .layer(HelmetLayer::new(
+                Helmet::new().add(ContentSecurityPolicy::default()),
+            ));
+
+        let server = TestServer::new(test_app).expect("failed to create test server");
+
+        let res = server.get("/").await;
+
+        assert_eq!(res.status_code(), 500);
+    }
+
+    async fn handler_with_headers() -> impl axum::response::IntoResponse {
+        (
+            [
+                (header::X_FRAME_OPTIONS, "DENY"),
+                (header::CONTENT_SECURITY_POLICY, "default-src 'none'"),
+            ],
+            "Hello, world!",
+        )
+    }
+
+    #[tokio::test]
+    async fn test_helmet_conflict_strategy_keep_existing() {
+        let test_app = Router::new()
+            .route("/", get(handler_with_headers))
+            .layer(HelmetLayer::new(Helmet::default()));
+
+        let server = TestServer::new(test_app).expect("failed to create test server");
+
+        let res = server.get("/").await;
+
+        assert_eq!(
+            res.headers()
+                .get_all(header::X_FRAME_OPTIONS)
+                .iter()
+                .count(),
+            1
+        );
+        assert_eq!(res.headers().get(header::X_FRAME_OPTIONS).unwrap(), "DENY");
+        assert_eq!(
+            res.headers().get(header::CONTENT_SECURITY_POLICY).unwrap(),
+            "default-src 'none'"
+        );
+        assert_eq!(
+            res.headers().get(header::REFERRER_POLICY).unwrap(),
+            "no-referrer"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_helmet_conflict_strategy_overwrite_and_append() {
+        let test_app = Router::new()
+            .route("/", get(handler_with_headers))
+            .layer(HelmetLayer::new(
+                Helmet::new()
+                    .add(ContentSecurityPolicy::new().script_src(vec!["'self'"]))
+                    .add(XFrameOptions::same_origin())
+                    .conflict_strategy(ConflictStrategy::Overwrite)
+                    .header_conflict_strategy("Content-Security-Policy", ConflictStrategy::Append),
+            ));
+
+        let server = TestServer::new(test_app).expect("failed to create test server");
+
+        let res = server.get("/").await;
+
+        assert_eq!(
+            res.headers()
+                .get_all(header::X_FRAME_OPTIONS)
+                .iter()
+                .count(),
+            1
+        );
+        assert_eq!(
+            res.headers().get(header::X_FRAME_OPTIONS).unwrap(),
+            "SAMEORIGIN"
+        );
+        let csp = res
+            .headers()
+            .get_all(header::CONTENT_SECURITY_POLICY)
+            .iter()
+            .map(|value| value.to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(csp, vec!["default-src 'none'", "script-src 'self'"]);
+    }
+
+    #[tokio::test]
+    async fn test_helmet_content_type_scopes() {
+        let test_app = Router::new()
+            .route("/json", get(|| async { axum::Json(vec![1, 2, 3]) }))
+            .route(
+                "/script",
+                get(|| async {
+                    (
+                        [(header::CONTENT_TYPE, "text/javascript")],
+                        "postMessage(1)",
+                    )
+                }),
+            )
+            .route(
+                "/html",
+                get(|| async { axum::response::Html("<html></html>") }),
+            )
+            .layer(HelmetLayer::new(Helmet::default()));
+
+        let server = TestServer::new(test_app).expect("failed to create test server");
+
+        let res = server.get("/json").await;
+        assert!(res.headers().get(header::X_FRAME_OPTIONS).is_none());
+        assert!(res.headers().get(header::X_XSS_PROTECTION).is_none());
+        assert!(res.headers().get(header::CONTENT_SECURITY_POLICY).is_none());
+        assert!(res.headers().get("origin-agent-cluster").is_none());
+        assert_eq!(
+            res.headers().get(header::X_CONTENT_TYPE_OPTIONS),
+            Some(&HeaderValue::from_static("nosniff"))
+        );
+        assert!(res
+            .headers()
+            .get(header::STRICT_TRANSPORT_SECURITY)
+            .is_some());
+
+        let res = server.get("/script").await;
+        assert!(res.headers().get(header::CONTENT_SECURITY_POLICY).is_some());
+        assert!(res.headers().get(header::X_FRAME_OPTIONS).is_none());
+
+        let res = server.get("/html").await;
+        assert!(res.headers().get(header::CONTENT_SECURITY_POLICY).is_some());
+        assert_eq!(
+            res.headers().get(header::X_FRAME_OPTIONS),
+            Some(&HeaderValue::from_static("SAMEORIGIN"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_helmet_path_rules() {
+        let test_app = Router::new()
+            .route("/admin/users", get(|| async { "users" }))
+            .route("/healthz", get(|| async { "ok" }))
+            .route("/", get(|| async { "Hello, world!" }))
+            .layer(HelmetLayer::new(
+                Helmet::default()
+                    .path("/admin/*", PathRule::new().add(XFrameOptions::deny()))
+                    .path("/healthz", PathRule::exclude_all()),
+            ));
+
+        let server = TestServer::new(test_app).expect("failed to create test server");
+
+        let res = server.get("/admin/users").await;
+        assert_eq!(
+            res.headers().get(header::X_FRAME_OPTIONS),
+            Some(&HeaderValue::from_static("DENY"))
+        );
+        assert!(res.headers().get(header::CONTENT_SECURITY_POLICY).is_some());
+
+        let res = server.get("/healthz").await;
+        assert!(res.headers().get(header::X_FRAME_OPTIONS).is_none());
+        assert!(res.headers().get(header::CONTENT_SECURITY_POLICY).is_none());
+
+        let res = server.get("/").await;
+        assert_eq!(
+            res.headers().get(header::X_FRAME_OPTIONS),
+            Some(&HeaderValue::from_static("SAMEORIGIN"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_helmet_conditions() {
+        let test_app = Router::new()
+            .route(
+                "/",
+                get(|| async { "Hello, world!" }).post(|| async { "posted" }),
+            )
+            .route("/not-modified", get(|| async { StatusCode::NOT_MODIFIED }))
+            .layer(HelmetLayer::new(
+                Helmet::default()
+                    .when(
+                        "Strict-Transport-Security",
+                        !Condition::status(101).or(Condition::status(304)),
+                    )
+                    .add_when(
+                        XPoweredBy::new("axum"),
+                        Condition::method(vec!["GET", "HEAD"]),
+                    )
+                    .remove::<ContentSecurityPolicy>()
+                    .add_when(ContentSecurityPolicy::default(), !Condition::error())
+                    .add_when(
+                        ContentSecurityPolicy::new().default_src(vec!["'none'"]),
+                        Condition::error(),
+                    ),
+            ));
+
+        let server = TestServer::new(test_app).expect("failed to create test server");
+
+        let res = server.get("/").await;
+        assert!(res
+            .headers()
+            .get(header::STRICT_TRANSPORT_SECURITY)
+            .is_some());
+        assert_eq!(
+            res.headers().get("x-powered-by"),
+            Some(&HeaderValue::from_static("axum"))
+        );
+        assert!(res
+            .headers()
+            .get(header::CONTENT_SECURITY_POLICY)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .starts_with("default-src 'self'"));
+
+        let res = server.post("/").await;
+        assert!(res.headers().get("x-powered-by").is_none());
+
+        let res = server.get("/not-modified").await;
+        assert_eq!(res.status_code(), StatusCode::NOT_MODIFIED);
+        assert!(res
+            .headers()
+            .get(header::STRICT_TRANSPORT_SECURITY)
+            .is_none());
+
+        let res = server.get("/missing").expect_failure().await;
+        assert_eq!(
+            res.headers()
+                .get_all(header::CONTENT_SECURITY_POLICY)
+                .iter()
+                .collect::<Vec<_>>(),
+            vec!["default-src 'none'"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_helmet_response_overrides() {
+        let test_app = Router::new()
+            .route(
+                "/widget",
+                get(|| async {
+                    (
+                        axum::Extension(
+                            ResponseOverrides::new()
+                                .skip("X-Frame-Options")
+                                .extend_content_security_policy(
+                                    ContentSecurityPolicy::new()
+                                        .connect_src(vec!["https://widget.example.com"]),
+                                ),
+                        ),
+                        "widget",
+                    )
+                }),
+            )
+            .route("/", get(|| async { "Hello, world!" }))
+            .layer(HelmetLayer::new(Helmet::default()));
+
+        let server = TestServer::new(test_app).expect("failed to create test server");
+
+        let res = server.get("/widget").await;
+        assert!(res.headers().get(header::X_FRAME_OPTIONS).is_none());
+        assert!(res
+            .headers()
+            .get(header::CONTENT_SECURITY_POLICY)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .ends_with("connect-src 'self' https://widget.example.com"));
+
+        let res = server.get("/").await;
+        assert_eq!(
+            res.headers().get(header::X_FRAME_OPTIONS),
+            Some(&HeaderValue::from_static("SAMEORIGIN"))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_helmet_development_loopback() {
+        let test_app = Router::new()
+            .route("/", get(|| async { "Hello, world!" }))
+            .layer(HelmetLayer::new(
+                Helmet::default().development(DevelopmentMode::Loopback),
+            ));
+        let server = |peer: &str| {
+            let test_app = test_app
+                .clone()
+                .layer(Extension(ConnectInfo(SocketAddr::new(
+                    peer.parse().unwrap(),
+                    50000,
+                ))));
+            TestServer::new(test_app).expect("failed to create test server")
+        };
+        let remote = server("192.0.2.1");
+        let server = server("127.0.0.1");
+
+        for host in ["localhost:3000", "127.0.0.1", "[::1]:8080", "app.localhost"] {
+            let res = server
+                .get("/")
+                .add_header(header::HOST, HeaderValue::from_static(host))
+                .await;
+
+            assert!(res
+                .headers()
+                .get(header::STRICT_TRANSPORT_SECURITY)
+                .is_none());
+            let csp = res
+                .headers()
+                .get(header::CONTENT_SECURITY_POLICY)
+                .unwrap()
+                .to_str()
+                .unwrap();
+            assert!(!csp.contains("upgrade-insecure-requests"));
+            assert!(csp.ends_with("; connect-src 'self' ws://localhost:*"));
+            assert_eq!(
+                res.headers().get(header::X_FRAME_OPTIONS).unwrap(),
+                "SAMEORIGIN"
+            );
+        }
+
+        let res = server
+            .get("/")
+            .add_header(header::HOST, HeaderValue::from_static("example.com"))
+            .await;
+
+        assert_eq!(
+            res.headers()
+                .get(header::STRICT_TRANSPORT_SECURITY)
+                .unwrap(),
+            "max-age=15552000; includeSubDomains"
+        );
+        assert!(res
+            .headers()
+            .get(header::CONTENT_SECURITY_POLICY)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .ends_with("upgrade-insecure-requests"));
+
+        // `Host` is set by the client, a reverse proxy on the same machine adds forwarding headers
+        for res in [
+            remote
+                .get("/")
+                .add_header(header::HOST, HeaderValue::from_static("localhost"))
+                .await,
+            server
+                .get("/")
+                .add_header(header::HOST, HeaderValue::from_static("127.0.0.1:8080"))
+                .add_header(
+                    HeaderName::from_static("x-forwarded-for"),
+                    HeaderValue::from_static("192.0.2.1"),
+                )
+                .await,
+        ] {
+            assert!(res
+                .headers()
+                .get(header::STRICT_TRANSPORT_SECURITY)
+                .is_some());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_helmet_hsts_https_only() {
+        use tower::Layer;
+
+        let layer = HelmetLayer::new(
+            Helmet::default()
+                .hsts_https_only(true)
+                .trusted_proxy("10.0.0.0/8"),
+        );
+        let mut service = layer.layer(Router::new().route("/", get(|| async { "Hello, world!" })));
+
+        let mut hsts = |peer: &str, uri: &str, proto: Option<&'static str>| {
+            let mut req = Request::builder().uri(uri);
+            if let Some(proto) = proto {
+                req = req.header("x-forwarded-proto", proto);
+            }
+            let mut req = req.body(axum::body::Body::empty()).unwrap();
+            req.extensions_mut()
+                .insert(ConnectInfo(SocketAddr::new(peer.parse().unwrap(), 443)));
+            let res = service.call(req);
+            async move {
+                res.await
+                    .unwrap()
+                    .headers()
+                    .get(header::STRICT_TRANSPORT_SECURITY)
+                    .cloned()
+            }
+        };
+
+        assert!(hsts("192.0.2.1", "/", None).await.is_none());
+        assert!(hsts("192.0.2.1", "/", Some("https")).await.is_none());
+        assert_eq!(
+            hsts("192.0.2.1", "https://example.com/", None)
+                .await
+                .unwrap(),
+            "max-age=15552000; includeSubDomains"
+        );
+        assert!(hsts("10.0.0.1", "/", Some("https")).await.is_some());
+        assert!(hsts("10.0.0.1", "https://example.com/", Some("http"))
+            .await
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn test_helmet_hsts_https_connection() {
+        let app = |https: bool| {
+            let app = Router::new()
+                .route("/", get(|| async { "Hello, world!" }))
+                .layer(HelmetLayer::new(Helmet::default().hsts_https_only(true)));
+            match https {
+                true => app.layer(axum::Extension(HttpsConnection)),
+                false => app,
+            }
+        };
+
+        let server = TestServer::new(app(true)).expect("failed to create test server");
+        let res = server.get("/").await;
+        assert_eq!(
+            res.headers()
+                .get(header::STRICT_TRANSPORT_SECURITY)
+                .unwrap(),
+            "max-age=15552000; includeSubDomains"
+        );
+
+        let server = TestServer::new(app(false)).expect("failed to create test server");
+        let res = server.get("/").await;
+        assert!(res
+            .headers()
+            .get(header::STRICT_TRANSPORT_SECURITY)
+            .is_none());
+    }
+
+    #[test]
+    fn test_helmet_try_new_invalid_header() {
+        #[derive(Clone)]
+        struct BadName;
+
+        impl helmet_core::Header for BadName {
+            fn name(&self) -> &'static str {
+                "Bad Name"
+            }
+
+            fn value(&self) -> String {
+                "value".to_string()
+            }
+        }
+
+        assert_eq!(
+            HelmetLayer::try_new(Helmet::new().add(BadName)).err(),
+            Some(HelmetError::InvalidHeaderName {
+                name: "Bad Name".to_string()
+            })
+        );
+        assert_eq!(
+            HelmetLayer::try_new(Helmet::new().add(XPoweredBy::new("PHP\r\n4.2.0"))).err(),
+            Some(HelmetError::InvalidHeaderValue {
+                name: "X-Powered-By".to_string(),
+                value: "PHP\r\n4.2.0".to_string()
+            })
+        );
+        assert_eq!(
+            HelmetLayer::try_new(Helmet::default().trusted_proxy("10.0.0.0/33")).err(),
+            Some(HelmetError::InvalidTrustedProxy {
+                value: "10.0.0.0/33".to_string()
+            })
+        );
+        assert!(HelmetLayer::try_new(Helmet::default()).is_ok());
+    }
+
+    #[cfg(feature = "serde")]
+    fn report_server(max_body_size: usize) -> (TestServer, Arc<Mutex<Vec<ViolationReport>>>) {
+        let violations = Arc::new(Mutex::new(Vec::new()));
+        let sink = violations.clone();
+        let receiver =
+            ReportReceiver::new(move |report: ViolationReport| sink.lock().unwrap().push(report))
+                .max_body_size(max_body_size);
+
+        let server = TestServer::new(Router::new().merge(receiver.into_router("/csp-reports")))
+            .expect("failed to create test server");
+        (server, violations)
+    }
+
+    #[cfg(feature = "serde")]
+    fn csp_violations(reports: &Mutex<Vec<ViolationReport>>) -> Vec<CspViolation> {
+        reports
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|report| match report {
+                ViolationReport::Csp(violation) => Some(violation.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn test_report_receiver_csp_report() {
+        let (server, violations) = report_server(64 * 1024);
+
+        let res = server
+            .post("/csp-reports")
+            .text(
+                r#"{"csp-report": {
+                    "document-uri": "https://example.com/",
+                    "blocked-uri": "https://evil.com/script.js",
+                    "violated-directive": "script-src",
+                    "original-policy": "script-src 'self'; report-uri /csp-reports"
+                }}"#,
+            )
+            .content_type("application/csp-report")
+            .await;
+
+        assert_eq!(res.status_code(), 204);
+        let violations = csp_violations(&violations);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].document_uri, "https://example.com/");
+        assert_eq!(
+            violations[0].blocked_uri.as_deref(),
+            Some("https://evil.com/script.js")
+        );
+        assert_eq!(violations[0].effective_directive, "script-src");
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn test_report_receiver_reports_json() {
+        let (server, violations) = report_server(64 * 1024);
+
+        let res = server
+            .post("/csp-reports")
+            .text(
+                r#"[
+                    {"type": "csp-violation", "age": 1, "url": "https://example.com/", "user_agent": "test", "body": {
+                        "documentURL": "https://example.com/",
+                        "blockedURL": "inline",
+                        "effectiveDirective": "style-src-elem",
+                        "originalPolicy": "style-src 'self'; report-to csp",
+                        "disposition": "report",
+                        "lineNumber": 12
+                    }},
+                    {"type": "deprecation", "age": 1, "url": "https://example.com/", "user_agent": "test", "body": {}}
+                ]"#,
+            )
+            .content_type("application/reports+json")
+            .await;
+
+        assert_eq!(res.status_code(), 204);
+        assert!(matches!(
+            &violations.lock().unwrap()[1],
+            ViolationReport::Deprecation(report) if report.url == "https://example.com/"
+        ));
+        let violations = csp_violations(&violations);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].blocked_uri.as_deref(), Some("inline"));
+        assert_eq!(violations[0].disposition.as_deref(), Some("report"));
+        assert_eq!(violations[0].line_number, Some(12));
+    }
+
+    #[cfg(feature = "serde")]
+    #[tokio::test]
+    async fn test_report_receiver_rejects_invalid_requests() {
+        let (server, violations) = report_server(128);
+
+        let res = server
+            .post("/csp-reports")
+            .text("hello")
+            .content_type("text/plain")
+            .await;
+        assert_eq!(res.status_code(), 415);
+
+        let res = server
+            .post("/csp-reports")
+            .text("{")
+            .content_type("application/csp-report")
+            .await;
+        assert_eq!(res.status_code(), 400);
+
+        let res = server
+            .post("/csp-reports")
+            .text(format!(
+                r#"{{"csp-report": {{"document-uri": "https://example.com/{}"}}}}"#,
+                "a".repeat(256)
+            ))
+            .content_type("application/csp-report")
+            .await;
+        assert_eq!(res.status_code(), 413);
+
+        assert!(violations.lock().unwrap().is_empty());
+    }
 }
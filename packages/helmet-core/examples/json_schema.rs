@@ -0,0 +1,9 @@
+//! Prints the JSON Schema of the `Helmet` configuration format.
+//!
+//! ```sh
+//! cargo run -p helmet-core --example json_schema --features serde > packages/helmet-core/helmet.schema.json
+//! ```
+fn main() {
+    let schema = helmet_core::Helmet::json_schema();
+    println!("{}", serde_json::to_string_pretty(&schema).unwrap());
+}
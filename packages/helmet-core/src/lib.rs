@@ -46,17 +46,110 @@
 //! By default if you construct a new instance of `Helmet` it will not set any headers.
 //!
 //! The `helmet-core` crate helps you configure Helmet by providing convenient builders for each header.
-use core::fmt::Display;
+//!
+//! # Loading configuration
+//!
+//! With the `serde` feature, `Helmet` and every built-in header implement `Serialize` and `Deserialize`, so headers can be configured per environment from TOML, YAML or JSON instead of code. The feature also enables `parse_reports` for violation reports and `Helmet::from_helmetjs_options`, without it `helmet-core` doesn't depend on `serde`, `serde_json` or `schemars`. Each header is keyed by its name in snake_case, headers that are not set are not sent. Unknown keys, directives and values are rejected, and the loaded headers are validated like [`Helmet::validate`] does. Directives Helmet doesn't know, e.g. parsed from a header string, are set in `other_directives`, and `Permissions-Policy` features that are not listed in [`PermissionsPolicyFeature`] are kept by name.
+//!
+//! ```toml
+//! referrer_policy = "strict-origin-when-cross-origin"
+//! x_frame_options = "deny"
+//!
+//! [content_security_policy.directives]
+//! default-src = ["'self'"]
+//! img-src = ["'self'", "data:"]
+//! upgrade-insecure-requests = []
+//!
+//! [strict_transport_security]
+//! max_age = 63072000
+//! include_sub_domains = true
+//! ```
+//!
+//! The format covers the headers and the settings of `Helmet`, not everything it can be built with. A configuration round-trips without the order the headers were added in, they are sent in the order of the format. Path rules, conditional headers, custom headers and [`StrictTransportSecuritySchedule`] can't be serialized, serializing a `Helmet` that has them fails. A JSON Schema of the format is returned by `Helmet::json_schema` and checked in as `helmet.schema.json`, for validating configuration files in editors.
+//!
+//! ```
+//! # #[cfg(feature = "serde")]
+//! # {
+//! use helmet_core::Helmet;
+//!
+//! let helmet: Helmet = serde_json::from_str(r#"{
+//!     "content_security_policy": {
+//!         "directives": { "default-src": ["'self'"], "upgrade-insecure-requests": [] }
+//!     },
+//!     "x_frame_options": "deny",
+//!     "header_conflict_strategies": { "Content-Security-Policy": "append" }
+//! }"#).unwrap();
+//!
+//! let headers = helmet.iter().map(|header| (header.name(), header.value())).collect::<Vec<_>>();
+//! assert_eq!(headers, vec![
+//!     ("Content-Security-Policy", "default-src 'self'; upgrade-insecure-requests".to_string()),
+//!     ("X-Frame-Options", "DENY".to_string()),
+//! ]);
+//!
+//! let error = serde_json::from_str::<Helmet>(r#"{ "x_frame_option": "deny" }"#).unwrap_err();
+//! assert!(error.to_string().starts_with("unknown field `x_frame_option`"));
+//!
+//! let error = serde_json::from_str::<Helmet>(r#"{
+//!     "content_security_policy": { "directives": { "defualt-src": ["'self'"] } }
+//! }"#).unwrap_err();
+//! assert!(error.to_string().starts_with("unknown field `defualt-src`"));
+//!
+//! let error = serde_json::from_str::<Helmet>(r#"{
+//!     "cross_origin_opener_policy": { "policy": "same-orign" }
+//! }"#).unwrap_err();
+//! assert!(error.to_string().starts_with("unknown variant `same-orign`"));
+//!
+//! let json = serde_json::to_string(&Helmet::default()).unwrap();
+//! let roundtrip: Helmet = serde_json::from_str(&json).unwrap();
+//! assert_eq!(serde_json::to_string(&roundtrip).unwrap(), json);
+//!
+//! use helmet_core::{ContentSecurityPolicy, PermissionsPolicy, PermissionsPolicyFeature};
+//!
+//! let helmet = Helmet::new()
+//!     .add("default-src 'self'; fenced-frame-src 'none'; block-all-mixed-content".parse::<ContentSecurityPolicy>().unwrap())
+//!     .add(PermissionsPolicy::new().deny(PermissionsPolicyFeature::Other("clipboard-read".to_string())));
+//! let json = serde_json::to_string(&helmet).unwrap();
+//! assert!(json.contains(r#""other_directives":{"fenced-frame-src":["'none'"],"block-all-mixed-content":[]}"#));
+//! assert!(json.contains(r#""clipboard-read":[]"#));
+//! let roundtrip: Helmet = serde_json::from_str(&json).unwrap();
+//! assert_eq!(serde_json::to_string(&roundtrip).unwrap(), json);
+//! assert_eq!(
+//!     roundtrip.iter().map(|header| header.value()).collect::<Vec<_>>(),
+//!     helmet.iter().map(|header| header.value()).collect::<Vec<_>>(),
+//! );
+//!
+//! use helmet_core::{CrossOriginEmbedderPolicy, CrossOriginOpenerPolicy};
+//!
+//! let helmet = Helmet::new()
+//!     .add(CrossOriginEmbedderPolicy::require_corp())
+//!     .add(CrossOriginOpenerPolicy::same_origin().report_to("coop"));
+//! let json = serde_json::to_string(&helmet).unwrap();
+//! assert_eq!(json, r#"{"cross_origin_embedder_policy":{"policy":"require-corp"},"cross_origin_opener_policy":{"policy":"same-origin","report_to":"coop"}}"#);
+//! let roundtrip: Helmet = serde_json::from_str(&json).unwrap();
+//! assert_eq!(
+//!     roundtrip.iter().map(|header| header.value()).collect::<Vec<_>>(),
+//!     vec!["require-corp", "same-origin; report-to=\"coop\""],
+//! );
+//! # }
+//! ```
+use core::{any::Any, fmt::Display, str::FromStr};
+use std::{borrow::Cow, net::IpAddr};
+
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use dyn_clone::DynClone;
 
 /// Header trait
 ///
 /// Allows custom headers to be added to the response
 ///
+/// Headers are `Send + Sync` and `Clone`, so a `Helmet` can be cloned and shared between worker threads.
+///
 /// # Examples
 ///
 /// ```
 /// use helmet_core::Header;
 ///
+/// #[derive(Clone, Debug)]
 /// struct MyHeader;
 ///
 /// impl Header for MyHeader {
@@ -69,544 +162,942 @@ use core::fmt::Display;
 ///  }
 /// }
 /// ```
-pub trait Header {
+pub trait Header: Any + Send + Sync + DynClone {
     fn name(&self) -> &'static str;
     fn value(&self) -> String;
+
+    /// Whether `Helmet` may send more than one header with this name
+    ///
+    /// [`Helmet::add`] always replaces the header with the same name. Headers that are meaningful more than once, such as `Content-Security-Policy`, where every policy is enforced, return `true` so they can be stacked with [`Helmet::add_additional`].
+    fn allows_multiple(&self) -> bool {
+        false
+    }
+
+    /// Template for rendering the header value with a per-request nonce
+    ///
+    /// Headers that embed a CSP nonce return `Some`, which tells framework adapters to generate a fresh [`Nonce`] for every request and render the value from the template instead of reusing `value()`.
+    fn nonce_template(&self) -> Option<NonceTemplate> {
+        None
+    }
+
+    /// Whether `value()` depends on the current time, e.g. [`StrictTransportSecuritySchedule`]
+    ///
+    /// Framework adapters compile the other headers once, and call `value()` of these headers for every response.
+    fn changes_over_time(&self) -> bool {
+        false
+    }
+
+    /// Check the header for values that are valid HTTP, but break the header's own grammar
+    ///
+    /// Called by [`Helmet::validate`] in addition to checking that the name and value are valid HTTP.
+    fn validate(&self) -> Result<(), HelmetError> {
+        Ok(())
+    }
+
+    /// Responses the header is sent on
+    ///
+    /// Framework adapters skip the header on responses outside the scope, based on their `Content-Type`, see [`ResponseKind::from_content_type`]. Headers that only change how a document is rendered, such as `X-Frame-Options`, return [`HeaderScope::Document`].
+    fn scope(&self) -> HeaderScope {
+        HeaderScope::Any
+    }
+
+    /// Condition the request and response must meet for the header to be sent, see [`Conditional`]
+    fn condition(&self) -> Option<&Condition> {
+        None
+    }
 }
 
-/// Manages `Cross-Origin-Embedder-Policy` header
-///
-/// The Cross-Origin-Embedder-Policy HTTP response header prevents a document from loading any cross-origin resources that do not explicitly grant the document permission (via CORS headers) to load them.
-///
-/// # Values
-///
-/// - unsafe-none: The document is not subject to any Cross-Origin-Embedder-Policy restrictions.
-/// - require-corp: The document is subject to Cross-Origin-Embedder-Policy restrictions.
-/// - credentialless: The document is subject to Cross-Origin-Embedder-Policy restrictions, and is not allowed to request credentials (e.g. cookies, certificates, HTTP authentication) from the user.
-///
-/// # Examples
+dyn_clone::clone_trait_object!(Header);
+
+/// Responses a header is sent on, see [`Header::scope`] and [`Helmet::header_scope`]
 ///
 /// ```
-/// use helmet_core::CrossOriginEmbedderPolicy;
-///
-/// let cross_origin_embedder_policy = CrossOriginEmbedderPolicy::unsafe_none();
-///
-/// let cross_origin_embedder_policy = CrossOriginEmbedderPolicy::require_corp();
+/// use helmet_core::{HeaderScope, ResponseKind};
 ///
-/// let cross_origin_embedder_policy = CrossOriginEmbedderPolicy::credentialless();
+/// assert!(HeaderScope::Document.includes(ResponseKind::Document));
+/// assert!(!HeaderScope::Document.includes(ResponseKind::Script));
+/// assert!(HeaderScope::DocumentOrScript.includes(ResponseKind::Script));
+/// assert!(!HeaderScope::DocumentOrScript.includes(ResponseKind::Other));
+/// assert!(HeaderScope::Any.includes(ResponseKind::Other));
 /// ```
-#[derive(Clone)]
-pub enum CrossOriginEmbedderPolicy {
-    UnsafeNone,
-    RequireCorp,
-    Credentialless,
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema),
+    serde(rename_all = "kebab-case"),
+    schemars(description = "Responses a header is sent on")
+)]
+pub enum HeaderScope {
+    /// Every response.
+    #[default]
+    Any,
+    /// Documents only, see [`ResponseKind::Document`].
+    Document,
+    /// Documents and scripts, which get their own policy when they are loaded as workers.
+    DocumentOrScript,
 }
 
-impl CrossOriginEmbedderPolicy {
-    pub fn unsafe_none() -> Self {
-        Self::UnsafeNone
-    }
-
-    pub fn require_corp() -> Self {
-        Self::RequireCorp
+impl HeaderScope {
+    /// Whether a header with this scope is sent on a response of `kind`
+    pub fn includes(self, kind: ResponseKind) -> bool {
+        match self {
+            Self::Any => true,
+            Self::Document => kind == ResponseKind::Document,
+            Self::DocumentOrScript => kind != ResponseKind::Other,
+        }
     }
+}
 
-    pub fn credentialless() -> Self {
-        Self::Credentialless
-    }
+/// Kind of a response, used to skip headers outside their [`HeaderScope`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResponseKind {
+    /// HTML, XML, SVG, PDF, plain text and other types browsers render when navigated to.
+    Document,
+    /// JavaScript, which may be loaded as a worker.
+    Script,
+    /// Subresources such as JSON, images, fonts, stylesheets and media.
+    Other,
 }
 
-impl Display for CrossOriginEmbedderPolicy {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            CrossOriginEmbedderPolicy::UnsafeNone => write!(f, "unsafe-none"),
-            CrossOriginEmbedderPolicy::RequireCorp => write!(f, "require-corp"),
-            CrossOriginEmbedderPolicy::Credentialless => write!(f, "credentialless"),
+impl ResponseKind {
+    /// Kind of a response with the given `Content-Type`
+    ///
+    /// A missing or malformed `Content-Type` is treated as a document, as browsers may sniff such responses as HTML, so every header is sent.
+    ///
+    /// ```
+    /// use helmet_core::ResponseKind;
+    ///
+    /// assert_eq!(ResponseKind::from_content_type(Some("text/html; charset=utf-8")), ResponseKind::Document);
+    /// assert_eq!(ResponseKind::from_content_type(Some("image/svg+xml")), ResponseKind::Document);
+    /// assert_eq!(ResponseKind::from_content_type(Some("text/javascript")), ResponseKind::Script);
+    /// assert_eq!(ResponseKind::from_content_type(Some("application/json")), ResponseKind::Other);
+    /// assert_eq!(ResponseKind::from_content_type(Some("image/png")), ResponseKind::Other);
+    /// assert_eq!(ResponseKind::from_content_type(Some("text/css")), ResponseKind::Other);
+    /// assert_eq!(ResponseKind::from_content_type(Some("")), ResponseKind::Document);
+    /// assert_eq!(ResponseKind::from_content_type(None), ResponseKind::Document);
+    /// ```
+    pub fn from_content_type(content_type: Option<&str>) -> Self {
+        let essence = content_type
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().to_ascii_lowercase())
+            .unwrap_or_default();
+        let Some((kind, subtype)) = essence.split_once('/') else {
+            return Self::Document;
+        };
+        match (kind, subtype) {
+            (
+                "text" | "application",
+                "javascript" | "ecmascript" | "x-javascript" | "x-ecmascript",
+            ) => Self::Script,
+            ("text", "css" | "csv" | "event-stream") => Self::Other,
+            ("text", _) => Self::Document,
+            ("application", "xhtml+xml" | "xml" | "pdf") => Self::Document,
+            (_, subtype) if subtype.ends_with("+xml") => Self::Document,
+            _ => Self::Other,
         }
     }
 }
 
-impl Header for CrossOriginEmbedderPolicy {
-    fn name(&self) -> &'static str {
-        "Cross-Origin-Embedder-Policy"
+/// Request and response a [`Condition`] is evaluated against, implemented by framework adapters
+pub trait Exchange {
+    /// Request method, e.g. `GET`
+    fn method(&self) -> &str;
+    /// Response status code
+    fn status(&self) -> u16;
+    /// First value of a request header. Header names are case-insensitive.
+    fn request_header(&self, name: &str) -> Option<&str>;
+    /// First value of a response header. Header names are case-insensitive.
+    fn response_header(&self, name: &str) -> Option<&str>;
+}
+
+/// [`Exchange`] of a request and its response, with the headers looked up by closures over the header maps of a framework
+///
+/// ```
+/// use helmet_core::{Condition, RequestResponse};
+///
+/// let request_headers = [("Upgrade", "websocket")];
+/// let exchange = RequestResponse {
+///     method: "GET",
+///     status: 101,
+///     request_header: |name: &str| {
+///         request_headers
+///             .iter()
+///             .find(|(n, _)| n.eq_ignore_ascii_case(name))
+///             .map(|(_, value)| *value)
+///     },
+///     response_header: |_: &str| None,
+/// };
+///
+/// assert!(Condition::request_header("upgrade").matches(&exchange));
+/// ```
+pub struct RequestResponse<'a, Req, Res> {
+    pub method: &'a str,
+    pub status: u16,
+    /// First value of a request header
+    pub request_header: Req,
+    /// First value of a response header
+    pub response_header: Res,
+}
+
+impl<'a, Req, Res> Exchange for RequestResponse<'a, Req, Res>
+where
+    Req: Fn(&str) -> Option<&'a str>,
+    Res: Fn(&str) -> Option<&'a str>,
+{
+    fn method(&self) -> &str {
+        self.method
     }
 
-    fn value(&self) -> String {
-        self.to_string()
+    fn status(&self) -> u16 {
+        self.status
+    }
+
+    fn request_header(&self, name: &str) -> Option<&str> {
+        (self.request_header)(name)
+    }
+
+    fn response_header(&self, name: &str) -> Option<&str> {
+        (self.response_header)(name)
     }
 }
 
-/// Manages `Cross-Origin-Opener-Policy` header
+/// Predicate over the request method, the response status and the request and response headers, see [`Helmet::add_when`]
 ///
-/// The Cross-Origin-Opener-Policy HTTP response header restricts how selected resources are allowed to interact with the document's browsing context in response to user navigation. Each resource can declare an opener policy which applies to the resource's corresponding browsing context.
-///
-/// # Values
+/// Conditions are combined with [`Condition::and`], [`Condition::or`] and `!`.
 ///
-/// - same-origin: The resource's browsing context is the same-origin as the document's browsing context.
-/// - same-origin-allow-popups: The resource's browsing context is the same-origin as the document's browsing context, and the resource is allowed to open new browsing contexts.
-/// - unsafe-none: The resource's browsing context is cross-origin with the document's browsing context.
+/// ```
+/// use helmet_core::{Condition, Exchange};
 ///
-/// # Examples
+/// struct Upgrade;
 ///
-/// ```
-/// use helmet_core::CrossOriginOpenerPolicy;
+/// impl Exchange for Upgrade {
+///     fn method(&self) -> &str { "GET" }
+///     fn status(&self) -> u16 { 101 }
+///     fn request_header(&self, name: &str) -> Option<&str> {
+///         name.eq_ignore_ascii_case("upgrade").then_some("websocket")
+///     }
+///     fn response_header(&self, _: &str) -> Option<&str> { None }
+/// }
 ///
-/// let cross_origin_opener_policy = CrossOriginOpenerPolicy::same_origin();
+/// assert!(Condition::method(vec!["GET", "HEAD"]).matches(&Upgrade));
+/// assert!(!(!Condition::status(101)).matches(&Upgrade));
+/// assert!(!Condition::status(304).or(Condition::error()).matches(&Upgrade));
+/// assert!(Condition::request_header_value("Upgrade", "WebSocket").matches(&Upgrade));
+/// assert!(!Condition::response_header("Content-Type").matches(&Upgrade));
 /// ```
-#[derive(Clone)]
-pub enum CrossOriginOpenerPolicy {
-    SameOrigin,
-    SameOriginAllowPopups,
-    UnsafeNone,
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Condition {
+    /// The request method is one of the methods.
+    Method(Vec<String>),
+    /// The response status is within the range, inclusive.
+    Status { min: u16, max: u16 },
+    /// The request has the header, with the value if given.
+    RequestHeader { name: String, value: Option<String> },
+    /// The response has the header, with the value if given.
+    ResponseHeader { name: String, value: Option<String> },
+    /// The condition doesn't match.
+    Not(Box<Condition>),
+    /// Every condition matches.
+    All(Vec<Condition>),
+    /// At least one condition matches.
+    Any(Vec<Condition>),
 }
 
-impl CrossOriginOpenerPolicy {
-    pub fn same_origin() -> Self {
-        Self::SameOrigin
+impl Condition {
+    /// The request method is one of `methods`, e.g. `vec!["GET", "HEAD"]`
+    pub fn method(methods: Vec<&str>) -> Self {
+        Self::Method(methods.into_iter().map(str::to_string).collect())
     }
 
-    pub fn same_origin_allow_popups() -> Self {
-        Self::SameOriginAllowPopups
+    /// The response has the status `code`, e.g. `304` for `Not Modified` or `101` for WebSocket upgrades
+    pub fn status(code: u16) -> Self {
+        Self::Status {
+            min: code,
+            max: code,
+        }
     }
 
-    pub fn unsafe_none() -> Self {
-        Self::UnsafeNone
+    /// The response status is within `range`, e.g. `500..=599`
+    pub fn status_range(range: std::ops::RangeInclusive<u16>) -> Self {
+        Self::Status {
+            min: *range.start(),
+            max: *range.end(),
+        }
     }
-}
 
-impl Display for CrossOriginOpenerPolicy {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            CrossOriginOpenerPolicy::SameOrigin => write!(f, "same-origin"),
-            CrossOriginOpenerPolicy::SameOriginAllowPopups => write!(f, "same-origin-allow-popups"),
-            CrossOriginOpenerPolicy::UnsafeNone => write!(f, "unsafe-none"),
-        }
+    /// The response is a client or server error, i.e. the status is `400` to `599`
+    pub fn error() -> Self {
+        Self::status_range(400..=599)
     }
-}
 
-impl Header for CrossOriginOpenerPolicy {
-    fn name(&self) -> &'static str {
-        "Cross-Origin-Opener-Policy"
+    /// The request has the header
+    pub fn request_header(name: &str) -> Self {
+        Self::RequestHeader {
+            name: name.to_string(),
+            value: None,
+        }
     }
 
-    fn value(&self) -> String {
-        self.to_string()
+    /// The request header has the value, compared case-insensitively with each comma-separated element, e.g. `Connection: keep-alive, Upgrade` has the value `upgrade`
+    pub fn request_header_value(name: &str, value: &str) -> Self {
+        Self::RequestHeader {
+            name: name.to_string(),
+            value: Some(value.to_string()),
+        }
     }
-}
 
-/// Manages `Cross-Origin-Resource-Policy` header
-///
-/// The Cross-Origin-Resource-Policy HTTP response header conveys a desire that the browser blocks no-cors cross-origin/cross-site requests to the given resource.
-///
-/// # Values
-///
-/// - same-origin: The resource is same-origin to the document.
-/// - same-site: The resource is same-site to the document.
-/// - cross-origin: The resource is cross-origin to the document.
-///
-/// # Examples
-///
-/// ```
-/// use helmet_core::CrossOriginResourcePolicy;
-///
-/// let cross_origin_resource_policy = CrossOriginResourcePolicy::same_origin();
-/// ```
-#[derive(Clone)]
-pub enum CrossOriginResourcePolicy {
-    SameOrigin,
-    SameSite,
-    CrossOrigin,
-}
+    /// The response has the header
+    pub fn response_header(name: &str) -> Self {
+        Self::ResponseHeader {
+            name: name.to_string(),
+            value: None,
+        }
+    }
 
-impl CrossOriginResourcePolicy {
-    pub fn same_origin() -> Self {
-        Self::SameOrigin
+    /// The response header has the value, compared like [`Condition::request_header_value`]
+    pub fn response_header_value(name: &str, value: &str) -> Self {
+        Self::ResponseHeader {
+            name: name.to_string(),
+            value: Some(value.to_string()),
+        }
     }
 
-    pub fn same_site() -> Self {
-        Self::SameSite
+    /// Both conditions match
+    pub fn and(self, other: Condition) -> Self {
+        match self {
+            Self::All(mut conditions) => {
+                conditions.push(other);
+                Self::All(conditions)
+            }
+            condition => Self::All(vec![condition, other]),
+        }
     }
 
-    pub fn cross_origin() -> Self {
-        Self::CrossOrigin
+    /// Either condition matches
+    pub fn or(self, other: Condition) -> Self {
+        match self {
+            Self::Any(mut conditions) => {
+                conditions.push(other);
+                Self::Any(conditions)
+            }
+            condition => Self::Any(vec![condition, other]),
+        }
     }
-}
 
-impl Display for CrossOriginResourcePolicy {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    /// Whether the condition matches the request and response
+    pub fn matches<E: Exchange + ?Sized>(&self, exchange: &E) -> bool {
+        let has_value = |header: Option<&str>, value: &Option<String>| match (header, value) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(header), Some(value)) => header
+                .split(',')
+                .any(|element| element.trim().eq_ignore_ascii_case(value)),
+        };
         match self {
-            CrossOriginResourcePolicy::SameOrigin => write!(f, "same-origin"),
-            CrossOriginResourcePolicy::SameSite => write!(f, "same-site"),
-            CrossOriginResourcePolicy::CrossOrigin => write!(f, "cross-origin"),
+            Self::Method(methods) => methods.iter().any(|method| method == exchange.method()),
+            Self::Status { min, max } => (*min..=*max).contains(&exchange.status()),
+            Self::RequestHeader { name, value } => has_value(exchange.request_header(name), value),
+            Self::ResponseHeader { name, value } => {
+                has_value(exchange.response_header(name), value)
+            }
+            Self::Not(condition) => !condition.matches(exchange),
+            Self::All(conditions) => conditions
+                .iter()
+                .all(|condition| condition.matches(exchange)),
+            Self::Any(conditions) => conditions
+                .iter()
+                .any(|condition| condition.matches(exchange)),
         }
     }
 }
 
-impl Header for CrossOriginResourcePolicy {
-    fn name(&self) -> &'static str {
-        "Cross-Origin-Resource-Policy"
-    }
+impl std::ops::Not for Condition {
+    type Output = Condition;
 
-    fn value(&self) -> String {
-        self.to_string()
+    fn not(self) -> Self::Output {
+        match self {
+            Self::Not(condition) => *condition,
+            condition => Self::Not(Box::new(condition)),
+        }
     }
 }
 
-/// Manages `Origin-Agent-Cluster` header
-///
-/// The Origin-Agent-Cluster HTTP request header indicates that the client prefers an "origin agent cluster" (OAC) for the origin of the resource being requested. An OAC is a cluster of servers that are controlled by the same entity as the origin server, and that are geographically close to the client. The OAC is used to provide the client with a better experience, for example by serving content from a server that is close to the client, or by serving content that is optimized for the client's device.
+/// Header that is only sent when a [`Condition`] matches, see [`Helmet::add_when`]
 ///
-/// # Values
-///
-/// - 0: The client does not prefer an OAC.
-/// - 1: The client prefers an OAC.
-///
-/// # Examples
+/// The header keeps its name, value and scope. [`Helmet::get`] and [`Helmet::remove`] look for the type of the header, so they don't find it wrapped in `Conditional`.
 ///
 /// ```
-/// use helmet_core::OriginAgentCluster;
+/// use helmet_core::{Condition, Conditional, Header, XFrameOptions};
 ///
-/// let origin_agent_cluster = OriginAgentCluster::new(true);
+/// let header = Conditional::new(XFrameOptions::deny(), Condition::method(vec!["GET"]));
+///
+/// assert_eq!(header.name(), "X-Frame-Options");
+/// assert_eq!(header.condition(), Some(&Condition::method(vec!["GET"])));
 /// ```
 #[derive(Clone)]
-pub struct OriginAgentCluster(bool);
+pub struct Conditional {
+    header: Box<dyn Header>,
+    condition: Condition,
+}
 
-impl OriginAgentCluster {
-    pub fn new(prefer_mobile_experience: bool) -> Self {
-        Self(prefer_mobile_experience)
+impl Conditional {
+    /// Send `header` only when `condition` matches
+    pub fn new(header: impl Header + 'static, condition: Condition) -> Self {
+        Self::from_boxed(Box::new(header), condition)
     }
-}
 
-impl Display for OriginAgentCluster {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.0 {
-            write!(f, "?1")
-        } else {
-            write!(f, "?0")
+    // Unwraps conditional headers, so the conditions of nested headers are combined.
+    fn from_boxed(header: Box<dyn Header>, condition: Condition) -> Self {
+        match (header.as_ref() as &dyn Any).downcast_ref::<Conditional>() {
+            Some(conditional) => Self {
+                header: conditional.header.clone(),
+                condition: conditional.condition.clone().and(condition),
+            },
+            None => Self { header, condition },
         }
     }
+
+    /// The wrapped header
+    pub fn header(&self) -> &dyn Header {
+        self.header.as_ref()
+    }
 }
 
-impl Header for OriginAgentCluster {
+impl Header for Conditional {
     fn name(&self) -> &'static str {
-        "Origin-Agent-Cluster"
+        self.header.name()
     }
 
     fn value(&self) -> String {
-        self.to_string()
+        self.header.value()
+    }
+
+    fn allows_multiple(&self) -> bool {
+        self.header.allows_multiple()
+    }
+
+    fn nonce_template(&self) -> Option<NonceTemplate> {
+        self.header.nonce_template()
+    }
+
+    fn changes_over_time(&self) -> bool {
+        self.header.changes_over_time()
+    }
+
+    fn validate(&self) -> Result<(), HelmetError> {
+        self.header.validate()
+    }
+
+    fn scope(&self) -> HeaderScope {
+        self.header.scope()
+    }
+
+    fn condition(&self) -> Option<&Condition> {
+        Some(&self.condition)
     }
 }
 
-/// Manages `Referrer-Policy` header
+/// Changes to the headers of a single response, attached to the response by the handler
 ///
-/// The Referrer-Policy HTTP response header controls how much referrer information (sent via the Referer header) should be included with requests.
+/// Framework adapters take it from the response extensions and apply it to the configured headers, see [`Helmet::with_overrides`]. This keeps per-route exceptions, e.g. an embeddable widget, next to the handler that needs them.
 ///
-/// # Values
+/// Overrides are checked when they are built, see [`ResponseOverrides::validate`]. Invalid overrides are not applied: adapters log the error and send the configured headers.
 ///
-/// - no-referrer: The Referer header will be omitted entirely. No referrer information is sent along with requests.
-/// - no-referrer-when-downgrade: The Referer header will be omitted entirely. However, if the protected resource URL scheme is HTTPS, then the full path will still be sent as a referrer.
-/// - origin: Only send the origin of the document as the referrer in all cases. The document https://example.com/page.html will send the referrer https://example.com/.
-/// - origin-when-cross-origin: Send a full URL when performing a same-origin request, but only send the origin of the document for other cases.
-/// - same-origin: A referrer will be sent for same-site origins, but cross-origin requests will contain no referrer information.
-/// - strict-origin: Only send the origin of the document as the referrer when the protocol security level stays the same (HTTPS→HTTPS), but don't send it to a less secure destination (HTTPS→HTTP).
-/// - strict-origin-when-cross-origin: Send a full URL when performing a same-origin request, only send the origin when the protocol security level stays the same (HTTPS→HTTPS), and send no header to a less secure destination (HTTPS→HTTP).
-/// - unsafe-url: Send a full URL (stripped from parameters) when performing a same-origin or cross-origin request. This policy will leak origins and paths from TLS-protected resources to insecure origins. Carefully consider the impact of this setting.
+/// ```
+/// use helmet_core::{ContentSecurityPolicy, Header, Helmet, ResponseOverrides, XFrameOptions};
 ///
-/// # Examples
+/// let overrides = ResponseOverrides::new()
+///     .skip("X-Frame-Options")
+///     .extend_content_security_policy(
+///         ContentSecurityPolicy::new()
+///             .frame_ancestors(vec!["https://partner.example.com"])
+///             .connect_src(vec!["https://widget.example.com"]),
+///     );
 ///
-/// ```
-/// use helmet_core::ReferrerPolicy;
+/// let helmet = Helmet::default().with_overrides(&overrides);
 ///
-/// let referrer_policy = ReferrerPolicy::no_referrer();
+/// assert!(helmet.get::<XFrameOptions>().is_none());
+/// let policy = helmet.get::<ContentSecurityPolicy>().unwrap().value();
+/// assert!(policy.contains("frame-ancestors 'self' https://partner.example.com"));
+/// assert!(policy.ends_with("connect-src 'self' https://widget.example.com"));
 /// ```
-#[derive(Clone)]
-pub enum ReferrerPolicy {
-    NoReferrer,
-    NoReferrerWhenDowngrade,
-    Origin,
-    OriginWhenCrossOrigin,
-    SameOrigin,
-    StrictOrigin,
-    StrictOriginWhenCrossOrigin,
-    UnsafeUrl,
+#[derive(Clone, Default)]
+pub struct ResponseOverrides {
+    skipped: Vec<String>,
+    headers: Vec<Box<dyn Header>>,
+    policy: Option<ContentSecurityPolicy<'static>>,
+    // first override that can't be applied
+    error: Option<HelmetError>,
 }
 
-impl ReferrerPolicy {
-    pub fn no_referrer() -> Self {
-        Self::NoReferrer
+impl ResponseOverrides {
+    /// Create overrides that don't change any header
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    pub fn no_referrer_when_downgrade() -> Self {
-        Self::NoReferrerWhenDowngrade
+    /// Don't send the headers with the given name. Header names are case-insensitive.
+    pub fn skip(mut self, name: &str) -> Self {
+        self.skipped.push(name.to_string());
+        self
     }
 
-    pub fn origin() -> Self {
-        Self::Origin
+    /// Send the header instead of every configured header with the same name
+    ///
+    /// The header can't have a nonce or a condition: the nonce of a response is generated before the handler runs, and the handler already decides which response the header is sent with.
+    pub fn replace(mut self, header: impl Header + 'static) -> Self {
+        self.check(&header);
+        self.headers
+            .retain(|existing| !existing.name().eq_ignore_ascii_case(header.name()));
+        self.headers.push(Box::new(header));
+        self
     }
 
-    pub fn origin_when_cross_origin() -> Self {
-        Self::OriginWhenCrossOrigin
+    /// Allow the sources of `policy` in every configured `Content-Security-Policy`
+    ///
+    /// Sources of a directive set by both policies are combined, directives only set by the configured policy are kept. A fetch directive only set by `policy` starts from the sources it falls back to in the configured policy, e.g. those of `default-src`, and is left out when the configured policy doesn't restrict it, like directives without a fallback, e.g. `frame-ancestors`. Extending a policy never makes it stricter.
+    ///
+    /// `policy` can't add a nonce, configure `script_nonce` or `style_nonce` on the configured policy instead.
+    pub fn extend_content_security_policy(
+        mut self,
+        policy: ContentSecurityPolicy<'static>,
+    ) -> Self {
+        self.check(&policy);
+        self.policy = Some(match self.policy.take() {
+            Some(existing) => {
+                existing.merge_directives(policy, ContentSecurityPolicyDirective::union)
+            }
+            None => policy,
+        });
+        self
     }
 
-    pub fn same_origin() -> Self {
-        Self::SameOrigin
+    /// Check that the overrides can be applied to a response
+    ///
+    /// ```
+    /// use helmet_core::{Condition, Conditional, ContentSecurityPolicy, HelmetError, ResponseOverrides, XFrameOptions};
+    ///
+    /// assert!(ResponseOverrides::new().replace(XFrameOptions::deny()).validate().is_ok());
+    ///
+    /// let overrides = ResponseOverrides::new()
+    ///     .extend_content_security_policy(ContentSecurityPolicy::new().script_nonce());
+    /// assert!(matches!(overrides.validate(), Err(HelmetError::InvalidOverride { .. })));
+    ///
+    /// let overrides = ResponseOverrides::new()
+    ///     .replace(Conditional::new(XFrameOptions::deny(), Condition::status(200)));
+    /// assert!(matches!(overrides.validate(), Err(HelmetError::InvalidOverride { .. })));
+    /// ```
+    pub fn validate(&self) -> Result<(), HelmetError> {
+        match &self.error {
+            Some(error) => Err(error.clone()),
+            None => Ok(()),
+        }
     }
 
-    pub fn strict_origin() -> Self {
-        Self::StrictOrigin
+    // Keeps the first error, so the overrides aren't checked again for every response
+    fn check(&mut self, header: &dyn Header) {
+        if self.error.is_some() {
+            return;
+        }
+        let invalid = |message: &str| HelmetError::InvalidOverride {
+            name: header.name().to_string(),
+            message: message.to_string(),
+        };
+        self.error = if header.nonce_template().is_some() {
+            Some(invalid(
+                "the nonce of a response is generated before the handler runs",
+            ))
+        } else if header.condition().is_some() {
+            Some(invalid(
+                "the handler already decides which response the header is sent with",
+            ))
+        } else {
+            validate_header(header).err()
+        };
     }
+}
 
-    pub fn strict_origin_when_cross_origin() -> Self {
-        Self::StrictOriginWhenCrossOrigin
+impl std::fmt::Debug for ResponseOverrides {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ResponseOverrides")
+            .field("skipped", &self.skipped)
+            .field("headers", &Headers(&self.headers))
+            .field(
+                "policy",
+                &self.policy.as_ref().map(|policy| policy.to_string()),
+            )
+            .field("error", &self.error)
+            .finish()
     }
+}
 
-    pub fn unsafe_url() -> Self {
-        Self::UnsafeUrl
+/// Cryptographically random nonce for a single request
+///
+/// Used to allow specific inline `<script>` and `<style>` elements without resorting to `'unsafe-inline'`. Framework adapters generate one per request and make it available to handlers, so it can be rendered into the `nonce` attribute of the element.
+///
+/// # Examples
+///
+/// ```
+/// use helmet_core::Nonce;
+///
+/// let nonce = Nonce::generate();
+///
+/// assert_ne!(nonce, Nonce::generate());
+/// assert_eq!(nonce.to_source(), format!("'nonce-{}'", nonce));
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Nonce(String);
+
+impl Nonce {
+    /// Generate a new nonce from 128 bits of OS randomness, base64 encoded.
+    pub fn generate() -> Self {
+        let mut bytes = [0u8; 16];
+        getrandom::getrandom(&mut bytes).expect("failed to generate CSP nonce");
+        Self(BASE64.encode(bytes))
+    }
+
+    /// The base64 encoded nonce, as used in the `nonce` attribute of an element.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// The nonce as a CSP source expression, e.g. `'nonce-cmFuZG9t'`.
+    pub fn to_source(&self) -> String {
+        format!("'nonce-{}'", self.0)
     }
 }
 
-impl Display for ReferrerPolicy {
+impl Display for Nonce {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ReferrerPolicy::NoReferrer => write!(f, "no-referrer"),
-            ReferrerPolicy::NoReferrerWhenDowngrade => write!(f, "no-referrer-when-downgrade"),
-            ReferrerPolicy::Origin => write!(f, "origin"),
-            ReferrerPolicy::OriginWhenCrossOrigin => write!(f, "origin-when-cross-origin"),
-            ReferrerPolicy::SameOrigin => write!(f, "same-origin"),
-            ReferrerPolicy::StrictOrigin => write!(f, "strict-origin"),
-            ReferrerPolicy::StrictOriginWhenCrossOrigin => {
-                write!(f, "strict-origin-when-cross-origin")
-            }
-            ReferrerPolicy::UnsafeUrl => write!(f, "unsafe-url"),
-        }
+        write!(f, "{}", self.0)
     }
 }
 
-impl Header for ReferrerPolicy {
-    fn name(&self) -> &'static str {
-        "Referrer-Policy"
-    }
+/// Header value with placeholders for a per-request [`Nonce`]
+///
+/// Framework adapters render the template once for every request, which avoids re-serializing the whole header.
+///
+/// # Examples
+///
+/// ```
+/// use helmet_core::{ContentSecurityPolicy, Header, Nonce};
+///
+/// let csp = ContentSecurityPolicy::new()
+///     .script_src(vec!["'self'"])
+///     .script_nonce();
+///
+/// let template = csp.nonce_template().unwrap();
+/// let nonce = Nonce::generate();
+///
+/// assert_eq!(
+///     template.render(&nonce),
+///     format!("script-src 'self' 'nonce-{}'", nonce)
+/// );
+///
+/// // 'none' is dropped, a missing directive starts from default-src
+/// let csp = ContentSecurityPolicy::new()
+///     .default_src(vec!["'self'"])
+///     .script_src(vec!["'none'"])
+///     .script_nonce()
+///     .style_nonce();
+///
+/// assert_eq!(
+///     csp.nonce_template().unwrap().render(&nonce),
+///     format!("default-src 'self'; script-src 'nonce-{0}'; style-src 'self' 'nonce-{0}'", nonce)
+/// );
+/// ```
+#[derive(Clone, Debug)]
+pub struct NonceTemplate(Vec<String>);
 
-    fn value(&self) -> String {
-        self.to_string()
+impl NonceTemplate {
+    /// Render the header value with `nonce` spliced in.
+    pub fn render(&self, nonce: &Nonce) -> String {
+        self.0.join(&nonce.to_source())
     }
 }
 
-/// Manages `Strict-Transport-Security` header
+/// Manages `Cross-Origin-Embedder-Policy` header
 ///
-/// The Strict-Transport-Security HTTP response header (often abbreviated as HSTS) lets a web site tell browsers that it should only be accessed using HTTPS, instead of using HTTP.
+/// The Cross-Origin-Embedder-Policy HTTP response header prevents a document from loading any cross-origin resources that do not explicitly grant the document permission (via CORS headers) to load them.
 ///
 /// # Values
 ///
-/// - max-age: The time, in seconds, that the browser should remember that a site is only to be accessed using HTTPS.
-/// - includeSubDomains: If this optional parameter is specified, this rule applies to all of the site's subdomains as well.
-/// - preload: If this optional parameter is specified, this rule applies to all of the site's subdomains as well.
+/// - unsafe-none: The document is not subject to any Cross-Origin-Embedder-Policy restrictions.
+/// - require-corp: The document is subject to Cross-Origin-Embedder-Policy restrictions.
+/// - credentialless: The document is subject to Cross-Origin-Embedder-Policy restrictions, and is not allowed to request credentials (e.g. cookies, certificates, HTTP authentication) from the user.
 ///
 /// # Examples
 ///
 /// ```
-/// use helmet_core::StrictTransportSecurity;
+/// use helmet_core::CrossOriginEmbedderPolicy;
 ///
-/// let strict_transport_security = StrictTransportSecurity::default();
+/// let cross_origin_embedder_policy = CrossOriginEmbedderPolicy::unsafe_none();
 ///
-/// let custom_strict_transport_security = StrictTransportSecurity::default()
-///    .max_age(31536000)
-///    .include_sub_domains()
-///    .preload();
+/// let cross_origin_embedder_policy = CrossOriginEmbedderPolicy::require_corp();
+///
+/// let cross_origin_embedder_policy = CrossOriginEmbedderPolicy::credentialless();
 /// ```
-#[derive(Clone)]
-pub struct StrictTransportSecurity {
-    max_age: u32,
-    include_sub_domains: bool,
-    preload: bool,
+///
+/// Violations can be reported to an endpoint registered with [`Helmet::reporting_endpoint`], see [`ReportingPolicy`]:
+///
+/// ```
+/// use helmet_core::{CrossOriginEmbedderPolicy, Helmet};
+///
+/// let cross_origin_embedder_policy = CrossOriginEmbedderPolicy::require_corp().report_to("coep");
+/// assert_eq!(cross_origin_embedder_policy.to_string(), "require-corp; report-to=\"coep\"");
+///
+/// let helmet = Helmet::new()
+///     .reporting_endpoint("coep", "https://example.com/reports")
+///     .add(cross_origin_embedder_policy);
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum CrossOriginEmbedderPolicy {
+    UnsafeNone,
+    RequireCorp,
+    Credentialless,
 }
 
-impl StrictTransportSecurity {
-    pub fn new() -> Self {
-        Self::default()
-    }
-
-    pub fn max_age(mut self, max_age: u32) -> Self {
-        self.max_age = max_age;
-        self
+impl CrossOriginEmbedderPolicy {
+    pub fn unsafe_none() -> Self {
+        Self::UnsafeNone
     }
 
-    pub fn include_sub_domains(mut self) -> Self {
-        self.include_sub_domains = true;
-        self
+    pub fn require_corp() -> Self {
+        Self::RequireCorp
     }
 
-    pub fn preload(mut self) -> Self {
-        self.preload = true;
-        self
+    pub fn credentialless() -> Self {
+        Self::Credentialless
     }
-}
 
-impl Default for StrictTransportSecurity {
-    fn default() -> Self {
-        Self {
-            max_age: 31536000,
-            include_sub_domains: false,
-            preload: false,
-        }
+    /// Send violation reports to the named reporting endpoint.
+    pub fn report_to(self, endpoint: &str) -> ReportingPolicy<Self> {
+        ReportingPolicy::new(self, endpoint)
     }
 }
 
-impl Display for StrictTransportSecurity {
+impl Display for CrossOriginEmbedderPolicy {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "max-age={}", self.max_age)?;
-        if self.include_sub_domains {
-            write!(f, "; includeSubDomains")?;
-        }
-        if self.preload {
-            write!(f, "; preload")?;
+        match self {
+            CrossOriginEmbedderPolicy::UnsafeNone => write!(f, "unsafe-none"),
+            CrossOriginEmbedderPolicy::RequireCorp => write!(f, "require-corp"),
+            CrossOriginEmbedderPolicy::Credentialless => write!(f, "credentialless"),
         }
-        Ok(())
     }
 }
 
-impl Header for StrictTransportSecurity {
+impl Header for CrossOriginEmbedderPolicy {
     fn name(&self) -> &'static str {
-        "Strict-Transport-Security"
+        "Cross-Origin-Embedder-Policy"
     }
 
     fn value(&self) -> String {
         self.to_string()
     }
+
+    fn scope(&self) -> HeaderScope {
+        HeaderScope::DocumentOrScript
+    }
 }
 
-/// Manages `X-Content-Type-Options` header
+/// Manages `Cross-Origin-Opener-Policy` header
 ///
-/// The X-Content-Type-Options response HTTP header is a marker used by the server to indicate that the MIME types advertised in the Content-Type headers should not be changed and be followed. This allows to opt-out of MIME type sniffing, or, in other words, it is a way to say that the webmasters knew what they were doing.
+/// The Cross-Origin-Opener-Policy HTTP response header restricts how selected resources are allowed to interact with the document's browsing context in response to user navigation. Each resource can declare an opener policy which applies to the resource's corresponding browsing context.
 ///
 /// # Values
 ///
-/// - nosniff: Prevents the browser from MIME-sniffing a response away from the declared content-type. This also applies to Google Chrome, when downloading extensions.
+/// - same-origin: The resource's browsing context is the same-origin as the document's browsing context.
+/// - same-origin-allow-popups: The resource's browsing context is the same-origin as the document's browsing context, and the resource is allowed to open new browsing contexts.
+/// - unsafe-none: The resource's browsing context is cross-origin with the document's browsing context.
 ///
 /// # Examples
 ///
 /// ```
-/// use helmet_core::XContentTypeOptions;
+/// use helmet_core::CrossOriginOpenerPolicy;
 ///
-/// let x_content_type_options = XContentTypeOptions::nosniff();
+/// let cross_origin_opener_policy = CrossOriginOpenerPolicy::same_origin();
+///
+/// let cross_origin_opener_policy = CrossOriginOpenerPolicy::same_origin().report_to("coop");
 /// ```
-#[derive(Clone)]
-pub enum XContentTypeOptions {
-    NoSniff,
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "kebab-case")
+)]
+pub enum CrossOriginOpenerPolicy {
+    SameOrigin,
+    SameOriginAllowPopups,
+    UnsafeNone,
 }
 
-impl XContentTypeOptions {
-    pub fn nosniff() -> Self {
-        Self::NoSniff
+impl CrossOriginOpenerPolicy {
+    pub fn same_origin() -> Self {
+        Self::SameOrigin
+    }
+
+    pub fn same_origin_allow_popups() -> Self {
+        Self::SameOriginAllowPopups
+    }
+
+    pub fn unsafe_none() -> Self {
+        Self::UnsafeNone
+    }
+
+    /// Send violation reports to the named reporting endpoint.
+    pub fn report_to(self, endpoint: &str) -> ReportingPolicy<Self> {
+        ReportingPolicy::new(self, endpoint)
     }
 }
 
-impl Display for XContentTypeOptions {
+impl Display for CrossOriginOpenerPolicy {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            XContentTypeOptions::NoSniff => write!(f, "nosniff"),
+            CrossOriginOpenerPolicy::SameOrigin => write!(f, "same-origin"),
+            CrossOriginOpenerPolicy::SameOriginAllowPopups => write!(f, "same-origin-allow-popups"),
+            CrossOriginOpenerPolicy::UnsafeNone => write!(f, "unsafe-none"),
         }
     }
 }
 
-impl Header for XContentTypeOptions {
+impl Header for CrossOriginOpenerPolicy {
     fn name(&self) -> &'static str {
-        "X-Content-Type-Options"
+        "Cross-Origin-Opener-Policy"
     }
 
     fn value(&self) -> String {
         self.to_string()
     }
+
+    fn scope(&self) -> HeaderScope {
+        HeaderScope::Document
+    }
 }
 
-/// Manages `X-DNS-Prefetch-Control` header
-///
-/// The X-DNS-Prefetch-Control HTTP response header controls DNS prefetching, a feature by which browsers proactively perform domain name resolution on both links that the user may choose to follow as well as URLs for items referenced by the document, including images, CSS, JavaScript, and so forth.
-///
-/// # Values
-///
-/// - off: Disable DNS prefetching.
-/// - on: Enable DNS prefetching, allowing the browser to proactively perform domain name resolution on both links that the user may choose to follow as well as URLs for items referenced by the document, including images, CSS, JavaScript, and so forth.
+/// `Cross-Origin-Embedder-Policy` or `Cross-Origin-Opener-Policy` that reports violations to a reporting endpoint
 ///
-/// # Examples
+/// Returned by [`CrossOriginEmbedderPolicy::report_to`] and [`CrossOriginOpenerPolicy::report_to`]. [`Helmet::get`] finds the header as `ReportingPolicy<CrossOriginOpenerPolicy>`, not as the plain policy.
 ///
 /// ```
-/// use helmet_core::XDNSPrefetchControl;
+/// use helmet_core::{CrossOriginOpenerPolicy, Header, Helmet, ReportingPolicy};
 ///
-/// let x_dns_prefetch_control = XDNSPrefetchControl::off();
+/// let helmet = Helmet::new().add(CrossOriginOpenerPolicy::same_origin().report_to("coop"));
+///
+/// let header = helmet.get::<ReportingPolicy<CrossOriginOpenerPolicy>>().unwrap();
+/// assert_eq!(header.endpoint(), "coop");
+/// assert_eq!(header.value(), "same-origin; report-to=\"coop\"");
 /// ```
-#[derive(Clone)]
-pub enum XDNSPrefetchControl {
-    Off,
-    On,
+#[derive(Clone, Debug)]
+pub struct ReportingPolicy<P> {
+    policy: P,
+    endpoint: String,
 }
 
-impl XDNSPrefetchControl {
-    pub fn off() -> Self {
-        Self::Off
+impl<P> ReportingPolicy<P> {
+    fn new(policy: P, endpoint: &str) -> Self {
+        Self {
+            policy,
+            endpoint: endpoint.to_string(),
+        }
     }
 
-    pub fn on() -> Self {
-        Self::On
+    /// The policy that is reported on
+    pub fn policy(&self) -> &P {
+        &self.policy
+    }
+
+    /// Name of the reporting endpoint
+    pub fn endpoint(&self) -> &str {
+        &self.endpoint
     }
 }
 
-impl Display for XDNSPrefetchControl {
+impl<P: Display> Display for ReportingPolicy<P> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            XDNSPrefetchControl::Off => write!(f, "off"),
-            XDNSPrefetchControl::On => write!(f, "on"),
-        }
+        write!(f, "{}; report-to=\"{}\"", self.policy, self.endpoint)
     }
 }
 
-impl Header for XDNSPrefetchControl {
+impl<P: Header + Display + Clone> Header for ReportingPolicy<P> {
     fn name(&self) -> &'static str {
-        "X-DNS-Prefetch-Control"
+        self.policy.name()
     }
 
     fn value(&self) -> String {
         self.to_string()
     }
+
+    fn validate(&self) -> Result<(), HelmetError> {
+        validate_endpoint_name(self.name(), &self.endpoint)
+    }
+
+    fn scope(&self) -> HeaderScope {
+        self.policy.scope()
+    }
 }
 
-/// Manages `X-Download-Options` header
+/// Manages `Cross-Origin-Resource-Policy` header
 ///
-/// The X-Download-Options HTTP response header indicates that the browser (Internet Explorer) should not display the option to "Open" a file that has been downloaded from an application, to prevent phishing attacks that could trick users into opening potentially malicious content that could infect their computer.
+/// The Cross-Origin-Resource-Policy HTTP response header conveys a desire that the browser blocks no-cors cross-origin/cross-site requests to the given resource.
 ///
 /// # Values
 ///
-/// - noopen: Prevents Internet Explorer from executing downloads in your site’s context.
+/// - same-origin: The resource is same-origin to the document.
+/// - same-site: The resource is same-site to the document.
+/// - cross-origin: The resource is cross-origin to the document.
 ///
 /// # Examples
 ///
 /// ```
-/// use helmet_core::XDownloadOptions;
+/// use helmet_core::CrossOriginResourcePolicy;
 ///
-/// let x_download_options = XDownloadOptions::noopen();
+/// let cross_origin_resource_policy = CrossOriginResourcePolicy::same_origin();
 /// ```
-#[derive(Clone)]
-pub enum XDownloadOptions {
-    NoOpen,
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema),
+    serde(rename_all = "kebab-case"),
+    schemars(description = "`Cross-Origin-Resource-Policy` header value")
+)]
+pub enum CrossOriginResourcePolicy {
+    SameOrigin,
+    SameSite,
+    CrossOrigin,
 }
 
-impl XDownloadOptions {
-    pub fn noopen() -> Self {
-        Self::NoOpen
+impl CrossOriginResourcePolicy {
+    pub fn same_origin() -> Self {
+        Self::SameOrigin
+    }
+
+    pub fn same_site() -> Self {
+        Self::SameSite
+    }
+
+    pub fn cross_origin() -> Self {
+        Self::CrossOrigin
     }
 }
 
-impl Display for XDownloadOptions {
+impl Display for CrossOriginResourcePolicy {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            XDownloadOptions::NoOpen => write!(f, "noopen"),
+            CrossOriginResourcePolicy::SameOrigin => write!(f, "same-origin"),
+            CrossOriginResourcePolicy::SameSite => write!(f, "same-site"),
+            CrossOriginResourcePolicy::CrossOrigin => write!(f, "cross-origin"),
         }
     }
 }
 
-impl Header for XDownloadOptions {
+impl Header for CrossOriginResourcePolicy {
     fn name(&self) -> &'static str {
-        "X-Download-Options"
+        "Cross-Origin-Resource-Policy"
     }
 
     fn value(&self) -> String {
@@ -614,913 +1105,6977 @@ impl Header for XDownloadOptions {
     }
 }
 
-/// Manages `X-Frame-Options` header
+/// Manages `Origin-Agent-Cluster` header
 ///
-/// The X-Frame-Options HTTP response header can be used to to avoid click-jacking attacks by preventing the content to be included in other websites.
+/// The Origin-Agent-Cluster HTTP request header indicates that the client prefers an "origin agent cluster" (OAC) for the origin of the resource being requested. An OAC is a cluster of servers that are controlled by the same entity as the origin server, and that are geographically close to the client. The OAC is used to provide the client with a better experience, for example by serving content from a server that is close to the client, or by serving content that is optimized for the client's device.
 ///
 /// # Values
 ///
-/// - deny: The page cannot be displayed in a frame, regardless of the site attempting to do so.
-/// - sameorigin: The page can only be displayed in a frame on the same origin as the page itself.
-/// - allow-from: The page can only be displayed in a frame on the specified origin. Requires a URI as an argument.
+/// - 0: The client does not prefer an OAC.
+/// - 1: The client prefers an OAC.
 ///
 /// # Examples
 ///
 /// ```
-/// use helmet_core::XFrameOptions;
-///
-/// let x_frame_options = XFrameOptions::deny();
-///
-/// let x_frame_options = XFrameOptions::same_origin();
+/// use helmet_core::OriginAgentCluster;
 ///
-/// let x_frame_options = XFrameOptions::allow_from("https://example.com");
+/// let origin_agent_cluster = OriginAgentCluster::new(true);
 /// ```
-#[derive(Clone)]
-pub enum XFrameOptions {
-    Deny,
-    SameOrigin,
-    // deprecated - use Content-Security-Policy instead see https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/X-Frame-Options#allow-from_origin
-    AllowFrom(String),
-}
-
-impl XFrameOptions {
-    pub fn deny() -> Self {
-        Self::Deny
-    }
-
-    pub fn same_origin() -> Self {
-        Self::SameOrigin
-    }
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema),
+    schemars(description = "`Origin-Agent-Cluster` header, `true` sends `?1`")
+)]
+pub struct OriginAgentCluster(bool);
 
-    pub fn allow_from(uri: &str) -> Self {
-        Self::AllowFrom(uri.to_string())
+impl OriginAgentCluster {
+    pub fn new(prefer_mobile_experience: bool) -> Self {
+        Self(prefer_mobile_experience)
     }
 }
 
-impl Display for XFrameOptions {
+impl Display for OriginAgentCluster {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            XFrameOptions::Deny => write!(f, "DENY"),
-            XFrameOptions::SameOrigin => write!(f, "SAMEORIGIN"),
-            XFrameOptions::AllowFrom(uri) => write!(f, "ALLOW-FROM {}", uri),
+        if self.0 {
+            write!(f, "?1")
+        } else {
+            write!(f, "?0")
         }
     }
 }
 
-impl Header for XFrameOptions {
+impl Header for OriginAgentCluster {
     fn name(&self) -> &'static str {
-        "X-Frame-Options"
+        "Origin-Agent-Cluster"
     }
 
     fn value(&self) -> String {
         self.to_string()
     }
+
+    fn scope(&self) -> HeaderScope {
+        HeaderScope::Document
+    }
 }
 
-/// Manages `X-Permitted-Cross-Domain-Policies` header
+/// Policy-controlled feature of a `Permissions-Policy` header
 ///
-/// The X-Permitted-Cross-Domain-Policies HTTP response header determines whether cross-domain policy files (crossdomain.xml and clientaccesspolicy.xml) will be ignored by Flash and Adobe Acrobat in subsequent requests.
+/// Features not listed here can be set with [`PermissionsPolicyFeature::Other`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema),
+    serde(rename_all = "kebab-case"),
+    schemars(description = "Policy-controlled feature of a `Permissions-Policy` header")
+)]
+pub enum PermissionsPolicyFeature {
+    Accelerometer,
+    AmbientLightSensor,
+    Autoplay,
+    Battery,
+    Bluetooth,
+    BrowsingTopics,
+    Camera,
+    DisplayCapture,
+    DocumentDomain,
+    EncryptedMedia,
+    ExecutionWhileNotRendered,
+    ExecutionWhileOutOfViewport,
+    Fullscreen,
+    Gamepad,
+    Geolocation,
+    Gyroscope,
+    Hid,
+    IdentityCredentialsGet,
+    IdleDetection,
+    InterestCohort,
+    LocalFonts,
+    Magnetometer,
+    Microphone,
+    Midi,
+    OtpCredentials,
+    Payment,
+    PictureInPicture,
+    PublickeyCredentialsCreate,
+    PublickeyCredentialsGet,
+    ScreenWakeLock,
+    Serial,
+    SpeakerSelection,
+    StorageAccess,
+    Usb,
+    WebShare,
+    WindowManagement,
+    XrSpatialTracking,
+    /// Any other feature, by its name, e.g. `"clipboard-read"`
+    #[cfg_attr(feature = "serde", serde(untagged))]
+    Other(String),
+}
+
+impl Display for PermissionsPolicyFeature {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            Self::Accelerometer => "accelerometer",
+            Self::AmbientLightSensor => "ambient-light-sensor",
+            Self::Autoplay => "autoplay",
+            Self::Battery => "battery",
+            Self::Bluetooth => "bluetooth",
+            Self::BrowsingTopics => "browsing-topics",
+            Self::Camera => "camera",
+            Self::DisplayCapture => "display-capture",
+            Self::DocumentDomain => "document-domain",
+            Self::EncryptedMedia => "encrypted-media",
+            Self::ExecutionWhileNotRendered => "execution-while-not-rendered",
+            Self::ExecutionWhileOutOfViewport => "execution-while-out-of-viewport",
+            Self::Fullscreen => "fullscreen",
+            Self::Gamepad => "gamepad",
+            Self::Geolocation => "geolocation",
+            Self::Gyroscope => "gyroscope",
+            Self::Hid => "hid",
+            Self::IdentityCredentialsGet => "identity-credentials-get",
+            Self::IdleDetection => "idle-detection",
+            Self::InterestCohort => "interest-cohort",
+            Self::LocalFonts => "local-fonts",
+            Self::Magnetometer => "magnetometer",
+            Self::Microphone => "microphone",
+            Self::Midi => "midi",
+            Self::OtpCredentials => "otp-credentials",
+            Self::Payment => "payment",
+            Self::PictureInPicture => "picture-in-picture",
+            Self::PublickeyCredentialsCreate => "publickey-credentials-create",
+            Self::PublickeyCredentialsGet => "publickey-credentials-get",
+            Self::ScreenWakeLock => "screen-wake-lock",
+            Self::Serial => "serial",
+            Self::SpeakerSelection => "speaker-selection",
+            Self::StorageAccess => "storage-access",
+            Self::Usb => "usb",
+            Self::WebShare => "web-share",
+            Self::WindowManagement => "window-management",
+            Self::XrSpatialTracking => "xr-spatial-tracking",
+            Self::Other(name) => name,
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Entry of a `Permissions-Policy` allowlist
 ///
 /// # Values
 ///
-/// - none: No policy file is allowed.
-/// - master-only: Only a master policy file, but no other policy files, is allowed.
-/// - by-content-type: A policy file is allowed if its MIME type matches the Content-Type of the requested resource.
-/// - by-ftp-filename: A policy file is allowed if its URL matches the URL of the requested resource.
-/// - all: Any policy file is allowed.
-///
-/// # Examples
-///
-/// ```
-/// use helmet_core::XPermittedCrossDomainPolicies;
-///
-/// let x_permitted_cross_domain_policies = XPermittedCrossDomainPolicies::none();
-///
-/// let x_permitted_cross_domain_policies = XPermittedCrossDomainPolicies::master_only();
-///
-/// let x_permitted_cross_domain_policies = XPermittedCrossDomainPolicies::by_content_type();
-///
-/// let x_permitted_cross_domain_policies = XPermittedCrossDomainPolicies::by_ftp_filename();
+/// - `*`: The feature is allowed in all browsing contexts.
+/// - `self`: The feature is allowed in the document and same-origin nested browsing contexts.
+/// - `src`: The feature is allowed in an iframe, as long as its document is loaded from the origin in its `src` attribute.
+/// - origin: The feature is allowed for the given origin, e.g. `"https://example.com"`.
 ///
-/// let x_permitted_cross_domain_policies = XPermittedCrossDomainPolicies::all();
-/// ```
-#[derive(Clone)]
-pub enum XPermittedCrossDomainPolicies {
-    None,
-    MasterOnly,
-    ByContentType,
-    ByFtpFilename,
+/// Strings convert into entries, where `"*"`, `"self"` and `"src"` are keywords and anything else is an origin.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum AllowlistEntry {
     All,
+    Self_,
+    Src,
+    Origin(String),
 }
 
-impl XPermittedCrossDomainPolicies {
-    pub fn none() -> Self {
-        Self::None
-    }
-
-    pub fn master_only() -> Self {
-        Self::MasterOnly
-    }
-
-    pub fn by_content_type() -> Self {
-        Self::ByContentType
-    }
-
-    pub fn by_ftp_filename() -> Self {
-        Self::ByFtpFilename
+impl From<&str> for AllowlistEntry {
+    fn from(value: &str) -> Self {
+        match value {
+            "*" => Self::All,
+            "self" => Self::Self_,
+            "src" => Self::Src,
+            origin => Self::Origin(origin.to_string()),
+        }
     }
+}
 
-    pub fn all() -> Self {
-        Self::All
+impl From<String> for AllowlistEntry {
+    fn from(value: String) -> Self {
+        match value.as_str() {
+            "*" | "self" | "src" => Self::from(value.as_str()),
+            _ => Self::Origin(value),
+        }
     }
 }
 
-impl Display for XPermittedCrossDomainPolicies {
+impl Display for AllowlistEntry {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
-            XPermittedCrossDomainPolicies::None => write!(f, "none"),
-            XPermittedCrossDomainPolicies::MasterOnly => write!(f, "master-only"),
-            XPermittedCrossDomainPolicies::ByContentType => write!(f, "by-content-type"),
-            XPermittedCrossDomainPolicies::ByFtpFilename => write!(f, "by-ftp-filename"),
-            XPermittedCrossDomainPolicies::All => write!(f, "all"),
+            Self::All => write!(f, "*"),
+            Self::Self_ => write!(f, "self"),
+            Self::Src => write!(f, "src"),
+            Self::Origin(origin) => {
+                write!(f, "\"")?;
+                for c in origin.chars() {
+                    if c == '"' || c == '\\' {
+                        write!(f, "\\")?;
+                    }
+                    write!(f, "{}", c)?;
+                }
+                write!(f, "\"")
+            }
         }
     }
 }
 
-impl Header for XPermittedCrossDomainPolicies {
-    fn name(&self) -> &'static str {
-        "X-Permitted-Cross-Domain-Policies"
-    }
-
-    fn value(&self) -> String {
-        self.to_string()
-    }
-}
-
-/// Manages `X-XSS-Protection` header
+/// Manages `Permissions-Policy` header
 ///
-/// The HTTP X-XSS-Protection response header is a feature of Internet Explorer, Chrome and Safari that stops pages from loading when they detect reflected cross-site scripting (XSS) attacks. Although these protections are largely unnecessary in modern browsers when sites implement a strong Content-Security-Policy that disables the use of inline JavaScript ('unsafe-inline'), they can still provide protections for users of older web browsers that don't yet support CSP.
+/// The Permissions-Policy HTTP response header provides a mechanism to allow and deny the use of browser features in a document or within any iframe elements in the document.
 ///
-/// # Values
+/// The header is a structured field dictionary (RFC 8941), mapping each feature to its allowlist:
 ///
-/// - 0: Disables XSS filtering.
-/// - 1: Enables XSS filtering (usually default in browsers).
-/// - 1; mode=block: Enables XSS filtering. Rather than sanitizing the page, the browser will prevent rendering of the page if an attack is detected.
-/// - 1; report=<reporting-URI>: Enables XSS filtering. If a cross-site scripting attack is detected, the browser will sanitize the page and report the violation. This uses the functionality of the CSP report-uri directive to send a report.
+/// - `feature=()`: The feature is disabled everywhere.
+/// - `feature=*`: The feature is allowed everywhere.
+/// - `feature=(self "https://example.com")`: The feature is allowed for the document's own origin and the listed origins.
 ///
 /// # Examples
 ///
 /// ```
-/// use helmet_core::XXSSProtection;
+/// use helmet_core::{AllowlistEntry, PermissionsPolicy, PermissionsPolicyFeature};
 ///
-/// let x_xss_protection = XXSSProtection::on();
+/// let permissions_policy = PermissionsPolicy::new()
+///     .deny(PermissionsPolicyFeature::Camera)
+///     .deny(PermissionsPolicyFeature::InterestCohort)
+///     .allow(PermissionsPolicyFeature::Fullscreen, vec!["*"])
+///     .allow(
+///         PermissionsPolicyFeature::Geolocation,
+///         vec![AllowlistEntry::Self_, AllowlistEntry::Origin("https://maps.example.com".to_string())],
+///     );
 ///
-/// let x_xss_protection = XXSSProtection::off();
+/// assert_eq!(
+///     permissions_policy.to_string(),
+///     "camera=(), interest-cohort=(), fullscreen=*, geolocation=(self \"https://maps.example.com\")"
+/// );
+/// ```
 ///
-/// let x_xss_protection = XXSSProtection::on().mode_block();
+/// Report-only mode sends the policy as `Permissions-Policy-Report-Only`, so violations are reported without being enforced:
 ///
-/// let x_xss_protection = XXSSProtection::on().report("https://example.com");
+/// ```
+/// use helmet_core::{Header, PermissionsPolicy, PermissionsPolicyFeature};
 ///
-/// let x_xss_protection = XXSSProtection::on().mode_block().report("https://example.com");
+/// let permissions_policy = PermissionsPolicy::new()
+///     .deny(PermissionsPolicyFeature::Microphone)
+///     .report_only();
+///
+/// assert_eq!(permissions_policy.name(), "Permissions-Policy-Report-Only");
 /// ```
-#[derive(Clone)]
-pub struct XXSSProtection {
-    on: bool,
-    mode_block: bool,
-    report: Option<String>,
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct PermissionsPolicy {
+    features: Vec<(PermissionsPolicyFeature, Vec<AllowlistEntry>)>,
+    report_only: bool,
 }
 
-impl XXSSProtection {
-    /// Disables XSS filtering.
-    pub fn off() -> Self {
-        Self {
-            on: false,
-            mode_block: false,
-            report: None,
-        }
+impl PermissionsPolicy {
+    /// Create an empty policy, which does not restrict any feature.
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    /// Enables XSS filtering (usually default in browsers).
-    pub fn on() -> Self {
-        Self {
-            on: true,
-            mode_block: false,
-            report: None,
+    /// Allow a feature for the given allowlist. An empty allowlist disables the feature.
+    ///
+    /// Setting a feature more than once replaces its allowlist.
+    pub fn allow<A: Into<AllowlistEntry>>(
+        mut self,
+        feature: PermissionsPolicyFeature,
+        allowlist: Vec<A>,
+    ) -> Self {
+        let allowlist = allowlist.into_iter().map(Into::into).collect();
+        match self.features.iter_mut().find(|(f, _)| *f == feature) {
+            Some((_, existing)) => *existing = allowlist,
+            None => self.features.push((feature, allowlist)),
         }
+        self
     }
 
-    /// Enables XSS filtering. Rather than sanitizing the page, the browser will prevent rendering of the page if an attack is detected.
-    pub fn mode_block(mut self) -> Self {
-        self.mode_block = true;
-        self
+    /// Disable a feature in all browsing contexts.
+    pub fn deny(self, feature: PermissionsPolicyFeature) -> Self {
+        self.allow::<AllowlistEntry>(feature, vec![])
     }
 
-    /// Enables XSS filtering. If a cross-site scripting attack is detected, the browser will sanitize the page and report the violation. This uses the functionality of the CSP report-uri directive to send a report.
-    pub fn report(mut self, report: &str) -> Self {
-        self.report = Some(report.to_string());
+    /// Send the policy as `Permissions-Policy-Report-Only`, which reports violations without enforcing the policy.
+    pub fn report_only(mut self) -> Self {
+        self.report_only = true;
         self
     }
+
+    /// Render the policy as a legacy `Feature-Policy` header, for browsers that don't support `Permissions-Policy`.
+    ///
+    /// `Feature-Policy` has no report-only variant, so add it only alongside an enforced policy.
+    ///
+    /// ```
+    /// use helmet_core::{Helmet, PermissionsPolicy, PermissionsPolicyFeature};
+    ///
+    /// let permissions_policy = PermissionsPolicy::new()
+    ///     .deny(PermissionsPolicyFeature::Camera)
+    ///     .allow(PermissionsPolicyFeature::Fullscreen, vec!["self", "https://example.com"]);
+    ///
+    /// let feature_policy = permissions_policy.feature_policy();
+    /// assert_eq!(feature_policy.to_string(), "camera 'none'; fullscreen 'self' https://example.com");
+    ///
+    /// let helmet = Helmet::new().add(permissions_policy).add(feature_policy);
+    /// ```
+    pub fn feature_policy(&self) -> FeaturePolicy {
+        FeaturePolicy(self.clone())
+    }
+
+    /// Check that feature names and origins can be represented in the header.
+    pub fn validate(&self) -> Result<(), HelmetError> {
+        let invalid = |value: &str| HelmetError::InvalidHeaderValue {
+            name: self.name().to_string(),
+            value: value.to_string(),
+        };
+        for (feature, allowlist) in self.features.iter() {
+            if let PermissionsPolicyFeature::Other(name) = feature {
+                let mut bytes = name.bytes();
+                let valid = matches!(bytes.next(), Some(b'a'..=b'z'))
+                    && bytes.all(|b| {
+                        b.is_ascii_lowercase() || b.is_ascii_digit() || b"_-.*".contains(&b)
+                    });
+                if !valid {
+                    return Err(invalid(name));
+                }
+            }
+            for entry in allowlist.iter() {
+                if let AllowlistEntry::Origin(origin) = entry {
+                    if origin.is_empty()
+                        || !origin
+                            .bytes()
+                            .all(|b| (0x21..=0x7e).contains(&b) && !b"\"\\,;'".contains(&b))
+                    {
+                        return Err(invalid(origin));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
 }
 
-impl Display for XXSSProtection {
+impl Display for PermissionsPolicy {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        if self.on {
-            write!(f, "1")?;
-            if self.mode_block {
-                write!(f, "; mode=block")?;
+        for (i, (feature, allowlist)) in self.features.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
             }
-            if let Some(report) = &self.report {
-                write!(f, "; report={}", report)?;
+            write!(f, "{}=", feature)?;
+            if allowlist.contains(&AllowlistEntry::All) {
+                write!(f, "*")?;
+                continue;
             }
-        } else {
-            write!(f, "0")?;
+            write!(f, "(")?;
+            for (j, entry) in allowlist.iter().enumerate() {
+                if j > 0 {
+                    write!(f, " ")?;
+                }
+                write!(f, "{}", entry)?;
+            }
+            write!(f, ")")?;
         }
         Ok(())
     }
 }
 
-impl Header for XXSSProtection {
+impl Header for PermissionsPolicy {
     fn name(&self) -> &'static str {
-        "X-XSS-Protection"
+        if self.report_only {
+            "Permissions-Policy-Report-Only"
+        } else {
+            "Permissions-Policy"
+        }
     }
 
     fn value(&self) -> String {
         self.to_string()
     }
+
+    fn validate(&self) -> Result<(), HelmetError> {
+        PermissionsPolicy::validate(self)
+    }
+
+    fn scope(&self) -> HeaderScope {
+        HeaderScope::Document
+    }
 }
 
-/// Manages `X-Powered-By` header
+/// Manages the legacy `Feature-Policy` header
 ///
-/// ntex does not set `X-Powered-By` header by default.
-/// Instead of silencing the header, Helmet allows you to set it to a custom value.
-/// This can be useful against primitive fingerprinting.
-///
-/// # Examples
-///
-/// ```
-/// use helmet_core::XPoweredBy;
-///
-/// let x_powered_by = XPoweredBy::new("PHP 4.2.0");
-/// ```
-#[derive(Clone)]
-pub struct XPoweredBy(String);
-
-impl XPoweredBy {
-    /// Set the `X-Powered-By` header to a custom value.
-    pub fn new(comment: &str) -> Self {
-        Self(comment.to_string())
-    }
-}
-
-impl Display for XPoweredBy {
+/// Feature-Policy is the predecessor of Permissions-Policy, still understood by older browsers. Create it from a [`PermissionsPolicy`] with [`PermissionsPolicy::feature_policy`], so both headers describe the same policy.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema),
+    schemars(description = "`Feature-Policy` header, rendered from a `Permissions-Policy`")
+)]
+pub struct FeaturePolicy(PermissionsPolicy);
+
+impl Display for FeaturePolicy {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.0)?;
+        for (i, (feature, allowlist)) in self.0.features.iter().enumerate() {
+            if i > 0 {
+                write!(f, "; ")?;
+            }
+            write!(f, "{}", feature)?;
+            if allowlist.is_empty() {
+                write!(f, " 'none'")?;
+            } else if allowlist.contains(&AllowlistEntry::All) {
+                write!(f, " *")?;
+            } else {
+                for entry in allowlist.iter() {
+                    match entry {
+                        AllowlistEntry::Self_ => write!(f, " 'self'")?,
+                        AllowlistEntry::Src => write!(f, " 'src'")?,
+                        AllowlistEntry::Origin(origin) => write!(f, " {}", origin)?,
+                        AllowlistEntry::All => unreachable!(),
+                    }
+                }
+            }
+        }
         Ok(())
     }
 }
 
-impl Header for XPoweredBy {
+impl Header for FeaturePolicy {
     fn name(&self) -> &'static str {
-        "X-Powered-By"
+        "Feature-Policy"
     }
 
     fn value(&self) -> String {
         self.to_string()
     }
+
+    fn validate(&self) -> Result<(), HelmetError> {
+        self.0.validate()
+    }
+
+    fn scope(&self) -> HeaderScope {
+        HeaderScope::Document
+    }
 }
 
-/// Manages `Content-Security-Policy` header
+/// Manages `Referrer-Policy` header
 ///
-/// The HTTP Content-Security-Policy response header allows web site administrators to control resources the user agent is allowed to load for a given page. With a few exceptions, policies mostly involve specifying server origins and script endpoints. This helps guard against cross-site scripting attacks (XSS).
+/// The Referrer-Policy HTTP response header controls how much referrer information (sent via the Referer header) should be included with requests.
 ///
-/// # Directives
+/// # Values
 ///
-/// - child-src: Defines valid sources for web workers and nested browsing contexts loaded using elements such as `<frame>` and `<iframe>`.
-/// - connect-src: Applies to XMLHttpRequest (AJAX), WebSocket or EventSource. If not allowed the browser emulates a 400 HTTP status code.
-/// - default-src: The default-src is the default policy for loading content such as JavaScript, Images, CSS, Font's, AJAX requests, Frames, HTML5 Media. See the list of directives to see which values are allowed as default.
-/// - font-src: Defines valid sources for fonts loaded using @font-face.
-/// - frame-src: Defines valid sources for nested browsing contexts loading using elements such as `<frame>` and `<iframe>`.
-/// - img-src: Defines valid sources of images and favicons.
-/// - manifest-src: Specifies which manifest can be applied to the resource.
-/// - media-src: Defines valid sources for loading media using the `<audio>` and `<video>` elements.
-/// - object-src: Defines valid sources for the `<object>`, `<embed>`, and `<applet>` elements.
-/// - prefetch-src: Specifies which referrer to use when fetching the resource.
-/// - script-src: Defines valid sources for JavaScript.
-/// - script-src-elem: Defines valid sources for JavaScript inline event handlers.
-/// - script-src-attr: Defines valid sources for JavaScript inline event handlers.
-/// - style-src: Defines valid sources for stylesheets.
-/// - style-src-elem: Defines valid sources for stylesheets inline event handlers.
-/// - style-src-attr: Defines valid sources for stylesheets inline event handlers.
-/// - worker-src: Defines valid sources for Worker, SharedWorker, or ServiceWorker scripts.
-/// - base-uri: Restricts the URLs which can be used in a document's `<base>` element.
-/// - sandbox: Enables a sandbox for the requested resource similar to the iframe sandbox attribute. The sandbox applies a same origin policy, prevents popups, plugins and script execution is blocked. You can keep the sandbox value empty to keep all restrictions in place, or add values: allow-forms allow-same-origin allow-scripts allow-popups, allow-modals, allow-orientation-lock, allow-pointer-lock, allow-presentation, allow-popups-to-escape-sandbox, allow-top-navigation, allow-top-navigation-by-user-activation.
-/// - form-action: Restricts the URLs which can be used as the target of a form submissions from a given context.
-/// - frame-ancestors: Specifies valid parents that may embed a page using `<frame>`, `<iframe>`, `<object>`, `<embed>`, or `<applet>`.
-/// - report-to: Enables reporting of violations.
-/// - require-trusted-types-for: Specifies which trusted types are required by a resource.
-/// - trusted-types: Specifies which trusted types are defined by a resource.
-/// - upgrade-insecure-requests: Block HTTP requests on insecure elements.
+/// - no-referrer: The Referer header will be omitted entirely. No referrer information is sent along with requests.
+/// - no-referrer-when-downgrade: The Referer header will be omitted entirely. However, if the protected resource URL scheme is HTTPS, then the full path will still be sent as a referrer.
+/// - origin: Only send the origin of the document as the referrer in all cases. The document https://example.com/page.html will send the referrer https://example.com/.
+/// - origin-when-cross-origin: Send a full URL when performing a same-origin request, but only send the origin of the document for other cases.
+/// - same-origin: A referrer will be sent for same-site origins, but cross-origin requests will contain no referrer information.
+/// - strict-origin: Only send the origin of the document as the referrer when the protocol security level stays the same (HTTPS→HTTPS), but don't send it to a less secure destination (HTTPS→HTTP).
+/// - strict-origin-when-cross-origin: Send a full URL when performing a same-origin request, only send the origin when the protocol security level stays the same (HTTPS→HTTPS), and send no header to a less secure destination (HTTPS→HTTP).
+/// - unsafe-url: Send a full URL (stripped from parameters) when performing a same-origin or cross-origin request. This policy will leak origins and paths from TLS-protected resources to insecure origins. Carefully consider the impact of this setting.
 ///
 /// # Examples
 ///
 /// ```
-/// use helmet_core::ContentSecurityPolicy;
+/// use helmet_core::ReferrerPolicy;
 ///
-/// let content_security_policy = ContentSecurityPolicy::default()
-///    .child_src(vec!["'self'", "https://youtube.com"])
-///    .connect_src(vec!["'self'", "https://youtube.com"])
-///    .default_src(vec!["'self'", "https://youtube.com"])
-///    .font_src(vec!["'self'", "https://youtube.com"]);
+/// let referrer_policy = ReferrerPolicy::no_referrer();
 /// ```
-#[derive(Clone)]
-pub enum ContentSecurityPolicyDirective<'a> {
-    /// Warning: Instead of child-src, if you want to regulate nested browsing contexts and workers, you should use the frame-src and worker-src directives, respectively.
-    ChildSrc(Vec<&'a str>),
-    /// Applies to XMLHttpRequest (AJAX), WebSocket or EventSource. If not allowed the browser emulates a 400 HTTP status code.
-    ConnectSrc(Vec<&'a str>),
-    /// The default-src is the default policy for loading content such as JavaScript, Images, CSS, Font's, AJAX requests, Frames, HTML5 Media. See the list of directives to see which values are allowed as default.
-    DefaultSrc(Vec<&'a str>),
-    /// Defines valid sources for fonts loaded using @font-face.
-    FontSrc(Vec<&'a str>),
-    /// Defines valid sources for nested browsing contexts loading using elements such as `<frame>` and `<iframe>`.
-    FrameSrc(Vec<&'a str>),
-    /// Defines valid sources of images and favicons.
-    ImgSrc(Vec<&'a str>),
-    /// Specifies which manifest can be applied to the resource.
-    ManifestSrc(Vec<&'a str>),
-    /// Defines valid sources for loading media using the `<audio>` and `<video>` elements.
-    MediaSrc(Vec<&'a str>),
-    /// Defines valid sources for the `<object>`, `<embed>`, and `<applet>` elements.
-    ObjectSrc(Vec<&'a str>),
-    /// Specifies which referrer to use when fetching the resource.
-    PrefetchSrc(Vec<&'a str>),
-    /// Defines valid sources for JavaScript.
-    ScriptSrc(Vec<&'a str>),
-    /// Defines valid sources for JavaScript inline event handlers.
-    ScriptSrcElem(Vec<&'a str>),
-    /// Defines valid sources for JavaScript inline event handlers.
-    ScriptSrcAttr(Vec<&'a str>),
-    /// Defines valid sources for stylesheets.
-    StyleSrc(Vec<&'a str>),
-    /// Defines valid sources for stylesheets inline event handlers.
-    StyleSrcElem(Vec<&'a str>),
-    /// Defines valid sources for stylesheets inline event handlers.
-    StyleSrcAttr(Vec<&'a str>),
-    /// Defines valid sources for Worker, SharedWorker, or ServiceWorker scripts.
-    WorkerSrc(Vec<&'a str>),
-    // Document directives
-    /// Restricts the URLs which can be used in a document's `<base>` element.
-    BaseUri(Vec<&'a str>),
-    /// Enables a sandbox for the requested resource similar to the iframe sandbox attribute. The sandbox applies a same origin policy, prevents popups, plugins and script execution is blocked. You can keep the sandbox value empty to keep all restrictions in place, or add values: allow-forms allow-same-origin allow-scripts allow-popups, allow-modals, allow-orientation-lock, allow-pointer-lock, allow-presentation, allow-popups-to-escape-sandbox, allow-top-navigation, allow-top-navigation-by-user-activation.
-    Sandbox(Vec<&'a str>),
-    // Navigation directives
-    /// Restricts the URLs which can be used as the target of a form submissions from a given context.
-    FormAction(Vec<&'a str>),
-    /// Specifies valid parents that may embed a page using `<frame>`, `<iframe>`, `<object>`, `<embed>`, or `<applet>`.
-    FrameAncestors(Vec<&'a str>),
-    // Reporting directives
-    /// Enables reporting of violations.
-    ///
-    /// report-uri is deprecated, however, it is still supported by browsers that don't yet support report-to. ReportTo will apply both to report-uri and report-to with the same values, to support browsers that support both.
-    ReportTo(Vec<&'a str>),
-    // Other
-    /// Specifies which trusted types are required by a resource.
-    RequireTrustedTypesFor(Vec<&'a str>),
-    /// Specifies which trusted types are defined by a resource.
-    TrustedTypes(Vec<&'a str>),
-    /// Block HTTP requests on insecure elements.
-    UpgradeInsecureRequests,
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema),
+    serde(rename_all = "kebab-case"),
+    schemars(description = "`Referrer-Policy` header value")
+)]
+pub enum ReferrerPolicy {
+    NoReferrer,
+    NoReferrerWhenDowngrade,
+    Origin,
+    OriginWhenCrossOrigin,
+    SameOrigin,
+    StrictOrigin,
+    StrictOriginWhenCrossOrigin,
+    UnsafeUrl,
 }
 
-impl<'a> ContentSecurityPolicyDirective<'a> {
-    /// child-src: Defines valid sources for web workers and nested browsing contexts loaded using elements such as `<frame>`` and `<iframe>`.
-    pub fn child_src(values: Vec<&'a str>) -> Self {
-        Self::ChildSrc(values)
+impl ReferrerPolicy {
+    pub fn no_referrer() -> Self {
+        Self::NoReferrer
     }
 
-    /// connect-src: Applies to XMLHttpRequest (AJAX), WebSocket or EventSource. If not allowed the browser emulates a 400 HTTP status code.
-    pub fn connect_src(values: Vec<&'a str>) -> Self {
-        Self::ConnectSrc(values)
+    pub fn no_referrer_when_downgrade() -> Self {
+        Self::NoReferrerWhenDowngrade
     }
 
-    /// default-src: The default-src is the default policy for loading content such as JavaScript, Images, CSS, Font's, AJAX requests, Frames, HTML5 Media. See the list of directives to see which values are allowed as default.
-    pub fn default_src(values: Vec<&'a str>) -> Self {
-        Self::DefaultSrc(values)
+    pub fn origin() -> Self {
+        Self::Origin
     }
 
-    /// font-src: Defines valid sources for fonts loaded using @font-face.
-    pub fn font_src(values: Vec<&'a str>) -> Self {
-        Self::FontSrc(values)
+    pub fn origin_when_cross_origin() -> Self {
+        Self::OriginWhenCrossOrigin
     }
 
-    /// frame-src: Defines valid sources for nested browsing contexts loading using elements such as `<frame>` and `<iframe>`.
-    pub fn frame_src(values: Vec<&'a str>) -> Self {
-        Self::FrameSrc(values)
+    pub fn same_origin() -> Self {
+        Self::SameOrigin
     }
 
-    /// img-src: Defines valid sources of images and favicons.
-    pub fn img_src(values: Vec<&'a str>) -> Self {
-        Self::ImgSrc(values)
+    pub fn strict_origin() -> Self {
+        Self::StrictOrigin
     }
 
-    /// manifest-src: Specifies which manifest can be applied to the resource.
-    pub fn manifest_src(values: Vec<&'a str>) -> Self {
-        Self::ManifestSrc(values)
+    pub fn strict_origin_when_cross_origin() -> Self {
+        Self::StrictOriginWhenCrossOrigin
     }
 
-    /// media-src: Defines valid sources for loading media using the `<audio>` and `<video>` elements.
-    pub fn media_src(values: Vec<&'a str>) -> Self {
-        Self::MediaSrc(values)
+    pub fn unsafe_url() -> Self {
+        Self::UnsafeUrl
     }
+}
 
-    /// object-src: Defines valid sources for the `<object>`, `<embed>`, and `<applet>` elements.
-    pub fn object_src(values: Vec<&'a str>) -> Self {
-        Self::ObjectSrc(values)
+impl Display for ReferrerPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReferrerPolicy::NoReferrer => write!(f, "no-referrer"),
+            ReferrerPolicy::NoReferrerWhenDowngrade => write!(f, "no-referrer-when-downgrade"),
+            ReferrerPolicy::Origin => write!(f, "origin"),
+            ReferrerPolicy::OriginWhenCrossOrigin => write!(f, "origin-when-cross-origin"),
+            ReferrerPolicy::SameOrigin => write!(f, "same-origin"),
+            ReferrerPolicy::StrictOrigin => write!(f, "strict-origin"),
+            ReferrerPolicy::StrictOriginWhenCrossOrigin => {
+                write!(f, "strict-origin-when-cross-origin")
+            }
+            ReferrerPolicy::UnsafeUrl => write!(f, "unsafe-url"),
+        }
     }
+}
 
-    /// prefetch-src: Specifies which referrer to use when fetching the resource.
-    pub fn prefetch_src(values: Vec<&'a str>) -> Self {
-        Self::PrefetchSrc(values)
+impl Header for ReferrerPolicy {
+    fn name(&self) -> &'static str {
+        "Referrer-Policy"
     }
 
-    /// script-src: Defines valid sources for JavaScript.
-    pub fn script_src(values: Vec<&'a str>) -> Self {
-        Self::ScriptSrc(values)
+    fn value(&self) -> String {
+        self.to_string()
     }
+}
 
-    /// script-src-elem: Defines valid sources for JavaScript inline event handlers.
-    pub fn script_src_elem(values: Vec<&'a str>) -> Self {
-        Self::ScriptSrcElem(values)
+/// Manages `Reporting-Endpoints` header
+///
+/// The Reporting-Endpoints HTTP response header names the endpoints browsers send reports to, e.g. violations of `Content-Security-Policy`, `Cross-Origin-Opener-Policy` or `Cross-Origin-Embedder-Policy`. Those headers reference an endpoint by its name.
+///
+/// Endpoints are usually registered with [`Helmet::reporting_endpoint`], which adds this header for you.
+///
+/// # Examples
+///
+/// ```
+/// use helmet_core::ReportingEndpoints;
+///
+/// let reporting_endpoints = ReportingEndpoints::new()
+///     .endpoint("csp", "https://example.com/csp-reports")
+///     .endpoint("default", "https://example.com/reports");
+///
+/// assert_eq!(
+///     reporting_endpoints.to_string(),
+///     "csp=\"https://example.com/csp-reports\", default=\"https://example.com/reports\""
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReportingEndpoints(Vec<(String, String)>);
+
+impl ReportingEndpoints {
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    /// script-src-attr: Defines valid sources for JavaScript inline event handlers.
-    pub fn script_src_attr(values: Vec<&'a str>) -> Self {
-        Self::ScriptSrcAttr(values)
+    /// Add an endpoint, replacing the URL of an existing endpoint with the same name.
+    pub fn endpoint(mut self, name: &str, url: &str) -> Self {
+        match self.0.iter_mut().find(|(n, _)| n == name) {
+            Some((_, existing)) => *existing = url.to_string(),
+            None => self.0.push((name.to_string(), url.to_string())),
+        }
+        self
     }
+}
 
-    /// style-src: Defines valid sources for stylesheets.
-    pub fn style_src(values: Vec<&'a str>) -> Self {
-        Self::StyleSrc(values)
+impl Display for ReportingEndpoints {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let endpoints = self
+            .0
+            .iter()
+            .map(|(name, url)| format!("{}=\"{}\"", name, url))
+            .collect::<Vec<String>>()
+            .join(", ");
+        write!(f, "{}", endpoints)
     }
+}
 
-    /// style-src-elem: Defines valid sources for stylesheets inline event handlers.
-    pub fn style_src_elem(values: Vec<&'a str>) -> Self {
-        Self::StyleSrcElem(values)
+impl Header for ReportingEndpoints {
+    fn name(&self) -> &'static str {
+        "Reporting-Endpoints"
     }
 
-    /// style-src-attr: Defines valid sources for stylesheets inline event handlers.
-    pub fn style_src_attr(values: Vec<&'a str>) -> Self {
-        Self::StyleSrcAttr(values)
+    fn value(&self) -> String {
+        self.to_string()
     }
 
-    /// worker-src: Defines valid sources for Worker, SharedWorker, or ServiceWorker scripts.
-    pub fn worker_src(values: Vec<&'a str>) -> Self {
-        Self::WorkerSrc(values)
+    fn validate(&self) -> Result<(), HelmetError> {
+        for (name, url) in self.0.iter() {
+            validate_endpoint_name(self.name(), name)?;
+            validate_endpoint_name(self.name(), url)?;
+        }
+        Ok(())
     }
+}
 
-    /// base-uri: Restricts the URLs which can be used in a document's `<base>` element.
-    pub fn base_uri(values: Vec<&'a str>) -> Self {
-        Self::BaseUri(values)
+/// Endpoint group of the legacy `Report-To` header
+///
+/// # Examples
+///
+/// ```
+/// use helmet_core::ReportToGroup;
+///
+/// let group = ReportToGroup::new("csp", 10886400)
+///     .endpoint("https://example.com/csp-reports")
+///     .include_subdomains();
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema),
+    serde(deny_unknown_fields),
+    schemars(description = "Endpoint group of a `Report-To` header")
+)]
+pub struct ReportToGroup {
+    group: String,
+    max_age: u64,
+    endpoints: Vec<String>,
+    #[cfg_attr(feature = "serde", serde(default))]
+    include_subdomains: bool,
+}
+
+impl ReportToGroup {
+    /// Create a group, which browsers remember for `max_age` seconds.
+    pub fn new(group: &str, max_age: u64) -> Self {
+        Self {
+            group: group.to_string(),
+            max_age,
+            endpoints: Vec::new(),
+            include_subdomains: false,
+        }
     }
 
-    /// sandbox: Enables a sandbox for the requested resource similar to the iframe sandbox attribute. The sandbox applies a same origin policy, prevents popups, plugins and script execution is blocked. You can keep the sandbox value empty to keep all restrictions in place, or add values: allow-forms allow-same-origin allow-scripts allow-popups, allow-modals, allow-orientation-lock, allow-pointer-lock, allow-presentation, allow-popups-to-escape-sandbox, allow-top-navigation, allow-top-navigation-by-user-activation.
-    pub fn sandbox(values: Vec<&'a str>) -> Self {
-        Self::Sandbox(values)
+    /// Add an endpoint URL to the group.
+    pub fn endpoint(mut self, url: &str) -> Self {
+        self.endpoints.push(url.to_string());
+        self
     }
 
-    /// form-action: Restricts the URLs which can be used as the target of a form submissions from a given context.
-    pub fn form_action(values: Vec<&'a str>) -> Self {
-        Self::FormAction(values)
+    /// Use the group for reports of subdomains as well.
+    pub fn include_subdomains(mut self) -> Self {
+        self.include_subdomains = true;
+        self
     }
+}
 
-    /// frame-ancestors: Specifies valid parents that may embed a page using `<frame>`, `<iframe>`, `<object>`, `<embed>`, or `<applet>`.
-    pub fn frame_ancestors(values: Vec<&'a str>) -> Self {
-        Self::FrameAncestors(values)
+impl Display for ReportToGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let endpoints = self
+            .endpoints
+            .iter()
+            .map(|url| format!("{{\"url\":{}}}", json_string(url)))
+            .collect::<Vec<String>>()
+            .join(",");
+        write!(
+            f,
+            "{{\"group\":{},\"max_age\":{},\"endpoints\":[{}]",
+            json_string(&self.group),
+            self.max_age,
+            endpoints
+        )?;
+        if self.include_subdomains {
+            write!(f, ",\"include_subdomains\":true")?;
+        }
+        write!(f, "}}")
+    }
+}
+
+/// Manages the legacy `Report-To` header
+///
+/// Report-To is the predecessor of `Reporting-Endpoints`, still used by some browsers. Its value is a list of JSON objects, one for each endpoint group. `report-to` directives reference a group by its name.
+///
+/// # Examples
+///
+/// ```
+/// use helmet_core::{ReportTo, ReportToGroup};
+///
+/// let report_to = ReportTo::new().group(
+///     ReportToGroup::new("csp", 10886400).endpoint("https://example.com/csp-reports"),
+/// );
+///
+/// assert_eq!(
+///     report_to.to_string(),
+///     r#"{"group":"csp","max_age":10886400,"endpoints":[{"url":"https://example.com/csp-reports"}]}"#
+/// );
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema),
+    schemars(description = "`Report-To` header, a list of endpoint groups")
+)]
+pub struct ReportTo(Vec<ReportToGroup>);
+
+impl ReportTo {
+    pub fn new() -> Self {
+        Self::default()
     }
 
-    /// report-to: Enables reporting of violations.
-    pub fn report_to(values: Vec<&'a str>) -> Self {
-        Self::ReportTo(values)
+    /// Add an endpoint group.
+    pub fn group(mut self, group: ReportToGroup) -> Self {
+        self.0.push(group);
+        self
     }
+}
 
-    /// require-trusted-types-for: Specifies which trusted types are required by a resource.
-    pub fn require_trusted_types_for(values: Vec<&'a str>) -> Self {
-        Self::RequireTrustedTypesFor(values)
+impl Display for ReportTo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let groups = self
+            .0
+            .iter()
+            .map(|group| group.to_string())
+            .collect::<Vec<String>>()
+            .join(", ");
+        write!(f, "{}", groups)
     }
+}
 
-    /// trusted-types: Specifies which trusted types are defined by a resource.
-    pub fn trusted_types(values: Vec<&'a str>) -> Self {
-        Self::TrustedTypes(values)
+impl Header for ReportTo {
+    fn name(&self) -> &'static str {
+        "Report-To"
     }
 
-    /// Block HTTP requests on insecure elements.
-    pub fn upgrade_insecure_requests() -> Self {
-        Self::UpgradeInsecureRequests
+    fn value(&self) -> String {
+        self.to_string()
     }
 }
 
-impl Display for ContentSecurityPolicyDirective<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            ContentSecurityPolicyDirective::ChildSrc(values) => {
-                write!(f, "child-src {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::ConnectSrc(values) => {
-                write!(f, "connect-src {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::DefaultSrc(values) => {
-                write!(f, "default-src {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::FontSrc(values) => {
-                write!(f, "font-src {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::FrameSrc(values) => {
-                write!(f, "frame-src {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::ImgSrc(values) => {
-                write!(f, "img-src {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::ManifestSrc(values) => {
-                write!(f, "manifest-src {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::MediaSrc(values) => {
-                write!(f, "media-src {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::ObjectSrc(values) => {
-                write!(f, "object-src {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::PrefetchSrc(values) => {
-                write!(f, "prefetch-src {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::ScriptSrc(values) => {
-                write!(f, "script-src {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::ScriptSrcElem(values) => {
-                write!(f, "script-src-elem {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::ScriptSrcAttr(values) => {
-                write!(f, "script-src-attr {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::StyleSrc(values) => {
-                write!(f, "style-src {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::StyleSrcElem(values) => {
-                write!(f, "style-src-elem {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::StyleSrcAttr(values) => {
-                write!(f, "style-src-attr {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::WorkerSrc(values) => {
-                write!(f, "worker-src {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::BaseUri(values) => {
-                write!(f, "base-uri {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::Sandbox(values) => {
-                write!(f, "sandbox {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::FormAction(values) => {
-                write!(f, "form-action {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::FrameAncestors(values) => {
-                write!(f, "frame-ancestors {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::ReportTo(values) => {
-                let values = values.join(" ");
-                write!(f, "report-to {}; report-uri {}", values, values)
-            }
-            ContentSecurityPolicyDirective::RequireTrustedTypesFor(values) => {
-                write!(f, "require-trusted-types-for {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::TrustedTypes(values) => {
-                write!(f, "trusted-types {}", values.join(" "))
-            }
-            ContentSecurityPolicyDirective::UpgradeInsecureRequests => {
-                write!(f, "upgrade-insecure-requests")
-            }
+// Endpoint names and URLs are quoted strings in structured headers, so they can't contain quotes or backslashes.
+fn validate_endpoint_name(name: &str, value: &str) -> Result<(), HelmetError> {
+    if value.is_empty()
+        || !value
+            .bytes()
+            .all(|b| (0x21..=0x7e).contains(&b) && !b"\"\\,;".contains(&b))
+    {
+        return Err(HelmetError::InvalidHeaderValue {
+            name: name.to_string(),
+            value: value.to_string(),
+        });
+    }
+    Ok(())
+}
+
+fn json_string(value: &str) -> String {
+    let mut json = String::with_capacity(value.len() + 2);
+    json.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
         }
     }
+    json.push('"');
+    json
 }
 
-/// Manages `Content-Security-Policy` header
+/// Manages `Strict-Transport-Security` header
 ///
-/// The HTTP Content-Security-Policy response header allows web site administrators to control resources the user agent is allowed to load for a given page. With a few exceptions, policies mostly involve specifying server origins and script endpoints. This helps guard against cross-site scripting attacks (XSS).
+/// The Strict-Transport-Security HTTP response header (often abbreviated as HSTS) lets a web site tell browsers that it should only be accessed using HTTPS, instead of using HTTP.
+///
+/// # Values
+///
+/// - max-age: The time, in seconds, that the browser should remember that a site is only to be accessed using HTTPS.
+/// - includeSubDomains: If this optional parameter is specified, this rule applies to all of the site's subdomains as well.
+/// - preload: Consents to including the domain in the HSTS preload list built into browsers. The list only accepts a max-age of at least one year with includeSubDomains, so it is only set by [`StrictTransportSecurity::preloadable`]. Other combinations, e.g. from a config file, are rejected by [`Helmet::validate`].
 ///
 /// # Examples
 ///
 /// ```
-/// use helmet_core::ContentSecurityPolicy;
+/// use std::time::Duration;
 ///
-/// let content_security_policy = ContentSecurityPolicy::default()
-///    .child_src(vec!["'self'", "https://youtube.com"])
-///    .connect_src(vec!["'self'", "https://youtube.com"])
-///    .default_src(vec!["'self'", "https://youtube.com"])
-///    .font_src(vec!["'self'", "https://youtube.com"]);
+/// use helmet_core::StrictTransportSecurity;
+///
+/// let strict_transport_security = StrictTransportSecurity::default();
+///
+/// let custom_strict_transport_security = StrictTransportSecurity::default()
+///    .max_age(31536000)
+///    .include_sub_domains();
+///
+/// let two_years = StrictTransportSecurity::new().max_age(Duration::from_secs(2 * 365 * 24 * 60 * 60));
+/// assert_eq!(two_years.to_string(), "max-age=63072000");
 /// ```
 ///
-/// ## Report only
+/// ## Preloading
+///
+/// `preload` is only available once the max-age and includeSubDomains meet the requirements of the preload list.
 ///
-/// In report only mode, the browser will not block the request, but will send a report to the specified URI.
+/// ```
+/// use std::time::Duration;
+///
+/// use helmet_core::{PreloadMaxAge, StrictTransportSecurity};
 ///
-/// Make sure to set the `report-to` directive.
+/// let preload = StrictTransportSecurity::preloadable(PreloadMaxAge::TWO_YEARS)
+///     .include_sub_domains()
+///     .preload();
+/// assert_eq!(preload.to_string(), "max-age=63072000; includeSubDomains; preload");
 ///
+/// assert!(PreloadMaxAge::new(Duration::from_secs(400 * 24 * 60 * 60)).is_some());
+/// assert!(PreloadMaxAge::new(300).is_none());
 /// ```
-/// use helmet_core::ContentSecurityPolicy;
 ///
-/// let content_security_policy = ContentSecurityPolicy::default()
-///    .child_src(vec!["'self'", "https://youtube.com"])
-///    .report_to(vec!["https://example.com/report"])
-///    .report_only();
+/// Without includeSubDomains there is no `preload` to call:
+///
+/// ```compile_fail
+/// use helmet_core::{PreloadMaxAge, StrictTransportSecurity};
+///
+/// let preload = StrictTransportSecurity::preloadable(PreloadMaxAge::ONE_YEAR).preload();
 /// ```
-#[derive(Clone)]
-pub struct ContentSecurityPolicy<'a> {
-    directives: Vec<ContentSecurityPolicyDirective<'a>>,
-    report_only: bool,
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema),
+    serde(default, deny_unknown_fields),
+    schemars(description = "`Strict-Transport-Security` header")
+)]
+pub struct StrictTransportSecurity {
+    max_age: u32,
+    include_sub_domains: bool,
+    preload: bool,
 }
 
-impl<'a> ContentSecurityPolicy<'a> {
+impl StrictTransportSecurity {
     pub fn new() -> Self {
-        Self {
-            directives: Vec::new(),
-            report_only: false,
-        }
+        Self::default()
     }
 
-    fn directive(mut self, directive: ContentSecurityPolicyDirective<'a>) -> Self {
-        self.directives.push(directive);
+    /// Set max-age, in seconds or as a [`Duration`](std::time::Duration)
+    pub fn max_age(mut self, max_age: impl Into<MaxAge>) -> Self {
+        self.max_age = max_age.into().0;
         self
     }
 
-    /// child-src: Defines valid sources for web workers and nested browsing contexts loaded using elements such as `<frame>` and `<iframe>`.
-    pub fn child_src(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::child_src(values))
+    pub fn include_sub_domains(mut self) -> Self {
+        self.include_sub_domains = true;
+        self
     }
 
-    /// connect-src: Applies to XMLHttpRequest (AJAX), WebSocket or EventSource. If not allowed the browser emulates a 400 HTTP status code.
-    pub fn connect_src(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::connect_src(values))
+    /// Start a header that can consent to preloading, see [`PreloadableStrictTransportSecurity`]
+    pub fn preloadable(
+        max_age: PreloadMaxAge,
+    ) -> PreloadableStrictTransportSecurity<SubDomainsExcluded> {
+        PreloadableStrictTransportSecurity {
+            max_age: max_age.0,
+            sub_domains: std::marker::PhantomData,
+        }
     }
 
-    /// default-src: The default-src is the default policy for loading content such as JavaScript, Images, CSS, Font's, AJAX requests, Frames, HTML5 Media. See the list of directives to see which values are allowed as default.
-    pub fn default_src(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::default_src(values))
-    }
+    /// Minimum max-age accepted by the HSTS preload list, one year
+    pub const PRELOAD_MIN_MAX_AGE: u32 = 31536000;
 
-    /// font-src: Defines valid sources for fonts loaded using @font-face.
-    pub fn font_src(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::font_src(values))
+    fn is_preloadable(&self) -> bool {
+        self.max_age >= Self::PRELOAD_MIN_MAX_AGE && self.include_sub_domains
     }
 
-    /// frame-src: Defines valid sources for nested browsing contexts loading using elements such as `<frame>` and `<iframe>`.
-    pub fn frame_src(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::frame_src(values))
+    /// Check that `preload` is only combined with a max-age of at least one year and includeSubDomains, as required by the HSTS preload list.
+    pub fn validate(&self) -> Result<(), HelmetError> {
+        if self.preload && !self.is_preloadable() {
+            return Err(HelmetError::InvalidPreload {
+                value: self.to_string(),
+            });
+        }
+        Ok(())
     }
+}
 
-    /// img-src: Defines valid sources of images and favicons.
-    pub fn img_src(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::img_src(values))
-    }
+/// max-age of [`StrictTransportSecurity`], from seconds or a [`Duration`](std::time::Duration)
+///
+/// Durations are truncated to whole seconds, and saturate at `u32::MAX` seconds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MaxAge(u32);
 
-    /// manifest-src: Specifies which manifest can be applied to the resource.
-    pub fn manifest_src(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::manifest_src(values))
+impl From<u32> for MaxAge {
+    fn from(seconds: u32) -> Self {
+        Self(seconds)
     }
+}
 
-    /// media-src: Defines valid sources for loading media using the `<audio>` and `<video>` elements.
-    pub fn media_src(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::media_src(values))
+impl From<std::time::Duration> for MaxAge {
+    fn from(duration: std::time::Duration) -> Self {
+        Self(u32::try_from(duration.as_secs()).unwrap_or(u32::MAX))
     }
+}
 
-    /// object-src: Defines valid sources for the `<object>`, `<embed>`, and `<applet>` elements.
-    pub fn object_src(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::object_src(values))
-    }
+/// max-age accepted by the HSTS preload list, at least [`StrictTransportSecurity::PRELOAD_MIN_MAX_AGE`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PreloadMaxAge(u32);
 
-    /// prefetch-src: Specifies which referrer to use when fetching the resource.
-    pub fn prefetch_src(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::prefetch_src(values))
-    }
+impl PreloadMaxAge {
+    pub const ONE_YEAR: Self = Self(StrictTransportSecurity::PRELOAD_MIN_MAX_AGE);
+    pub const TWO_YEARS: Self = Self(63072000);
 
-    /// script-src: Defines valid sources for JavaScript.
-    pub fn script_src(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::script_src(values))
+    /// `None` when `max_age` is shorter than one year
+    pub fn new(max_age: impl Into<MaxAge>) -> Option<Self> {
+        let MaxAge(max_age) = max_age.into();
+        (max_age >= StrictTransportSecurity::PRELOAD_MIN_MAX_AGE).then_some(Self(max_age))
     }
+}
 
-    /// script-src-elem: Defines valid sources for JavaScript inline event handlers.
-    pub fn script_src_elem(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::script_src_elem(values))
+/// Typestate builder of a [`StrictTransportSecurity`] consenting to preloading
+///
+/// Created by [`StrictTransportSecurity::preloadable`] with a [`PreloadMaxAge`]. `preload` is only available after `include_sub_domains`, so the header can't be rejected by the preload list.
+#[derive(Clone, Debug)]
+pub struct PreloadableStrictTransportSecurity<S> {
+    max_age: u32,
+    sub_domains: std::marker::PhantomData<S>,
+}
+
+/// includeSubDomains not set yet, see [`PreloadableStrictTransportSecurity`]
+#[derive(Clone, Copy, Debug)]
+pub struct SubDomainsExcluded;
+
+/// includeSubDomains set, see [`PreloadableStrictTransportSecurity`]
+#[derive(Clone, Copy, Debug)]
+pub struct SubDomainsIncluded;
+
+impl PreloadableStrictTransportSecurity<SubDomainsExcluded> {
+    pub fn include_sub_domains(self) -> PreloadableStrictTransportSecurity<SubDomainsIncluded> {
+        PreloadableStrictTransportSecurity {
+            max_age: self.max_age,
+            sub_domains: std::marker::PhantomData,
+        }
     }
+}
 
-    /// script-src-attr: Defines valid sources for JavaScript inline event handlers.
-    pub fn script_src_attr(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::script_src_attr(values))
+impl PreloadableStrictTransportSecurity<SubDomainsIncluded> {
+    /// Consent to preloading
+    pub fn preload(self) -> StrictTransportSecurity {
+        StrictTransportSecurity {
+            max_age: self.max_age,
+            include_sub_domains: true,
+            preload: true,
+        }
     }
+}
 
-    /// style-src: Defines valid sources for stylesheets.
-    pub fn style_src(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::style_src(values))
+impl Default for StrictTransportSecurity {
+    fn default() -> Self {
+        Self {
+            max_age: 31536000,
+            include_sub_domains: false,
+            preload: false,
+        }
     }
+}
 
-    /// style-src-elem: Defines valid sources for stylesheets inline event handlers.
-    pub fn style_src_elem(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::style_src_elem(values))
+impl Display for StrictTransportSecurity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "max-age={}", self.max_age)?;
+        if self.include_sub_domains {
+            write!(f, "; includeSubDomains")?;
+        }
+        if self.preload {
+            write!(f, "; preload")?;
+        }
+        Ok(())
     }
+}
 
-    /// style-src-attr: Defines valid sources for stylesheets inline event handlers.
-    pub fn style_src_attr(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::style_src_attr(values))
+impl Header for StrictTransportSecurity {
+    fn name(&self) -> &'static str {
+        "Strict-Transport-Security"
     }
 
-    /// worker-src: Defines valid sources for Worker, SharedWorker, or ServiceWorker scripts.
-    pub fn worker_src(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::worker_src(values))
+    fn value(&self) -> String {
+        self.to_string()
     }
 
-    /// base-uri: Restricts the URLs which can be used in a document's `<base>` element.
-    pub fn base_uri(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::base_uri(values))
+    fn validate(&self) -> Result<(), HelmetError> {
+        StrictTransportSecurity::validate(self)
     }
+}
 
-    /// sandbox: Enables a sandbox for the requested resource similar to the iframe sandbox attribute. The sandbox applies a same origin policy, prevents popups, plugins and script execution is blocked. You can keep the sandbox value empty to keep all restrictions in place, or add values: allow-forms allow-same-origin allow-scripts allow-popups, allow-modals, allow-orientation-lock, allow-pointer-lock, allow-presentation, allow-popups-to-escape-sandbox, allow-top-navigation, allow-top-navigation-by-user-activation.
-    pub fn sandbox(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::sandbox(values))
+/// Schedule raising the max-age of [`StrictTransportSecurity`] step by step
+///
+/// A long max-age can't be taken back: browsers keep enforcing HTTPS until it expires, even after the header is removed. The [HSTS preload guidelines](https://hstspreload.org/#deployment-recommendations) recommend starting with a short max-age and raising it once the site, and every subdomain, works over HTTPS.
+///
+/// Each step applies from its offset after `start`. The schedule is a [`Header`] whose value is the step at the current time, see [`Header::changes_over_time`], so a running server moves on to the next step without a restart. [`StrictTransportSecuritySchedule::at`] returns the header of the step at a given time.
+///
+/// ```
+/// use std::time::{Duration, SystemTime};
+///
+/// use helmet_core::{Header, Helmet, StrictTransportSecuritySchedule};
+///
+/// const WEEK: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+///
+/// let start = SystemTime::UNIX_EPOCH + Duration::from_secs(1767225600); // 2026-01-01
+/// let schedule = StrictTransportSecuritySchedule::preload(start, 2 * WEEK);
+///
+/// assert_eq!(schedule.at(start).value(), "max-age=300; includeSubDomains");
+/// assert_eq!(schedule.at(start + 3 * WEEK).value(), "max-age=604800; includeSubDomains");
+/// assert_eq!(schedule.at(start + 5 * WEEK).value(), "max-age=2592000; includeSubDomains");
+/// assert_eq!(schedule.at(start + 6 * WEEK).value(), "max-age=63072000; includeSubDomains; preload");
+///
+/// // replaces the `Strict-Transport-Security` header of the defaults
+/// let helmet = Helmet::default().add(schedule);
+/// ```
+#[derive(Clone, Debug)]
+pub struct StrictTransportSecuritySchedule {
+    start: std::time::SystemTime,
+    steps: Vec<(std::time::Duration, StrictTransportSecurity)>,
+}
+
+impl StrictTransportSecuritySchedule {
+    /// Create a schedule starting at `start`, sending `header` until the next step
+    pub fn new(start: std::time::SystemTime, header: StrictTransportSecurity) -> Self {
+        Self {
+            start,
+            steps: vec![(std::time::Duration::ZERO, header)],
+        }
     }
 
-    /// form-action: Restricts the URLs which can be used as the target of a form submissions from a given context.
-    pub fn form_action(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::form_action(values))
+    /// Schedule recommended for preloading: 5 minutes, 1 week and 1 month with includeSubDomains, then 2 years with preload, each step lasting `step`
+    pub fn preload(start: std::time::SystemTime, step: std::time::Duration) -> Self {
+        let header = |max_age: u32| {
+            StrictTransportSecurity::new()
+                .max_age(max_age)
+                .include_sub_domains()
+        };
+        Self::new(start, header(300))
+            .step(step, header(604800))
+            .step(step * 2, header(2592000))
+            .step(
+                step * 3,
+                StrictTransportSecurity::preloadable(PreloadMaxAge::TWO_YEARS)
+                    .include_sub_domains()
+                    .preload(),
+            )
     }
 
-    /// frame-ancestors: Specifies valid parents that may embed a page using `<frame>`, `<iframe>`, `<object>`, `<embed>`, or `<applet>`.
-    pub fn frame_ancestors(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::frame_ancestors(values))
+    /// Send `header` from `after` the start of the schedule on
+    pub fn step(mut self, after: std::time::Duration, header: StrictTransportSecurity) -> Self {
+        let index = self.steps.partition_point(|(offset, _)| *offset <= after);
+        self.steps.insert(index, (after, header));
+        self
     }
 
-    /// report-to: Enables reporting of violations.
-    pub fn report_to(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::report_to(values))
+    /// Header of the step at `time`, the first step before the schedule starts
+    pub fn at(&self, time: std::time::SystemTime) -> StrictTransportSecurity {
+        let elapsed = time.duration_since(self.start).unwrap_or_default();
+        self.steps
+            .iter()
+            .rev()
+            .find(|(offset, _)| *offset <= elapsed)
+            .unwrap_or(&self.steps[0])
+            .1
+            .clone()
     }
 
-    /// require-trusted-types-for: Specifies which trusted types are required by a resource.
-    pub fn require_trusted_types_for(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::require_trusted_types_for(
-            values,
-        ))
+    /// Header of the current step
+    pub fn current(&self) -> StrictTransportSecurity {
+        self.at(std::time::SystemTime::now())
     }
+}
 
-    /// trusted-types: Specifies which trusted types are defined by a resource.
-    pub fn trusted_types(self, values: Vec<&'a str>) -> Self {
-        self.directive(ContentSecurityPolicyDirective::trusted_types(values))
+impl Header for StrictTransportSecuritySchedule {
+    fn name(&self) -> &'static str {
+        "Strict-Transport-Security"
     }
 
-    /// Block HTTP requests on insecure elements.
-    pub fn upgrade_insecure_requests(self) -> Self {
-        self.directive(ContentSecurityPolicyDirective::upgrade_insecure_requests())
+    fn value(&self) -> String {
+        self.current().to_string()
     }
 
-    /// Enable report only mode
-    ///
-    /// When set to true, the `Content-Security-Policy-Report-Only` header is set instead of `Content-Security-Policy`.
-    ///
-    /// Defaults to false.
-    ///
-    /// See https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Content-Security-Policy-Report-Only
-    pub fn report_only(mut self) -> Self {
-        self.report_only = true;
-        self
+    fn changes_over_time(&self) -> bool {
+        true
     }
-}
 
-impl<'a> Display for ContentSecurityPolicy<'a> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let directives = self
-            .directives
+    fn validate(&self) -> Result<(), HelmetError> {
+        self.steps
             .iter()
-            .map(|d| d.to_string())
-            .collect::<Vec<String>>()
-            .join("; ");
-        write!(f, "{}", directives)
+            .try_for_each(|(_, header)| header.validate())
     }
 }
 
-impl<'a> Default for ContentSecurityPolicy<'a> {
-    /// Default policy for the Content-Security-Policy header.
-    ///
-    /// values:
-    /// ```text
-    /// default-src 'self';
-    /// base-uri 'self';
-    /// font-src 'self' https: data:;
-    /// form-action 'self';
-    /// frame-ancestors 'self';
-    /// img-src 'self' data:;
-    /// object-src 'none';
-    /// script-src 'self';
-    /// script-src-attr 'none';
-    /// style-src 'self' https: 'unsafe-inline';
-    /// upgrade-insecure-requests
-    /// ```
-    fn default() -> Self {
-        Self::new()
-            .default_src(vec!["'self'"])
-            .base_uri(vec!["'self'"])
-            .font_src(vec!["'self'", "https:", "data:"])
-            .form_action(vec!["'self'"])
-            .frame_ancestors(vec!["'self'"])
-            .img_src(vec!["'self'", "data:"])
-            .object_src(vec!["'none'"])
-            .script_src(vec!["'self'"])
-            .script_src_attr(vec!["'none'"])
-            .style_src(vec!["'self'", "https:", "'unsafe-inline'"])
-            .upgrade_insecure_requests()
+/// Manages `X-Content-Type-Options` header
+///
+/// The X-Content-Type-Options response HTTP header is a marker used by the server to indicate that the MIME types advertised in the Content-Type headers should not be changed and be followed. This allows to opt-out of MIME type sniffing, or, in other words, it is a way to say that the webmasters knew what they were doing.
+///
+/// # Values
+///
+/// - nosniff: Prevents the browser from MIME-sniffing a response away from the declared content-type. This also applies to Google Chrome, when downloading extensions.
+///
+/// # Examples
+///
+/// ```
+/// use helmet_core::XContentTypeOptions;
+///
+/// let x_content_type_options = XContentTypeOptions::nosniff();
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema),
+    serde(rename_all = "lowercase"),
+    schemars(description = "`X-Content-Type-Options` header value")
+)]
+pub enum XContentTypeOptions {
+    NoSniff,
+}
+
+impl XContentTypeOptions {
+    pub fn nosniff() -> Self {
+        Self::NoSniff
     }
 }
 
-impl<'a> Header for ContentSecurityPolicy<'a> {
-    fn name(&self) -> &'static str {
-        if self.report_only {
-            "Content-Security-Policy-Report-Only"
-        } else {
-            "Content-Security-Policy"
+impl Display for XContentTypeOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XContentTypeOptions::NoSniff => write!(f, "nosniff"),
         }
     }
+}
+
+impl Header for XContentTypeOptions {
+    fn name(&self) -> &'static str {
+        "X-Content-Type-Options"
+    }
 
     fn value(&self) -> String {
         self.to_string()
     }
 }
 
-/// Helmet security headers middleware for ntex services
+/// Manages `X-DNS-Prefetch-Control` header
+///
+/// The X-DNS-Prefetch-Control HTTP response header controls DNS prefetching, a feature by which browsers proactively perform domain name resolution on both links that the user may choose to follow as well as URLs for items referenced by the document, including images, CSS, JavaScript, and so forth.
+///
+/// # Values
+///
+/// - off: Disable DNS prefetching.
+/// - on: Enable DNS prefetching, allowing the browser to proactively perform domain name resolution on both links that the user may choose to follow as well as URLs for items referenced by the document, including images, CSS, JavaScript, and so forth.
 ///
 /// # Examples
 ///
 /// ```
-/// use helmet_core::Helmet;
+/// use helmet_core::XDNSPrefetchControl;
 ///
-/// let helmet = Helmet::default();
+/// let x_dns_prefetch_control = XDNSPrefetchControl::off();
 /// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema),
+    serde(rename_all = "lowercase"),
+    schemars(description = "`X-DNS-Prefetch-Control` header value")
+)]
+pub enum XDNSPrefetchControl {
+    Off,
+    On,
+}
+
+impl XDNSPrefetchControl {
+    pub fn off() -> Self {
+        Self::Off
+    }
+
+    pub fn on() -> Self {
+        Self::On
+    }
+}
+
+impl Display for XDNSPrefetchControl {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XDNSPrefetchControl::Off => write!(f, "off"),
+            XDNSPrefetchControl::On => write!(f, "on"),
+        }
+    }
+}
+
+impl Header for XDNSPrefetchControl {
+    fn name(&self) -> &'static str {
+        "X-DNS-Prefetch-Control"
+    }
+
+    fn value(&self) -> String {
+        self.to_string()
+    }
+
+    fn scope(&self) -> HeaderScope {
+        HeaderScope::Document
+    }
+}
+
+/// Manages `X-Download-Options` header
 ///
-/// ## Adding custom headers
+/// The X-Download-Options HTTP response header indicates that the browser (Internet Explorer) should not display the option to "Open" a file that has been downloaded from an application, to prevent phishing attacks that could trick users into opening potentially malicious content that could infect their computer.
+///
+/// # Values
+///
+/// - noopen: Prevents Internet Explorer from executing downloads in your site’s context.
+///
+/// # Examples
 ///
 /// ```
-/// use helmet_core::{Helmet, StrictTransportSecurity};
+/// use helmet_core::XDownloadOptions;
 ///
-/// let helmet = Helmet::new()
-///    .add(StrictTransportSecurity::new().max_age(31536000).include_sub_domains());
+/// let x_download_options = XDownloadOptions::noopen();
 /// ```
-pub struct Helmet {
-    pub headers: Vec<Box<dyn Header>>,
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema),
+    serde(rename_all = "lowercase"),
+    schemars(description = "`X-Download-Options` header value")
+)]
+pub enum XDownloadOptions {
+    NoOpen,
 }
 
-impl Helmet {
-    /// Create new `Helmet` instance without any headers applied
-    pub fn new() -> Self {
-        Self {
-            headers: Vec::new(),
+impl XDownloadOptions {
+    pub fn noopen() -> Self {
+        Self::NoOpen
+    }
+}
+
+impl Display for XDownloadOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XDownloadOptions::NoOpen => write!(f, "noopen"),
         }
     }
+}
 
-    /// Add header to the middleware
-    pub fn add(mut self, header: impl Header + 'static) -> Self {
-        self.headers.push(Box::new(header));
-        self
+impl Header for XDownloadOptions {
+    fn name(&self) -> &'static str {
+        "X-Download-Options"
+    }
+
+    fn value(&self) -> String {
+        self.to_string()
+    }
+
+    fn scope(&self) -> HeaderScope {
+        HeaderScope::Document
     }
 }
 
-impl Default for Helmet {
-    /// Default `Helmet` instance with all headers applied
+/// Manages `X-Frame-Options` header
+///
+/// The X-Frame-Options HTTP response header can be used to to avoid click-jacking attacks by preventing the content to be included in other websites.
+///
+/// # Values
+///
+/// - deny: The page cannot be displayed in a frame, regardless of the site attempting to do so.
+/// - sameorigin: The page can only be displayed in a frame on the same origin as the page itself.
+/// - allow-from: The page can only be displayed in a frame on the specified origin. Requires a URI as an argument.
+///
+/// # Examples
+///
+/// ```
+/// use helmet_core::XFrameOptions;
+///
+/// let x_frame_options = XFrameOptions::deny();
+///
+/// let x_frame_options = XFrameOptions::same_origin();
+///
+/// let x_frame_options = XFrameOptions::allow_from("https://example.com");
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema),
+    serde(rename_all = "lowercase"),
+    schemars(description = "`X-Frame-Options` header value")
+)]
+pub enum XFrameOptions {
+    Deny,
+    SameOrigin,
+    // deprecated - use Content-Security-Policy instead see https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/X-Frame-Options#allow-from_origin
+    #[cfg_attr(feature = "serde", serde(rename = "allow-from"))]
+    AllowFrom(String),
+}
+
+impl XFrameOptions {
+    pub fn deny() -> Self {
+        Self::Deny
+    }
+
+    pub fn same_origin() -> Self {
+        Self::SameOrigin
+    }
+
+    pub fn allow_from(uri: &str) -> Self {
+        Self::AllowFrom(uri.to_string())
+    }
+
+    /// Like [`XFrameOptions::allow_from`], but rejects URIs containing control characters, whitespace or `,`.
     ///
-    /// ```text
-    /// Content-Security-Policy: default-src 'self'; base-uri 'self'; font-src 'self' https: data:; form-action 'self'; frame-ancestors 'self'; img-src 'self' data:; object-src 'none'; script-src 'self'; script-src-attr 'none'; style-src 'self' https: 'unsafe-inline'; upgrade-insecure-requests
-    /// Cross-Origin-Opener-Policy: same-origin
-    /// Cross-Origin-Resource-Policy: same-origin
-    /// Origin-Agent-Cluster: ?1
-    /// Referrer-Policy: no-referrer
-    /// Strict-Transport-Security: max-age=15552000; includeSubDomains
-    /// X-Content-Type-Options: nosniff
-    /// X-DNS-Prefetch-Control: off
-    /// X-Download-Options: noopen
-    /// X-Frame-Options: sameorigin
-    /// X-Permitted-Cross-Domain-Policies: none
-    /// X-XSS-Protection: 0
     /// ```
-    fn default() -> Self {
-        Self::new()
-            .add(ContentSecurityPolicy::default())
-            .add(CrossOriginOpenerPolicy::same_origin())
-            .add(CrossOriginResourcePolicy::same_origin())
-            .add(OriginAgentCluster(true))
-            .add(ReferrerPolicy::no_referrer())
-            .add(
-                StrictTransportSecurity::new()
-                    .max_age(15552000)
-                    .include_sub_domains(),
-            )
-            .add(XContentTypeOptions::nosniff())
-            .add(XDNSPrefetchControl::off())
-            .add(XDownloadOptions::noopen())
-            .add(XFrameOptions::same_origin())
-            .add(XPermittedCrossDomainPolicies::none())
-            .add(XXSSProtection::off())
+    /// use helmet_core::XFrameOptions;
+    ///
+    /// assert!(XFrameOptions::try_allow_from("https://example.com").is_ok());
+    /// assert!(XFrameOptions::try_allow_from("https://example.com\r\nSet-Cookie: a=b").is_err());
+    /// ```
+    pub fn try_allow_from(uri: &str) -> Result<Self, HelmetError> {
+        validate_parameter("X-Frame-Options", uri)?;
+        Ok(Self::allow_from(uri))
+    }
+}
+
+impl Display for XFrameOptions {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XFrameOptions::Deny => write!(f, "DENY"),
+            XFrameOptions::SameOrigin => write!(f, "SAMEORIGIN"),
+            XFrameOptions::AllowFrom(uri) => write!(f, "ALLOW-FROM {}", uri),
+        }
+    }
+}
+
+impl Header for XFrameOptions {
+    fn name(&self) -> &'static str {
+        "X-Frame-Options"
+    }
+
+    fn value(&self) -> String {
+        self.to_string()
+    }
+
+    fn scope(&self) -> HeaderScope {
+        HeaderScope::Document
+    }
+}
+
+/// Manages `X-Permitted-Cross-Domain-Policies` header
+///
+/// The X-Permitted-Cross-Domain-Policies HTTP response header determines whether cross-domain policy files (crossdomain.xml and clientaccesspolicy.xml) will be ignored by Flash and Adobe Acrobat in subsequent requests.
+///
+/// # Values
+///
+/// - none: No policy file is allowed.
+/// - master-only: Only a master policy file, but no other policy files, is allowed.
+/// - by-content-type: A policy file is allowed if its MIME type matches the Content-Type of the requested resource.
+/// - by-ftp-filename: A policy file is allowed if its URL matches the URL of the requested resource.
+/// - all: Any policy file is allowed.
+///
+/// # Examples
+///
+/// ```
+/// use helmet_core::XPermittedCrossDomainPolicies;
+///
+/// let x_permitted_cross_domain_policies = XPermittedCrossDomainPolicies::none();
+///
+/// let x_permitted_cross_domain_policies = XPermittedCrossDomainPolicies::master_only();
+///
+/// let x_permitted_cross_domain_policies = XPermittedCrossDomainPolicies::by_content_type();
+///
+/// let x_permitted_cross_domain_policies = XPermittedCrossDomainPolicies::by_ftp_filename();
+///
+/// let x_permitted_cross_domain_policies = XPermittedCrossDomainPolicies::all();
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema),
+    serde(rename_all = "kebab-case"),
+    schemars(description = "`X-Permitted-Cross-Domain-Policies` header value")
+)]
+pub enum XPermittedCrossDomainPolicies {
+    None,
+    MasterOnly,
+    ByContentType,
+    ByFtpFilename,
+    All,
+}
+
+impl XPermittedCrossDomainPolicies {
+    pub fn none() -> Self {
+        Self::None
+    }
+
+    pub fn master_only() -> Self {
+        Self::MasterOnly
+    }
+
+    pub fn by_content_type() -> Self {
+        Self::ByContentType
+    }
+
+    pub fn by_ftp_filename() -> Self {
+        Self::ByFtpFilename
+    }
+
+    pub fn all() -> Self {
+        Self::All
+    }
+}
+
+impl Display for XPermittedCrossDomainPolicies {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            XPermittedCrossDomainPolicies::None => write!(f, "none"),
+            XPermittedCrossDomainPolicies::MasterOnly => write!(f, "master-only"),
+            XPermittedCrossDomainPolicies::ByContentType => write!(f, "by-content-type"),
+            XPermittedCrossDomainPolicies::ByFtpFilename => write!(f, "by-ftp-filename"),
+            XPermittedCrossDomainPolicies::All => write!(f, "all"),
+        }
+    }
+}
+
+impl Header for XPermittedCrossDomainPolicies {
+    fn name(&self) -> &'static str {
+        "X-Permitted-Cross-Domain-Policies"
+    }
+
+    fn value(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Manages `X-XSS-Protection` header
+///
+/// The HTTP X-XSS-Protection response header is a feature of Internet Explorer, Chrome and Safari that stops pages from loading when they detect reflected cross-site scripting (XSS) attacks. Although these protections are largely unnecessary in modern browsers when sites implement a strong Content-Security-Policy that disables the use of inline JavaScript ('unsafe-inline'), they can still provide protections for users of older web browsers that don't yet support CSP.
+///
+/// # Values
+///
+/// - 0: Disables XSS filtering.
+/// - 1: Enables XSS filtering (usually default in browsers).
+/// - 1; mode=block: Enables XSS filtering. Rather than sanitizing the page, the browser will prevent rendering of the page if an attack is detected.
+/// - 1; report=<reporting-URI>: Enables XSS filtering. If a cross-site scripting attack is detected, the browser will sanitize the page and report the violation. This uses the functionality of the CSP report-uri directive to send a report.
+///
+/// # Examples
+///
+/// ```
+/// use helmet_core::XXSSProtection;
+///
+/// let x_xss_protection = XXSSProtection::on();
+///
+/// let x_xss_protection = XXSSProtection::off();
+///
+/// let x_xss_protection = XXSSProtection::on().mode_block();
+///
+/// let x_xss_protection = XXSSProtection::on().report("https://example.com");
+///
+/// let x_xss_protection = XXSSProtection::on().mode_block().report("https://example.com");
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema),
+    serde(deny_unknown_fields),
+    schemars(description = "`X-XSS-Protection` header, `on: false` sends `0`")
+)]
+pub struct XXSSProtection {
+    on: bool,
+    #[cfg_attr(feature = "serde", serde(default))]
+    mode_block: bool,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, skip_serializing_if = "Option::is_none")
+    )]
+    report: Option<String>,
+}
+
+impl XXSSProtection {
+    /// Disables XSS filtering.
+    pub fn off() -> Self {
+        Self {
+            on: false,
+            mode_block: false,
+            report: None,
+        }
+    }
+
+    /// Enables XSS filtering (usually default in browsers).
+    pub fn on() -> Self {
+        Self {
+            on: true,
+            mode_block: false,
+            report: None,
+        }
+    }
+
+    /// Enables XSS filtering. Rather than sanitizing the page, the browser will prevent rendering of the page if an attack is detected.
+    pub fn mode_block(mut self) -> Self {
+        self.mode_block = true;
+        self
+    }
+
+    /// Enables XSS filtering. If a cross-site scripting attack is detected, the browser will sanitize the page and report the violation. This uses the functionality of the CSP report-uri directive to send a report.
+    pub fn report(mut self, report: &str) -> Self {
+        self.report = Some(report.to_string());
+        self
+    }
+
+    /// Like [`XXSSProtection::report`], but rejects URIs containing control characters, whitespace, `;` or `,`.
+    ///
+    /// ```
+    /// use helmet_core::XXSSProtection;
+    ///
+    /// assert!(XXSSProtection::on().try_report("https://example.com/report").is_ok());
+    /// assert!(XXSSProtection::on().try_report("https://example.com/report; mode=block").is_err());
+    /// ```
+    pub fn try_report(self, report: &str) -> Result<Self, HelmetError> {
+        validate_parameter("X-XSS-Protection", report)?;
+        Ok(self.report(report))
+    }
+}
+
+impl Display for XXSSProtection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.on {
+            write!(f, "1")?;
+            if self.mode_block {
+                write!(f, "; mode=block")?;
+            }
+            if let Some(report) = &self.report {
+                write!(f, "; report={}", report)?;
+            }
+        } else {
+            write!(f, "0")?;
+        }
+        Ok(())
+    }
+}
+
+impl Header for XXSSProtection {
+    fn name(&self) -> &'static str {
+        "X-XSS-Protection"
+    }
+
+    fn value(&self) -> String {
+        self.to_string()
+    }
+
+    fn scope(&self) -> HeaderScope {
+        HeaderScope::Document
+    }
+}
+
+/// Manages `X-Powered-By` header
+///
+/// ntex does not set `X-Powered-By` header by default.
+/// Instead of silencing the header, Helmet allows you to set it to a custom value.
+/// This can be useful against primitive fingerprinting.
+///
+/// # Examples
+///
+/// ```
+/// use helmet_core::XPoweredBy;
+///
+/// let x_powered_by = XPoweredBy::new("PHP 4.2.0");
+/// ```
+#[derive(Clone, Debug)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema),
+    schemars(description = "`X-Powered-By` header value")
+)]
+pub struct XPoweredBy(String);
+
+impl XPoweredBy {
+    /// Set the `X-Powered-By` header to a custom value.
+    pub fn new(comment: &str) -> Self {
+        Self(comment.to_string())
+    }
+
+    /// Like [`XPoweredBy::new`], but rejects values containing characters that are not allowed in HTTP header values, such as CR and LF.
+    ///
+    /// ```
+    /// use helmet_core::XPoweredBy;
+    ///
+    /// assert!(XPoweredBy::try_new("PHP 4.2.0").is_ok());
+    /// assert!(XPoweredBy::try_new("PHP\r\nSet-Cookie: a=b").is_err());
+    /// ```
+    pub fn try_new(comment: &str) -> Result<Self, HelmetError> {
+        if !is_valid_header_value(comment) {
+            return Err(HelmetError::InvalidHeaderValue {
+                name: "X-Powered-By".to_string(),
+                value: comment.to_string(),
+            });
+        }
+        Ok(Self::new(comment))
+    }
+}
+
+impl Display for XPoweredBy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)?;
+        Ok(())
+    }
+}
+
+impl Header for XPoweredBy {
+    fn name(&self) -> &'static str {
+        "X-Powered-By"
+    }
+
+    fn value(&self) -> String {
+        self.to_string()
+    }
+}
+
+/// Hash algorithm of a CSP hash source, e.g. `'sha256-...'`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum HashAlgorithm {
+    Sha256,
+    Sha384,
+    Sha512,
+}
+
+impl Display for HashAlgorithm {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HashAlgorithm::Sha256 => write!(f, "sha256"),
+            HashAlgorithm::Sha384 => write!(f, "sha384"),
+            HashAlgorithm::Sha512 => write!(f, "sha512"),
+        }
+    }
+}
+
+/// Error returned when a typed CSP [`Source`] is not valid
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SourceError {
+    source: String,
+    reason: &'static str,
+}
+
+impl SourceError {
+    fn new(source: &str, reason: &'static str) -> Self {
+        Self {
+            source: source.to_string(),
+            reason,
+        }
+    }
+}
+
+impl Display for SourceError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "invalid CSP source `{}`: {}", self.source, self.reason)
+    }
+}
+
+impl std::error::Error for SourceError {}
+
+/// Source expression of a CSP source list, such as `script-src` or `img-src`
+///
+/// Renders with the quoting the CSP grammar requires, so keywords can't accidentally be written as host names (e.g. `self` instead of `'self'`).
+///
+/// Every source list directive accepts `Source` values as well as plain strings. Plain strings are classified into the matching variant, but are otherwise not validated. Use the fallible constructors, such as [`Source::host`], to validate values coming from configuration.
+///
+/// # Examples
+///
+/// ```
+/// use helmet_core::{ContentSecurityPolicy, HashAlgorithm, Source};
+///
+/// let content_security_policy = ContentSecurityPolicy::new()
+///     .default_src(vec![Source::Self_])
+///     .script_src(vec![
+///         Source::Self_,
+///         Source::StrictDynamic,
+///         Source::hash(HashAlgorithm::Sha256, "B2yPHKaXnvFWtRChIbabYmUBFZdVfKKXHbWtWidDVF8=").unwrap(),
+///     ])
+///     .img_src(vec![
+///         Source::Self_,
+///         Source::scheme("data").unwrap(),
+///         Source::host("https://*.example.com:443/images/").unwrap(),
+///     ]);
+///
+/// assert_eq!(
+///     content_security_policy.to_string(),
+///     "default-src 'self'; script-src 'self' 'strict-dynamic' 'sha256-B2yPHKaXnvFWtRChIbabYmUBFZdVfKKXHbWtWidDVF8='; img-src 'self' data: https://*.example.com:443/images/"
+/// );
+///
+/// assert!(Source::host("'none' https:").is_err());
+/// assert_eq!(Source::from("'self'"), Source::Self_);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Source<'a> {
+    /// `'self'`: the origin the document was served from.
+    Self_,
+    /// `'none'`: no sources are allowed. Must be the only source in the list.
+    None,
+    /// `'unsafe-inline'`: allows inline scripts, styles and event handlers.
+    UnsafeInline,
+    /// `'unsafe-eval'`: allows `eval()` and similar constructs.
+    UnsafeEval,
+    /// `'unsafe-hashes'`: allows hashes to match inline event handlers and `style` attributes.
+    UnsafeHashes,
+    /// `'wasm-unsafe-eval'`: allows compiling and instantiating WebAssembly.
+    WasmUnsafeEval,
+    /// `'strict-dynamic'`: extends trust to scripts loaded by already trusted scripts.
+    StrictDynamic,
+    /// `'report-sample'`: includes a sample of the violating code in reports.
+    ReportSample,
+    /// `'nonce-<base64>'`: allows elements with a matching `nonce` attribute.
+    Nonce(Cow<'a, str>),
+    /// `'<algorithm>-<base64>'`: allows inline elements with a matching hash.
+    Hash(HashAlgorithm, Cow<'a, str>),
+    /// `<scheme>:`, e.g. `https:` or `data:`. Stored without the trailing colon.
+    Scheme(Cow<'a, str>),
+    /// Host source, e.g. `https://*.example.com:443/path`.
+    Host(Cow<'a, str>),
+}
+
+impl<'a> Source<'a> {
+    /// `'nonce-<base64>'` source, validating that `value` is base64.
+    pub fn nonce(value: impl Into<Cow<'a, str>>) -> Result<Self, SourceError> {
+        let value = value.into();
+        if !is_base64_value(&value) {
+            return Err(SourceError::new(&value, "nonce must be base64 encoded"));
+        }
+        Ok(Self::Nonce(value))
+    }
+
+    /// `'<algorithm>-<base64>'` source, validating that `digest` is base64.
+    pub fn hash(
+        algorithm: HashAlgorithm,
+        digest: impl Into<Cow<'a, str>>,
+    ) -> Result<Self, SourceError> {
+        let digest = digest.into();
+        if !is_base64_value(&digest) {
+            return Err(SourceError::new(
+                &digest,
+                "hash digest must be base64 encoded",
+            ));
+        }
+        Ok(Self::Hash(algorithm, digest))
+    }
+
+    /// `<scheme>:` source. The trailing colon is optional.
+    pub fn scheme(scheme: impl Into<Cow<'a, str>>) -> Result<Self, SourceError> {
+        let scheme = scheme.into();
+        let name = scheme.strip_suffix(':').unwrap_or(&scheme);
+        if !is_scheme(name) {
+            return Err(SourceError::new(&scheme, "not a valid URL scheme"));
+        }
+        let len = name.len();
+        Ok(Self::Scheme(slice_cow(&scheme, 0, len)))
+    }
+
+    /// Host source, with optional scheme, port and path, e.g. `https://*.example.com:8443/path`.
+    ///
+    /// The host may be `*`, or start with a `*.` wildcard label. The port may be a number or `*`.
+    pub fn host(host: impl Into<Cow<'a, str>>) -> Result<Self, SourceError> {
+        let host = host.into();
+        validate_host_source(&host).map_err(|reason| SourceError::new(&host, reason))?;
+        Ok(Self::Host(host))
+    }
+
+    /// Convert into a `Source` that owns its data, so it no longer borrows from the input.
+    pub fn into_owned(self) -> Source<'static> {
+        match self {
+            Source::Self_ => Source::Self_,
+            Source::None => Source::None,
+            Source::UnsafeInline => Source::UnsafeInline,
+            Source::UnsafeEval => Source::UnsafeEval,
+            Source::UnsafeHashes => Source::UnsafeHashes,
+            Source::WasmUnsafeEval => Source::WasmUnsafeEval,
+            Source::StrictDynamic => Source::StrictDynamic,
+            Source::ReportSample => Source::ReportSample,
+            Source::Nonce(value) => Source::Nonce(Cow::Owned(value.into_owned())),
+            Source::Hash(algorithm, digest) => {
+                Source::Hash(algorithm, Cow::Owned(digest.into_owned()))
+            }
+            Source::Scheme(scheme) => Source::Scheme(Cow::Owned(scheme.into_owned())),
+            Source::Host(host) => Source::Host(Cow::Owned(host.into_owned())),
+        }
+    }
+
+    // Keywords are matched case-insensitively. Anything that isn't a keyword, nonce, hash or scheme is treated as a host source, without validation.
+    fn classify(value: Cow<'a, str>) -> Self {
+        let lower = value.to_ascii_lowercase();
+        match lower.as_str() {
+            "'self'" => return Self::Self_,
+            "'none'" => return Self::None,
+            "'unsafe-inline'" => return Self::UnsafeInline,
+            "'unsafe-eval'" => return Self::UnsafeEval,
+            "'unsafe-hashes'" => return Self::UnsafeHashes,
+            "'wasm-unsafe-eval'" => return Self::WasmUnsafeEval,
+            "'strict-dynamic'" => return Self::StrictDynamic,
+            "'report-sample'" => return Self::ReportSample,
+            _ => {}
+        }
+
+        if value.len() > 2 && value.starts_with('\'') && value.ends_with('\'') {
+            let end = value.len() - 1;
+            if lower.starts_with("'nonce-") {
+                return Self::Nonce(slice_cow(&value, "'nonce-".len(), end));
+            }
+            for algorithm in [
+                HashAlgorithm::Sha256,
+                HashAlgorithm::Sha384,
+                HashAlgorithm::Sha512,
+            ] {
+                let prefix = format!("'{}-", algorithm);
+                if lower.starts_with(&prefix) {
+                    return Self::Hash(algorithm, slice_cow(&value, prefix.len(), end));
+                }
+            }
+        }
+
+        if let Some(scheme) = value.strip_suffix(':') {
+            if is_scheme(scheme) {
+                return Self::Scheme(slice_cow(&value, 0, scheme.len()));
+            }
+        }
+
+        Self::Host(value)
+    }
+
+    /// Classify and validate a source expression written as a string
+    ///
+    /// Unlike the `From<&str>` conversion, unknown keywords, malformed nonces and hashes, and anything that would break the CSP grammar, such as whitespace, `;` or `,`, are rejected.
+    ///
+    /// ```
+    /// use helmet_core::Source;
+    ///
+    /// assert_eq!(Source::try_new("'self'"), Ok(Source::Self_));
+    /// assert!(Source::try_new("https://example.com").is_ok());
+    /// assert!(Source::try_new("'slef'").is_err());
+    /// assert!(Source::try_new("self").is_err());
+    /// assert!(Source::try_new("https://example.com; script-src *").is_err());
+    /// ```
+    pub fn try_new(value: impl Into<Cow<'a, str>>) -> Result<Self, SourceError> {
+        let source = Self::classify(value.into());
+        source.validate()?;
+        Ok(source)
+    }
+
+    /// Check that the source is valid, e.g. one created from a plain string.
+    pub fn validate(&self) -> Result<(), SourceError> {
+        let reason = match self {
+            Source::Nonce(value) | Source::Hash(_, value) if !is_base64_value(value) => {
+                "nonce or hash value must be base64 encoded"
+            }
+            Source::Scheme(scheme) if !is_scheme(scheme) => "not a valid URL scheme",
+            Source::Host(host) if host.starts_with('\'') => "unknown keyword",
+            Source::Host(host) => match validate_host_source(host) {
+                Ok(()) => return Ok(()),
+                Err(reason) => reason,
+            },
+            _ => return Ok(()),
+        };
+        Err(SourceError::new(&self.to_string(), reason))
+    }
+}
+
+impl<'a> From<&'a str> for Source<'a> {
+    /// Classify a source expression written as a string.
+    ///
+    /// Keywords are matched case-insensitively. Anything that isn't a keyword, nonce, hash or scheme is treated as a host source, without validation.
+    fn from(value: &'a str) -> Self {
+        Self::classify(Cow::Borrowed(value))
+    }
+}
+
+impl<'a> From<String> for Source<'a> {
+    /// Classify a source expression written as a string, e.g. one loaded from configuration.
+    ///
+    /// See the `From<&str>` implementation for details.
+    fn from(value: String) -> Self {
+        Self::classify(Cow::Owned(value))
+    }
+}
+
+impl<'a> From<Cow<'a, str>> for Source<'a> {
+    fn from(value: Cow<'a, str>) -> Self {
+        Self::classify(value)
+    }
+}
+
+impl<'a> From<&'a Nonce> for Source<'a> {
+    fn from(nonce: &'a Nonce) -> Self {
+        Self::Nonce(Cow::Borrowed(nonce.as_str()))
+    }
+}
+
+impl Display for Source<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Source::Self_ => write!(f, "'self'"),
+            Source::None => write!(f, "'none'"),
+            Source::UnsafeInline => write!(f, "'unsafe-inline'"),
+            Source::UnsafeEval => write!(f, "'unsafe-eval'"),
+            Source::UnsafeHashes => write!(f, "'unsafe-hashes'"),
+            Source::WasmUnsafeEval => write!(f, "'wasm-unsafe-eval'"),
+            Source::StrictDynamic => write!(f, "'strict-dynamic'"),
+            Source::ReportSample => write!(f, "'report-sample'"),
+            Source::Nonce(value) => write!(f, "'nonce-{}'", value),
+            Source::Hash(algorithm, digest) => write!(f, "'{}-{}'", algorithm, digest),
+            Source::Scheme(scheme) => write!(f, "{}:", scheme),
+            Source::Host(host) => write!(f, "{}", host),
+        }
+    }
+}
+
+fn slice_cow<'a>(value: &Cow<'a, str>, start: usize, end: usize) -> Cow<'a, str> {
+    match value {
+        Cow::Borrowed(value) => Cow::Borrowed(&value[start..end]),
+        Cow::Owned(value) => Cow::Owned(value[start..end].to_string()),
+    }
+}
+
+// Values of directives that don't take source lists, e.g. sandbox flags or trusted type policy names.
+fn validate_word(word: &str) -> Result<(), SourceError> {
+    if word.is_empty()
+        || !word
+            .bytes()
+            .all(|b| (0x21..=0x7e).contains(&b) && b != b';' && b != b',')
+    {
+        return Err(SourceError::new(
+            word,
+            "contains characters that are not allowed in a CSP directive",
+        ));
+    }
+    Ok(())
+}
+
+// base64-value = 1*( ALPHA / DIGIT / "+" / "/" / "-" / "_" )*2( "=" )
+fn is_base64_value(value: &str) -> bool {
+    let data = value.trim_end_matches('=');
+    value.len() - data.len() <= 2
+        && !data.is_empty()
+        && data
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'/' | b'-' | b'_'))
+}
+
+// scheme = ALPHA *( ALPHA / DIGIT / "+" / "-" / "." )
+fn is_scheme(value: &str) -> bool {
+    let mut bytes = value.bytes();
+    matches!(bytes.next(), Some(b) if b.is_ascii_alphabetic())
+        && bytes.all(|b| b.is_ascii_alphanumeric() || matches!(b, b'+' | b'-' | b'.'))
+}
+
+// Keywords that are host names unless quoted, e.g. `self` instead of `'self'`.
+const KEYWORDS: [&str; 8] = [
+    "self",
+    "none",
+    "unsafe-inline",
+    "unsafe-eval",
+    "unsafe-hashes",
+    "wasm-unsafe-eval",
+    "strict-dynamic",
+    "report-sample",
+];
+
+// host-source = [ scheme-part "://" ] host-part [ ":" port-part ] [ path-part ]
+fn validate_host_source(value: &str) -> Result<(), &'static str> {
+    if KEYWORDS
+        .iter()
+        .any(|keyword| value.eq_ignore_ascii_case(keyword))
+    {
+        return Err("missing quotes around keyword");
+    }
+
+    let rest = match value.split_once("://") {
+        Some((scheme, rest)) => {
+            if !is_scheme(scheme) {
+                return Err("not a valid URL scheme");
+            }
+            rest
+        }
+        None => value,
+    };
+
+    let (authority, path) = match rest.find('/') {
+        Some(index) => rest.split_at(index),
+        None => (rest, ""),
+    };
+
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => (host, Some(port)),
+        None => (authority, None),
+    };
+
+    if host != "*" {
+        let labels = host.strip_prefix("*.").unwrap_or(host);
+        let labels = labels.strip_suffix('.').unwrap_or(labels);
+        if labels.is_empty()
+            || !labels.split('.').all(|label| {
+                !label.is_empty()
+                    && label
+                        .bytes()
+                        .all(|b| b.is_ascii_alphanumeric() || b == b'-')
+            })
+        {
+            return Err("not a valid host, wildcards are only allowed as the first label");
+        }
+    }
+
+    if let Some(port) = port {
+        if port != "*" && (port.is_empty() || !port.bytes().all(|b| b.is_ascii_digit())) {
+            return Err("port must be a number or `*`");
+        }
+    }
+
+    if path.bytes().any(|b| {
+        matches!(b, b';' | b',' | b'\'' | b'"')
+            || b.is_ascii_whitespace()
+            || b.is_ascii_control()
+            || !b.is_ascii()
+    }) {
+        return Err("path contains characters that are not allowed in a CSP source");
+    }
+
+    Ok(())
+}
+
+/// Manages `Content-Security-Policy` header
+///
+/// The HTTP Content-Security-Policy response header allows web site administrators to control resources the user agent is allowed to load for a given page. With a few exceptions, policies mostly involve specifying server origins and script endpoints. This helps guard against cross-site scripting attacks (XSS).
+///
+/// # Directives
+///
+/// - child-src: Defines valid sources for web workers and nested browsing contexts loaded using elements such as `<frame>` and `<iframe>`.
+/// - connect-src: Applies to XMLHttpRequest (AJAX), WebSocket or EventSource. If not allowed the browser emulates a 400 HTTP status code.
+/// - default-src: The default-src is the default policy for loading content such as JavaScript, Images, CSS, Font's, AJAX requests, Frames, HTML5 Media. See the list of directives to see which values are allowed as default.
+/// - font-src: Defines valid sources for fonts loaded using @font-face.
+/// - frame-src: Defines valid sources for nested browsing contexts loading using elements such as `<frame>` and `<iframe>`.
+/// - img-src: Defines valid sources of images and favicons.
+/// - manifest-src: Specifies which manifest can be applied to the resource.
+/// - media-src: Defines valid sources for loading media using the `<audio>` and `<video>` elements.
+/// - object-src: Defines valid sources for the `<object>`, `<embed>`, and `<applet>` elements.
+/// - prefetch-src: Specifies which referrer to use when fetching the resource.
+/// - script-src: Defines valid sources for JavaScript.
+/// - script-src-elem: Defines valid sources for JavaScript inline event handlers.
+/// - script-src-attr: Defines valid sources for JavaScript inline event handlers.
+/// - style-src: Defines valid sources for stylesheets.
+/// - style-src-elem: Defines valid sources for stylesheets inline event handlers.
+/// - style-src-attr: Defines valid sources for stylesheets inline event handlers.
+/// - worker-src: Defines valid sources for Worker, SharedWorker, or ServiceWorker scripts.
+/// - base-uri: Restricts the URLs which can be used in a document's `<base>` element.
+/// - sandbox: Enables a sandbox for the requested resource similar to the iframe sandbox attribute. The sandbox applies a same origin policy, prevents popups, plugins and script execution is blocked. You can keep the sandbox value empty to keep all restrictions in place, or add values: allow-forms allow-same-origin allow-scripts allow-popups, allow-modals, allow-orientation-lock, allow-pointer-lock, allow-presentation, allow-popups-to-escape-sandbox, allow-top-navigation, allow-top-navigation-by-user-activation.
+/// - form-action: Restricts the URLs which can be used as the target of a form submissions from a given context.
+/// - frame-ancestors: Specifies valid parents that may embed a page using `<frame>`, `<iframe>`, `<object>`, `<embed>`, or `<applet>`.
+/// - report-to: Enables reporting of violations.
+/// - require-trusted-types-for: Specifies which trusted types are required by a resource.
+/// - trusted-types: Specifies which trusted types are defined by a resource.
+/// - upgrade-insecure-requests: Block HTTP requests on insecure elements.
+///
+/// # Examples
+///
+/// ```
+/// use helmet_core::ContentSecurityPolicy;
+///
+/// let content_security_policy = ContentSecurityPolicy::default()
+///    .child_src(vec!["'self'", "https://youtube.com"])
+///    .connect_src(vec!["'self'", "https://youtube.com"])
+///    .default_src(vec!["'self'", "https://youtube.com"])
+///    .font_src(vec!["'self'", "https://youtube.com"]);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ContentSecurityPolicyDirective<'a> {
+    /// Warning: Instead of child-src, if you want to regulate nested browsing contexts and workers, you should use the frame-src and worker-src directives, respectively.
+    ChildSrc(Vec<Source<'a>>),
+    /// Applies to XMLHttpRequest (AJAX), WebSocket or EventSource. If not allowed the browser emulates a 400 HTTP status code.
+    ConnectSrc(Vec<Source<'a>>),
+    /// The default-src is the default policy for loading content such as JavaScript, Images, CSS, Font's, AJAX requests, Frames, HTML5 Media. See the list of directives to see which values are allowed as default.
+    DefaultSrc(Vec<Source<'a>>),
+    /// Defines valid sources for fonts loaded using @font-face.
+    FontSrc(Vec<Source<'a>>),
+    /// Defines valid sources for nested browsing contexts loading using elements such as `<frame>` and `<iframe>`.
+    FrameSrc(Vec<Source<'a>>),
+    /// Defines valid sources of images and favicons.
+    ImgSrc(Vec<Source<'a>>),
+    /// Specifies which manifest can be applied to the resource.
+    ManifestSrc(Vec<Source<'a>>),
+    /// Defines valid sources for loading media using the `<audio>` and `<video>` elements.
+    MediaSrc(Vec<Source<'a>>),
+    /// Defines valid sources for the `<object>`, `<embed>`, and `<applet>` elements.
+    ObjectSrc(Vec<Source<'a>>),
+    /// Specifies which referrer to use when fetching the resource.
+    PrefetchSrc(Vec<Source<'a>>),
+    /// Defines valid sources for JavaScript.
+    ScriptSrc(Vec<Source<'a>>),
+    /// Defines valid sources for JavaScript inline event handlers.
+    ScriptSrcElem(Vec<Source<'a>>),
+    /// Defines valid sources for JavaScript inline event handlers.
+    ScriptSrcAttr(Vec<Source<'a>>),
+    /// Defines valid sources for stylesheets.
+    StyleSrc(Vec<Source<'a>>),
+    /// Defines valid sources for stylesheets inline event handlers.
+    StyleSrcElem(Vec<Source<'a>>),
+    /// Defines valid sources for stylesheets inline event handlers.
+    StyleSrcAttr(Vec<Source<'a>>),
+    /// Defines valid sources for Worker, SharedWorker, or ServiceWorker scripts.
+    WorkerSrc(Vec<Source<'a>>),
+    // Document directives
+    /// Restricts the URLs which can be used in a document's `<base>` element.
+    BaseUri(Vec<Source<'a>>),
+    /// Enables a sandbox for the requested resource similar to the iframe sandbox attribute. The sandbox applies a same origin policy, prevents popups, plugins and script execution is blocked. You can keep the sandbox value empty to keep all restrictions in place, or add values: allow-forms allow-same-origin allow-scripts allow-popups, allow-modals, allow-orientation-lock, allow-pointer-lock, allow-presentation, allow-popups-to-escape-sandbox, allow-top-navigation, allow-top-navigation-by-user-activation.
+    Sandbox(Vec<Cow<'a, str>>),
+    // Navigation directives
+    /// Restricts the URLs which can be used as the target of a form submissions from a given context.
+    FormAction(Vec<Source<'a>>),
+    /// Specifies valid parents that may embed a page using `<frame>`, `<iframe>`, `<object>`, `<embed>`, or `<applet>`.
+    FrameAncestors(Vec<Source<'a>>),
+    // Reporting directives
+    /// Names the reporting endpoint violations are sent to, as registered in the `Reporting-Endpoints` header.
+    ReportTo(Cow<'a, str>),
+    /// URLs violation reports are sent to.
+    ///
+    /// report-uri is deprecated in favour of report-to, however, it is still the only reporting directive supported by some browsers. Browsers that support report-to ignore report-uri when both are set.
+    ReportUri(Vec<Cow<'a, str>>),
+    // Other
+    /// Specifies which trusted types are required by a resource.
+    RequireTrustedTypesFor(Vec<Cow<'a, str>>),
+    /// Specifies which trusted types are defined by a resource.
+    TrustedTypes(Vec<Cow<'a, str>>),
+    /// Block HTTP requests on insecure elements.
+    UpgradeInsecureRequests,
+    /// Any directive not known to Helmet, with its name and values preserved as is.
+    Other(Cow<'a, str>, Vec<Cow<'a, str>>),
+}
+
+impl<'a> ContentSecurityPolicyDirective<'a> {
+    /// child-src: Defines valid sources for web workers and nested browsing contexts loaded using elements such as `<frame>`` and `<iframe>`.
+    pub fn child_src<S: Into<Source<'a>>>(values: Vec<S>) -> Self {
+        Self::ChildSrc(values.into_iter().map(Into::into).collect())
+    }
+
+    /// connect-src: Applies to XMLHttpRequest (AJAX), WebSocket or EventSource. If not allowed the browser emulates a 400 HTTP status code.
+    pub fn connect_src<S: Into<Source<'a>>>(values: Vec<S>) -> Self {
+        Self::ConnectSrc(values.into_iter().map(Into::into).collect())
+    }
+
+    /// default-src: The default-src is the default policy for loading content such as JavaScript, Images, CSS, Font's, AJAX requests, Frames, HTML5 Media. See the list of directives to see which values are allowed as default.
+    pub fn default_src<S: Into<Source<'a>>>(values: Vec<S>) -> Self {
+        Self::DefaultSrc(values.into_iter().map(Into::into).collect())
+    }
+
+    /// font-src: Defines valid sources for fonts loaded using @font-face.
+    pub fn font_src<S: Into<Source<'a>>>(values: Vec<S>) -> Self {
+        Self::FontSrc(values.into_iter().map(Into::into).collect())
+    }
+
+    /// frame-src: Defines valid sources for nested browsing contexts loading using elements such as `<frame>` and `<iframe>`.
+    pub fn frame_src<S: Into<Source<'a>>>(values: Vec<S>) -> Self {
+        Self::FrameSrc(values.into_iter().map(Into::into).collect())
+    }
+
+    /// img-src: Defines valid sources of images and favicons.
+    pub fn img_src<S: Into<Source<'a>>>(values: Vec<S>) -> Self {
+        Self::ImgSrc(values.into_iter().map(Into::into).collect())
+    }
+
+    /// manifest-src: Specifies which manifest can be applied to the resource.
+    pub fn manifest_src<S: Into<Source<'a>>>(values: Vec<S>) -> Self {
+        Self::ManifestSrc(values.into_iter().map(Into::into).collect())
+    }
+
+    /// media-src: Defines valid sources for loading media using the `<audio>` and `<video>` elements.
+    pub fn media_src<S: Into<Source<'a>>>(values: Vec<S>) -> Self {
+        Self::MediaSrc(values.into_iter().map(Into::into).collect())
+    }
+
+    /// object-src: Defines valid sources for the `<object>`, `<embed>`, and `<applet>` elements.
+    pub fn object_src<S: Into<Source<'a>>>(values: Vec<S>) -> Self {
+        Self::ObjectSrc(values.into_iter().map(Into::into).collect())
+    }
+
+    /// prefetch-src: Specifies which referrer to use when fetching the resource.
+    pub fn prefetch_src<S: Into<Source<'a>>>(values: Vec<S>) -> Self {
+        Self::PrefetchSrc(values.into_iter().map(Into::into).collect())
+    }
+
+    /// script-src: Defines valid sources for JavaScript.
+    pub fn script_src<S: Into<Source<'a>>>(values: Vec<S>) -> Self {
+        Self::ScriptSrc(values.into_iter().map(Into::into).collect())
+    }
+
+    /// script-src-elem: Defines valid sources for JavaScript inline event handlers.
+    pub fn script_src_elem<S: Into<Source<'a>>>(values: Vec<S>) -> Self {
+        Self::ScriptSrcElem(values.into_iter().map(Into::into).collect())
+    }
+
+    /// script-src-attr: Defines valid sources for JavaScript inline event handlers.
+    pub fn script_src_attr<S: Into<Source<'a>>>(values: Vec<S>) -> Self {
+        Self::ScriptSrcAttr(values.into_iter().map(Into::into).collect())
+    }
+
+    /// style-src: Defines valid sources for stylesheets.
+    pub fn style_src<S: Into<Source<'a>>>(values: Vec<S>) -> Self {
+        Self::StyleSrc(values.into_iter().map(Into::into).collect())
+    }
+
+    /// style-src-elem: Defines valid sources for stylesheets inline event handlers.
+    pub fn style_src_elem<S: Into<Source<'a>>>(values: Vec<S>) -> Self {
+        Self::StyleSrcElem(values.into_iter().map(Into::into).collect())
+    }
+
+    /// style-src-attr: Defines valid sources for stylesheets inline event handlers.
+    pub fn style_src_attr<S: Into<Source<'a>>>(values: Vec<S>) -> Self {
+        Self::StyleSrcAttr(values.into_iter().map(Into::into).collect())
+    }
+
+    /// worker-src: Defines valid sources for Worker, SharedWorker, or ServiceWorker scripts.
+    pub fn worker_src<S: Into<Source<'a>>>(values: Vec<S>) -> Self {
+        Self::WorkerSrc(values.into_iter().map(Into::into).collect())
+    }
+
+    /// base-uri: Restricts the URLs which can be used in a document's `<base>` element.
+    pub fn base_uri<S: Into<Source<'a>>>(values: Vec<S>) -> Self {
+        Self::BaseUri(values.into_iter().map(Into::into).collect())
+    }
+
+    /// sandbox: Enables a sandbox for the requested resource similar to the iframe sandbox attribute. The sandbox applies a same origin policy, prevents popups, plugins and script execution is blocked. You can keep the sandbox value empty to keep all restrictions in place, or add values: allow-forms allow-same-origin allow-scripts allow-popups, allow-modals, allow-orientation-lock, allow-pointer-lock, allow-presentation, allow-popups-to-escape-sandbox, allow-top-navigation, allow-top-navigation-by-user-activation.
+    pub fn sandbox<S: Into<Cow<'a, str>>>(values: Vec<S>) -> Self {
+        Self::Sandbox(values.into_iter().map(Into::into).collect())
+    }
+
+    /// form-action: Restricts the URLs which can be used as the target of a form submissions from a given context.
+    pub fn form_action<S: Into<Source<'a>>>(values: Vec<S>) -> Self {
+        Self::FormAction(values.into_iter().map(Into::into).collect())
+    }
+
+    /// frame-ancestors: Specifies valid parents that may embed a page using `<frame>`, `<iframe>`, `<object>`, `<embed>`, or `<applet>`.
+    pub fn frame_ancestors<S: Into<Source<'a>>>(values: Vec<S>) -> Self {
+        Self::FrameAncestors(values.into_iter().map(Into::into).collect())
+    }
+
+    /// report-to: Names the reporting endpoint violations are sent to.
+    pub fn report_to<S: Into<Cow<'a, str>>>(endpoint: S) -> Self {
+        Self::ReportTo(endpoint.into())
+    }
+
+    /// report-uri: URLs violation reports are sent to.
+    pub fn report_uri<S: Into<Cow<'a, str>>>(values: Vec<S>) -> Self {
+        Self::ReportUri(values.into_iter().map(Into::into).collect())
+    }
+
+    /// require-trusted-types-for: Specifies which trusted types are required by a resource.
+    pub fn require_trusted_types_for<S: Into<Cow<'a, str>>>(values: Vec<S>) -> Self {
+        Self::RequireTrustedTypesFor(values.into_iter().map(Into::into).collect())
+    }
+
+    /// trusted-types: Specifies which trusted types are defined by a resource.
+    pub fn trusted_types<S: Into<Cow<'a, str>>>(values: Vec<S>) -> Self {
+        Self::TrustedTypes(values.into_iter().map(Into::into).collect())
+    }
+
+    /// Block HTTP requests on insecure elements.
+    pub fn upgrade_insecure_requests() -> Self {
+        Self::UpgradeInsecureRequests
+    }
+
+    /// Check that every value of the directive is valid, e.g. ones created from plain strings.
+    pub fn validate(&self) -> Result<(), SourceError> {
+        match self {
+            Self::ChildSrc(values)
+            | Self::ConnectSrc(values)
+            | Self::DefaultSrc(values)
+            | Self::FontSrc(values)
+            | Self::FrameSrc(values)
+            | Self::ImgSrc(values)
+            | Self::ManifestSrc(values)
+            | Self::MediaSrc(values)
+            | Self::ObjectSrc(values)
+            | Self::PrefetchSrc(values)
+            | Self::ScriptSrc(values)
+            | Self::ScriptSrcElem(values)
+            | Self::ScriptSrcAttr(values)
+            | Self::StyleSrc(values)
+            | Self::StyleSrcElem(values)
+            | Self::StyleSrcAttr(values)
+            | Self::WorkerSrc(values)
+            | Self::BaseUri(values)
+            | Self::FormAction(values)
+            | Self::FrameAncestors(values) => values.iter().try_for_each(Source::validate),
+            Self::ReportTo(endpoint) => validate_word(endpoint),
+            Self::Sandbox(values)
+            | Self::ReportUri(values)
+            | Self::RequireTrustedTypesFor(values)
+            | Self::TrustedTypes(values) => {
+                values.iter().try_for_each(|value| validate_word(value))
+            }
+            Self::UpgradeInsecureRequests => Ok(()),
+            Self::Other(name, values) => {
+                if name.is_empty() || !name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-')
+                {
+                    return Err(SourceError::new(name, "not a valid directive name"));
+                }
+                values.iter().try_for_each(|value| validate_word(value))
+            }
+        }
+    }
+
+    /// The directive name, e.g. `script-src`.
+    ///
+    /// ```
+    /// use helmet_core::ContentSecurityPolicyDirective;
+    ///
+    /// assert_eq!(ContentSecurityPolicyDirective::script_src(vec!["'self'"]).name(), "script-src");
+    /// ```
+    pub fn name(&self) -> &str {
+        match self {
+            Self::ChildSrc(_) => "child-src",
+            Self::ConnectSrc(_) => "connect-src",
+            Self::DefaultSrc(_) => "default-src",
+            Self::FontSrc(_) => "font-src",
+            Self::FrameSrc(_) => "frame-src",
+            Self::ImgSrc(_) => "img-src",
+            Self::ManifestSrc(_) => "manifest-src",
+            Self::MediaSrc(_) => "media-src",
+            Self::ObjectSrc(_) => "object-src",
+            Self::PrefetchSrc(_) => "prefetch-src",
+            Self::ScriptSrc(_) => "script-src",
+            Self::ScriptSrcElem(_) => "script-src-elem",
+            Self::ScriptSrcAttr(_) => "script-src-attr",
+            Self::StyleSrc(_) => "style-src",
+            Self::StyleSrcElem(_) => "style-src-elem",
+            Self::StyleSrcAttr(_) => "style-src-attr",
+            Self::WorkerSrc(_) => "worker-src",
+            Self::BaseUri(_) => "base-uri",
+            Self::Sandbox(_) => "sandbox",
+            Self::FormAction(_) => "form-action",
+            Self::FrameAncestors(_) => "frame-ancestors",
+            Self::ReportTo(_) => "report-to",
+            Self::ReportUri(_) => "report-uri",
+            Self::RequireTrustedTypesFor(_) => "require-trusted-types-for",
+            Self::TrustedTypes(_) => "trusted-types",
+            Self::UpgradeInsecureRequests => "upgrade-insecure-requests",
+            Self::Other(name, _) => name,
+        }
+    }
+
+    // Values as written in the header, without the directive name.
+    #[cfg(feature = "serde")]
+    fn values(&self) -> Vec<String> {
+        let rendered = self.to_string();
+        rendered[self.name().len()..]
+            .split_whitespace()
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn sources_mut(&mut self) -> Option<&mut Vec<Source<'a>>> {
+        match self {
+            Self::ChildSrc(values)
+            | Self::ConnectSrc(values)
+            | Self::DefaultSrc(values)
+            | Self::FontSrc(values)
+            | Self::FrameSrc(values)
+            | Self::ImgSrc(values)
+            | Self::ManifestSrc(values)
+            | Self::MediaSrc(values)
+            | Self::ObjectSrc(values)
+            | Self::PrefetchSrc(values)
+            | Self::ScriptSrc(values)
+            | Self::ScriptSrcElem(values)
+            | Self::ScriptSrcAttr(values)
+            | Self::StyleSrc(values)
+            | Self::StyleSrcElem(values)
+            | Self::StyleSrcAttr(values)
+            | Self::WorkerSrc(values)
+            | Self::BaseUri(values)
+            | Self::FormAction(values)
+            | Self::FrameAncestors(values) => Some(values),
+            _ => None,
+        }
+    }
+
+    fn values_mut(&mut self) -> Option<&mut Vec<Cow<'a, str>>> {
+        match self {
+            Self::Sandbox(values)
+            | Self::ReportUri(values)
+            | Self::RequireTrustedTypesFor(values)
+            | Self::TrustedTypes(values)
+            | Self::Other(_, values) => Some(values),
+            _ => None,
+        }
+    }
+
+    // `report-to` and `report-uri` only tell browsers where to send reports, they don't restrict anything.
+    fn is_reporting(&self) -> bool {
+        matches!(self, Self::ReportTo(_) | Self::ReportUri(_))
+    }
+
+    // Adds the values of a directive with the same name. `'none'` is dropped once other sources are allowed, single valued directives take the value of `other`.
+    fn union(&mut self, mut other: Self) {
+        if let (Some(sources), Some(other_sources)) = (self.sources_mut(), other.sources_mut()) {
+            for source in other_sources.drain(..) {
+                if !sources.contains(&source) {
+                    sources.push(source);
+                }
+            }
+            if sources.len() > 1 {
+                sources.retain(|source| *source != Source::None);
+            }
+            return;
+        }
+        if let (Some(values), Some(other_values)) = (self.values_mut(), other.values_mut()) {
+            for value in other_values.drain(..) {
+                if !values.contains(&value) {
+                    values.push(value);
+                }
+            }
+            return;
+        }
+        *self = other;
+    }
+
+    /// Convert into a directive that owns its data, so it no longer borrows from the input.
+    pub fn into_owned(self) -> ContentSecurityPolicyDirective<'static> {
+        fn owned_sources(values: Vec<Source<'_>>) -> Vec<Source<'static>> {
+            values.into_iter().map(Source::into_owned).collect()
+        }
+
+        fn owned_words(values: Vec<Cow<'_, str>>) -> Vec<Cow<'static, str>> {
+            values
+                .into_iter()
+                .map(|value| Cow::Owned(value.into_owned()))
+                .collect()
+        }
+
+        match self {
+            Self::ChildSrc(values) => {
+                ContentSecurityPolicyDirective::ChildSrc(owned_sources(values))
+            }
+            Self::ConnectSrc(values) => {
+                ContentSecurityPolicyDirective::ConnectSrc(owned_sources(values))
+            }
+            Self::DefaultSrc(values) => {
+                ContentSecurityPolicyDirective::DefaultSrc(owned_sources(values))
+            }
+            Self::FontSrc(values) => ContentSecurityPolicyDirective::FontSrc(owned_sources(values)),
+            Self::FrameSrc(values) => {
+                ContentSecurityPolicyDirective::FrameSrc(owned_sources(values))
+            }
+            Self::ImgSrc(values) => ContentSecurityPolicyDirective::ImgSrc(owned_sources(values)),
+            Self::ManifestSrc(values) => {
+                ContentSecurityPolicyDirective::ManifestSrc(owned_sources(values))
+            }
+            Self::MediaSrc(values) => {
+                ContentSecurityPolicyDirective::MediaSrc(owned_sources(values))
+            }
+            Self::ObjectSrc(values) => {
+                ContentSecurityPolicyDirective::ObjectSrc(owned_sources(values))
+            }
+            Self::PrefetchSrc(values) => {
+                ContentSecurityPolicyDirective::PrefetchSrc(owned_sources(values))
+            }
+            Self::ScriptSrc(values) => {
+                ContentSecurityPolicyDirective::ScriptSrc(owned_sources(values))
+            }
+            Self::ScriptSrcElem(values) => {
+                ContentSecurityPolicyDirective::ScriptSrcElem(owned_sources(values))
+            }
+            Self::ScriptSrcAttr(values) => {
+                ContentSecurityPolicyDirective::ScriptSrcAttr(owned_sources(values))
+            }
+            Self::StyleSrc(values) => {
+                ContentSecurityPolicyDirective::StyleSrc(owned_sources(values))
+            }
+            Self::StyleSrcElem(values) => {
+                ContentSecurityPolicyDirective::StyleSrcElem(owned_sources(values))
+            }
+            Self::StyleSrcAttr(values) => {
+                ContentSecurityPolicyDirective::StyleSrcAttr(owned_sources(values))
+            }
+            Self::WorkerSrc(values) => {
+                ContentSecurityPolicyDirective::WorkerSrc(owned_sources(values))
+            }
+            Self::BaseUri(values) => ContentSecurityPolicyDirective::BaseUri(owned_sources(values)),
+            Self::Sandbox(values) => ContentSecurityPolicyDirective::Sandbox(owned_words(values)),
+            Self::FormAction(values) => {
+                ContentSecurityPolicyDirective::FormAction(owned_sources(values))
+            }
+            Self::FrameAncestors(values) => {
+                ContentSecurityPolicyDirective::FrameAncestors(owned_sources(values))
+            }
+            Self::ReportTo(endpoint) => {
+                ContentSecurityPolicyDirective::ReportTo(Cow::Owned(endpoint.into_owned()))
+            }
+            Self::ReportUri(values) => {
+                ContentSecurityPolicyDirective::ReportUri(owned_words(values))
+            }
+            Self::RequireTrustedTypesFor(values) => {
+                ContentSecurityPolicyDirective::RequireTrustedTypesFor(owned_words(values))
+            }
+            Self::TrustedTypes(values) => {
+                ContentSecurityPolicyDirective::TrustedTypes(owned_words(values))
+            }
+            Self::UpgradeInsecureRequests => {
+                ContentSecurityPolicyDirective::UpgradeInsecureRequests
+            }
+            Self::Other(name, values) => ContentSecurityPolicyDirective::Other(
+                Cow::Owned(name.into_owned()),
+                owned_words(values),
+            ),
+        }
+    }
+}
+
+fn join_sources(values: &[Source<'_>]) -> String {
+    values
+        .iter()
+        .map(|value| value.to_string())
+        .collect::<Vec<String>>()
+        .join(" ")
+}
+
+impl Display for ContentSecurityPolicyDirective<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContentSecurityPolicyDirective::ChildSrc(values) => {
+                write!(f, "child-src {}", join_sources(values))
+            }
+            ContentSecurityPolicyDirective::ConnectSrc(values) => {
+                write!(f, "connect-src {}", join_sources(values))
+            }
+            ContentSecurityPolicyDirective::DefaultSrc(values) => {
+                write!(f, "default-src {}", join_sources(values))
+            }
+            ContentSecurityPolicyDirective::FontSrc(values) => {
+                write!(f, "font-src {}", join_sources(values))
+            }
+            ContentSecurityPolicyDirective::FrameSrc(values) => {
+                write!(f, "frame-src {}", join_sources(values))
+            }
+            ContentSecurityPolicyDirective::ImgSrc(values) => {
+                write!(f, "img-src {}", join_sources(values))
+            }
+            ContentSecurityPolicyDirective::ManifestSrc(values) => {
+                write!(f, "manifest-src {}", join_sources(values))
+            }
+            ContentSecurityPolicyDirective::MediaSrc(values) => {
+                write!(f, "media-src {}", join_sources(values))
+            }
+            ContentSecurityPolicyDirective::ObjectSrc(values) => {
+                write!(f, "object-src {}", join_sources(values))
+            }
+            ContentSecurityPolicyDirective::PrefetchSrc(values) => {
+                write!(f, "prefetch-src {}", join_sources(values))
+            }
+            ContentSecurityPolicyDirective::ScriptSrc(values) => {
+                write!(f, "script-src {}", join_sources(values))
+            }
+            ContentSecurityPolicyDirective::ScriptSrcElem(values) => {
+                write!(f, "script-src-elem {}", join_sources(values))
+            }
+            ContentSecurityPolicyDirective::ScriptSrcAttr(values) => {
+                write!(f, "script-src-attr {}", join_sources(values))
+            }
+            ContentSecurityPolicyDirective::StyleSrc(values) => {
+                write!(f, "style-src {}", join_sources(values))
+            }
+            ContentSecurityPolicyDirective::StyleSrcElem(values) => {
+                write!(f, "style-src-elem {}", join_sources(values))
+            }
+            ContentSecurityPolicyDirective::StyleSrcAttr(values) => {
+                write!(f, "style-src-attr {}", join_sources(values))
+            }
+            ContentSecurityPolicyDirective::WorkerSrc(values) => {
+                write!(f, "worker-src {}", join_sources(values))
+            }
+            ContentSecurityPolicyDirective::BaseUri(values) => {
+                write!(f, "base-uri {}", join_sources(values))
+            }
+            ContentSecurityPolicyDirective::Sandbox(values) => {
+                write!(f, "sandbox {}", values.join(" "))
+            }
+            ContentSecurityPolicyDirective::FormAction(values) => {
+                write!(f, "form-action {}", join_sources(values))
+            }
+            ContentSecurityPolicyDirective::FrameAncestors(values) => {
+                write!(f, "frame-ancestors {}", join_sources(values))
+            }
+            ContentSecurityPolicyDirective::ReportTo(endpoint) => {
+                write!(f, "report-to {}", endpoint)
+            }
+            ContentSecurityPolicyDirective::ReportUri(values) => {
+                write!(f, "report-uri {}", values.join(" "))
+            }
+            ContentSecurityPolicyDirective::RequireTrustedTypesFor(values) => {
+                write!(f, "require-trusted-types-for {}", values.join(" "))
+            }
+            ContentSecurityPolicyDirective::TrustedTypes(values) => {
+                write!(f, "trusted-types {}", values.join(" "))
+            }
+            ContentSecurityPolicyDirective::UpgradeInsecureRequests => {
+                write!(f, "upgrade-insecure-requests")
+            }
+            ContentSecurityPolicyDirective::Other(name, values) => {
+                if values.is_empty() {
+                    write!(f, "{}", name)
+                } else {
+                    write!(f, "{} {}", name, values.join(" "))
+                }
+            }
+        }
+    }
+}
+
+/// Error returned when parsing a `Content-Security-Policy` header value fails
+///
+/// The position is the byte offset into the parsed string where the problem was found.
+///
+/// # Examples
+///
+/// ```
+/// use helmet_core::{ContentSecurityPolicy, ContentSecurityPolicyDirective};
+///
+/// let error = "default-src 'self'; img-src 'self' data:, https:"
+///     .parse::<ContentSecurityPolicy>()
+///     .unwrap_err();
+/// assert_eq!(
+///     error.to_string(),
+///     "unexpected `,`, only a single policy can be parsed at position 40"
+/// );
+///
+/// let error = "upgrade-insecure-requests 1"
+///     .parse::<ContentSecurityPolicyDirective>()
+///     .unwrap_err();
+/// assert_eq!(error.position(), 26);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContentSecurityPolicyParseError {
+    position: usize,
+    message: String,
+}
+
+impl ContentSecurityPolicyParseError {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        Self {
+            position,
+            message: message.into(),
+        }
+    }
+
+    /// Byte offset into the parsed string where the error occurred.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Description of the error, without the position.
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Display for ContentSecurityPolicyParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} at position {}", self.message, self.position)
+    }
+}
+
+impl std::error::Error for ContentSecurityPolicyParseError {}
+
+// Splits on ASCII whitespace, keeping the byte offset of every token.
+fn tokens(input: &str) -> impl Iterator<Item = (usize, &str)> {
+    input
+        .split(|c: char| c.is_ascii_whitespace())
+        .scan(0, |offset, token| {
+            let position = *offset;
+            *offset += token.len() + 1;
+            Some((position, token))
+        })
+        .filter(|(_, token)| !token.is_empty())
+}
+
+impl ContentSecurityPolicyDirective<'static> {
+    fn parse_at(input: &str, offset: usize) -> Result<Self, ContentSecurityPolicyParseError> {
+        let mut tokens = tokens(input).map(|(position, token)| (position + offset, token));
+
+        let (name_position, name) = tokens
+            .next()
+            .ok_or_else(|| ContentSecurityPolicyParseError::new(offset, "empty directive"))?;
+        if !name.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-') {
+            return Err(ContentSecurityPolicyParseError::new(
+                name_position,
+                format!("invalid directive name `{}`", name),
+            ));
+        }
+
+        let mut values = Vec::new();
+        for (position, value) in tokens {
+            if let Some(index) = value
+                .bytes()
+                .position(|b| !(0x21..=0x7e).contains(&b) || b == b';' || b == b',')
+            {
+                let message = if value.as_bytes()[index] == b',' {
+                    "unexpected `,`, only a single policy can be parsed".to_string()
+                } else {
+                    format!("invalid character in `{}`", value)
+                };
+                return Err(ContentSecurityPolicyParseError::new(
+                    position + index,
+                    message,
+                ));
+            }
+            values.push((position, value));
+        }
+
+        let sources = || {
+            values
+                .iter()
+                .map(|(position, value)| {
+                    Source::try_new(value.to_string()).map_err(|err| {
+                        ContentSecurityPolicyParseError::new(
+                            *position,
+                            format!("invalid source `{}`: {}", value, err.reason),
+                        )
+                    })
+                })
+                .collect::<Result<Vec<_>, _>>()
+        };
+        let words = || {
+            values
+                .iter()
+                .map(|(_, value)| Cow::Owned(value.to_string()))
+                .collect::<Vec<_>>()
+        };
+
+        let directive = match name.to_ascii_lowercase().as_str() {
+            "child-src" => Self::ChildSrc(sources()?),
+            "connect-src" => Self::ConnectSrc(sources()?),
+            "default-src" => Self::DefaultSrc(sources()?),
+            "font-src" => Self::FontSrc(sources()?),
+            "frame-src" => Self::FrameSrc(sources()?),
+            "img-src" => Self::ImgSrc(sources()?),
+            "manifest-src" => Self::ManifestSrc(sources()?),
+            "media-src" => Self::MediaSrc(sources()?),
+            "object-src" => Self::ObjectSrc(sources()?),
+            "prefetch-src" => Self::PrefetchSrc(sources()?),
+            "script-src" => Self::ScriptSrc(sources()?),
+            "script-src-elem" => Self::ScriptSrcElem(sources()?),
+            "script-src-attr" => Self::ScriptSrcAttr(sources()?),
+            "style-src" => Self::StyleSrc(sources()?),
+            "style-src-elem" => Self::StyleSrcElem(sources()?),
+            "style-src-attr" => Self::StyleSrcAttr(sources()?),
+            "worker-src" => Self::WorkerSrc(sources()?),
+            "base-uri" => Self::BaseUri(sources()?),
+            "sandbox" => Self::Sandbox(words()),
+            "form-action" => Self::FormAction(sources()?),
+            "frame-ancestors" => Self::FrameAncestors(sources()?),
+            "report-to" => match values.as_slice() {
+                [(_, endpoint)] => Self::ReportTo(Cow::Owned(endpoint.to_string())),
+                _ => {
+                    return Err(ContentSecurityPolicyParseError::new(
+                        values
+                            .get(1)
+                            .map_or(name_position, |(position, _)| *position),
+                        "report-to takes a single endpoint name",
+                    ))
+                }
+            },
+            "report-uri" => Self::ReportUri(words()),
+            "require-trusted-types-for" => Self::RequireTrustedTypesFor(words()),
+            "trusted-types" => Self::TrustedTypes(words()),
+            "upgrade-insecure-requests" => {
+                if let Some((position, _)) = values.first() {
+                    return Err(ContentSecurityPolicyParseError::new(
+                        *position,
+                        "upgrade-insecure-requests does not take a value",
+                    ));
+                }
+                Self::UpgradeInsecureRequests
+            }
+            _ => Self::Other(Cow::Owned(name.to_string()), words()),
+        };
+
+        Ok(directive)
+    }
+}
+
+impl FromStr for ContentSecurityPolicyDirective<'static> {
+    type Err = ContentSecurityPolicyParseError;
+
+    /// Parse a single directive, e.g. `script-src 'self' https://example.com`.
+    ///
+    /// Unknown directives are preserved as [`ContentSecurityPolicyDirective::Other`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if let Some(position) = s.find(';') {
+            return Err(ContentSecurityPolicyParseError::new(
+                position,
+                "unexpected `;`, use ContentSecurityPolicy to parse multiple directives",
+            ));
+        }
+        Self::parse_at(s, 0)
+    }
+}
+
+/// Manages `Content-Security-Policy` header
+///
+/// The HTTP Content-Security-Policy response header allows web site administrators to control resources the user agent is allowed to load for a given page. With a few exceptions, policies mostly involve specifying server origins and script endpoints. This helps guard against cross-site scripting attacks (XSS).
+///
+/// # Examples
+///
+/// ```
+/// use helmet_core::ContentSecurityPolicy;
+///
+/// let content_security_policy = ContentSecurityPolicy::default()
+///    .child_src(vec!["'self'", "https://youtube.com"])
+///    .connect_src(vec!["'self'", "https://youtube.com"])
+///    .default_src(vec!["'self'", "https://youtube.com"])
+///    .font_src(vec!["'self'", "https://youtube.com"]);
+/// ```
+///
+/// ## Report only
+///
+/// In report only mode, the browser will not block the request, but will send a report to the specified endpoint.
+///
+/// Make sure to set the `report-to` directive, and `report-uri` for browsers that don't support it yet.
+///
+/// ```
+/// use helmet_core::{ContentSecurityPolicy, Helmet};
+///
+/// let content_security_policy = ContentSecurityPolicy::default()
+///    .child_src(vec!["'self'", "https://youtube.com"])
+///    .report_to("csp")
+///    .report_uri(vec!["https://example.com/report"])
+///    .report_only();
+///
+/// let helmet = Helmet::new()
+///    .reporting_endpoint("csp", "https://example.com/report")
+///    .add(content_security_policy);
+/// ```
+///
+/// ## Nonces
+///
+/// Instead of allowing inline scripts and styles with `'unsafe-inline'`, a fresh nonce can be generated for each request and added to `script-src` and/or `style-src`.
+/// The nonce is made available to request handlers by the framework adapters, so it can be added to the `nonce` attribute of inline elements.
+///
+/// ```
+/// use helmet_core::ContentSecurityPolicy;
+///
+/// let content_security_policy = ContentSecurityPolicy::default()
+///    .script_nonce()
+///    .style_nonce();
+/// ```
+///
+/// ## Runtime configuration
+///
+/// Sources can also be owned `String`s, e.g. loaded from a configuration file or environment variables. Such a policy is `ContentSecurityPolicy<'static>`, so it can be added to `Helmet` without leaking the strings.
+///
+/// ```
+/// use helmet_core::{ContentSecurityPolicy, Helmet};
+///
+/// fn policy_from_config(script_hosts: Vec<String>, report_endpoint: String) -> ContentSecurityPolicy<'static> {
+///     let mut script_src = vec!["'self'".to_string()];
+///     script_src.extend(script_hosts);
+///
+///     ContentSecurityPolicy::new()
+///         .default_src(vec!["'self'"])
+///         .script_src(script_src)
+///         .report_to(report_endpoint)
+/// }
+///
+/// let policy = policy_from_config(vec!["https://cdn.example.com".to_string()], "csp".to_string());
+/// assert_eq!(
+///     policy.to_string(),
+///     "default-src 'self'; script-src 'self' https://cdn.example.com; report-to csp"
+/// );
+///
+/// let helmet = Helmet::new().add(policy);
+/// ```
+///
+/// A policy borrowing from short-lived data can be converted with [`ContentSecurityPolicy::into_owned`].
+///
+/// ## Parsing
+///
+/// Existing policies can be parsed from a header value. Directives Helmet doesn't know about are preserved.
+///
+/// ```
+/// use helmet_core::{ContentSecurityPolicy, ContentSecurityPolicyDirective, Source};
+///
+/// let content_security_policy: ContentSecurityPolicy = "default-src 'self'; img-src 'self' data:; fenced-frame-src 'none'"
+///     .parse()
+///     .unwrap();
+///
+/// assert_eq!(
+///     content_security_policy,
+///     ContentSecurityPolicy::new()
+///         .default_src(vec![Source::Self_])
+///         .img_src(vec!["'self'", "data:"])
+///         .directive(ContentSecurityPolicyDirective::Other("fenced-frame-src".into(), vec!["'none'".into()]))
+/// );
+///
+/// let error = "script-src 'self' 'unsafe-inlin'"
+///     .parse::<ContentSecurityPolicy>()
+///     .unwrap_err();
+/// assert_eq!(error.position(), 18);
+///
+/// let policy = ContentSecurityPolicy::default();
+/// assert_eq!(policy.to_string().parse::<ContentSecurityPolicy>().unwrap(), policy);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ContentSecurityPolicy<'a> {
+    directives: Vec<ContentSecurityPolicyDirective<'a>>,
+    report_only: bool,
+    script_nonce: bool,
+    style_nonce: bool,
+}
+
+impl<'a> ContentSecurityPolicy<'a> {
+    pub fn new() -> Self {
+        Self {
+            directives: Vec::new(),
+            report_only: false,
+            script_nonce: false,
+            style_nonce: false,
+        }
+    }
+
+    /// Add a directive to the policy, e.g. one not covered by the other builder methods.
+    pub fn directive(mut self, directive: ContentSecurityPolicyDirective<'a>) -> Self {
+        self.directives.push(directive);
+        self
+    }
+
+    /// child-src: Defines valid sources for web workers and nested browsing contexts loaded using elements such as `<frame>` and `<iframe>`.
+    pub fn child_src<S: Into<Source<'a>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::child_src(values))
+    }
+
+    /// connect-src: Applies to XMLHttpRequest (AJAX), WebSocket or EventSource. If not allowed the browser emulates a 400 HTTP status code.
+    pub fn connect_src<S: Into<Source<'a>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::connect_src(values))
+    }
+
+    /// default-src: The default-src is the default policy for loading content such as JavaScript, Images, CSS, Font's, AJAX requests, Frames, HTML5 Media. See the list of directives to see which values are allowed as default.
+    pub fn default_src<S: Into<Source<'a>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::default_src(values))
+    }
+
+    /// font-src: Defines valid sources for fonts loaded using @font-face.
+    pub fn font_src<S: Into<Source<'a>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::font_src(values))
+    }
+
+    /// frame-src: Defines valid sources for nested browsing contexts loading using elements such as `<frame>` and `<iframe>`.
+    pub fn frame_src<S: Into<Source<'a>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::frame_src(values))
+    }
+
+    /// img-src: Defines valid sources of images and favicons.
+    pub fn img_src<S: Into<Source<'a>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::img_src(values))
+    }
+
+    /// manifest-src: Specifies which manifest can be applied to the resource.
+    pub fn manifest_src<S: Into<Source<'a>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::manifest_src(values))
+    }
+
+    /// media-src: Defines valid sources for loading media using the `<audio>` and `<video>` elements.
+    pub fn media_src<S: Into<Source<'a>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::media_src(values))
+    }
+
+    /// object-src: Defines valid sources for the `<object>`, `<embed>`, and `<applet>` elements.
+    pub fn object_src<S: Into<Source<'a>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::object_src(values))
+    }
+
+    /// prefetch-src: Specifies which referrer to use when fetching the resource.
+    pub fn prefetch_src<S: Into<Source<'a>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::prefetch_src(values))
+    }
+
+    /// script-src: Defines valid sources for JavaScript.
+    pub fn script_src<S: Into<Source<'a>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::script_src(values))
+    }
+
+    /// script-src-elem: Defines valid sources for JavaScript inline event handlers.
+    pub fn script_src_elem<S: Into<Source<'a>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::script_src_elem(values))
+    }
+
+    /// script-src-attr: Defines valid sources for JavaScript inline event handlers.
+    pub fn script_src_attr<S: Into<Source<'a>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::script_src_attr(values))
+    }
+
+    /// style-src: Defines valid sources for stylesheets.
+    pub fn style_src<S: Into<Source<'a>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::style_src(values))
+    }
+
+    /// style-src-elem: Defines valid sources for stylesheets inline event handlers.
+    pub fn style_src_elem<S: Into<Source<'a>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::style_src_elem(values))
+    }
+
+    /// style-src-attr: Defines valid sources for stylesheets inline event handlers.
+    pub fn style_src_attr<S: Into<Source<'a>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::style_src_attr(values))
+    }
+
+    /// worker-src: Defines valid sources for Worker, SharedWorker, or ServiceWorker scripts.
+    pub fn worker_src<S: Into<Source<'a>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::worker_src(values))
+    }
+
+    /// base-uri: Restricts the URLs which can be used in a document's `<base>` element.
+    pub fn base_uri<S: Into<Source<'a>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::base_uri(values))
+    }
+
+    /// sandbox: Enables a sandbox for the requested resource similar to the iframe sandbox attribute. The sandbox applies a same origin policy, prevents popups, plugins and script execution is blocked. You can keep the sandbox value empty to keep all restrictions in place, or add values: allow-forms allow-same-origin allow-scripts allow-popups, allow-modals, allow-orientation-lock, allow-pointer-lock, allow-presentation, allow-popups-to-escape-sandbox, allow-top-navigation, allow-top-navigation-by-user-activation.
+    pub fn sandbox<S: Into<Cow<'a, str>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::sandbox(values))
+    }
+
+    /// form-action: Restricts the URLs which can be used as the target of a form submissions from a given context.
+    pub fn form_action<S: Into<Source<'a>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::form_action(values))
+    }
+
+    /// frame-ancestors: Specifies valid parents that may embed a page using `<frame>`, `<iframe>`, `<object>`, `<embed>`, or `<applet>`.
+    pub fn frame_ancestors<S: Into<Source<'a>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::frame_ancestors(values))
+    }
+
+    /// report-to: Names the reporting endpoint violations are sent to.
+    ///
+    /// The endpoint can be registered with [`Helmet::reporting_endpoint`].
+    pub fn report_to<S: Into<Cow<'a, str>>>(self, endpoint: S) -> Self {
+        self.directive(ContentSecurityPolicyDirective::report_to(endpoint))
+    }
+
+    /// report-uri: URLs violation reports are sent to, for browsers that don't support report-to.
+    pub fn report_uri<S: Into<Cow<'a, str>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::report_uri(values))
+    }
+
+    /// require-trusted-types-for: Specifies which trusted types are required by a resource.
+    pub fn require_trusted_types_for<S: Into<Cow<'a, str>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::require_trusted_types_for(
+            values,
+        ))
+    }
+
+    /// trusted-types: Specifies which trusted types are defined by a resource.
+    pub fn trusted_types<S: Into<Cow<'a, str>>>(self, values: Vec<S>) -> Self {
+        self.directive(ContentSecurityPolicyDirective::trusted_types(values))
+    }
+
+    /// Block HTTP requests on insecure elements.
+    pub fn upgrade_insecure_requests(self) -> Self {
+        self.directive(ContentSecurityPolicyDirective::upgrade_insecure_requests())
+    }
+
+    /// Enable report only mode
+    ///
+    /// When set to true, the `Content-Security-Policy-Report-Only` header is set instead of `Content-Security-Policy`.
+    ///
+    /// Defaults to false.
+    ///
+    /// See https://developer.mozilla.org/en-US/docs/Web/HTTP/Headers/Content-Security-Policy-Report-Only
+    pub fn report_only(mut self) -> Self {
+        self.report_only = true;
+        self
+    }
+
+    /// Add a per-request nonce to `script-src` and `script-src-elem`
+    ///
+    /// If neither directive is set, a `script-src` directive with the sources of `default-src` and the nonce is added. `'none'` is dropped from a directive the nonce is added to.
+    ///
+    /// The nonce is only rendered by framework adapters, which generate a new one for every request. The plain `Display` output of the policy does not contain it.
+    pub fn script_nonce(mut self) -> Self {
+        self.script_nonce = true;
+        self
+    }
+
+    /// Add a per-request nonce to `style-src` and `style-src-elem`
+    ///
+    /// If neither directive is set, a `style-src` directive with the sources of `default-src` and the nonce is added. `'none'` is dropped from a directive the nonce is added to.
+    ///
+    /// The nonce is only rendered by framework adapters, which generate a new one for every request. The plain `Display` output of the policy does not contain it.
+    pub fn style_nonce(mut self) -> Self {
+        self.style_nonce = true;
+        self
+    }
+
+    /// Check that every directive is valid
+    ///
+    /// Sources given as plain strings are not validated when the policy is built. `Helmet::validate`, and therefore the framework adapters, call this to catch values that would break the policy, e.g. ones read from tenant configuration.
+    ///
+    /// ```
+    /// use helmet_core::ContentSecurityPolicy;
+    ///
+    /// assert!(ContentSecurityPolicy::default().validate().is_ok());
+    ///
+    /// let policy = ContentSecurityPolicy::new().img_src(vec!["'self'", "https://cdn.example.com; script-src *"]);
+    /// assert!(policy.validate().is_err());
+    ///
+    /// // keywords without quotes are host names, which is never what was meant
+    /// let error = ContentSecurityPolicy::new().default_src(vec!["self"]).validate().unwrap_err();
+    /// assert_eq!(error.to_string(), "invalid CSP source `self`: missing quotes around keyword");
+    /// for keyword in ["none", "unsafe-inline", "unsafe-eval", "strict-dynamic", "report-sample", "wasm-unsafe-eval", "unsafe-hashes"] {
+    ///     assert!(ContentSecurityPolicy::new().script_src(vec![keyword]).validate().is_err());
+    /// }
+    /// ```
+    pub fn validate(&self) -> Result<(), SourceError> {
+        self.directives
+            .iter()
+            .try_for_each(ContentSecurityPolicyDirective::validate)
+    }
+
+    /// Merge `other` into the policy, allowing the sources of both policies
+    ///
+    /// The result allows what either policy allows. Sources of a directive set by both policies are combined. A fetch directive only set by one policy starts from the sources it falls back to in the other policy, e.g. those of `default-src`, and is left out when the other policy has nothing to fall back to, as that policy doesn't restrict it. The same applies to directives without a fallback, e.g. `frame-ancestors`, `base-uri` or `form-action`, which are only kept when both policies set them. `'none'` is dropped from a directive once it allows other sources. Reporting directives are kept, and those with a single value, i.e. `report-to`, take the value of `other`.
+    ///
+    /// ```
+    /// use helmet_core::ContentSecurityPolicy;
+    ///
+    /// let base = ContentSecurityPolicy::new()
+    ///     .default_src(vec!["'self'"])
+    ///     .object_src(vec!["'none'"])
+    ///     .script_src(vec!["'self'"])
+    ///     .frame_ancestors(vec!["'none'"]);
+    /// let service = ContentSecurityPolicy::new()
+    ///     .default_src(vec!["'none'"])
+    ///     .script_src(vec!["https://cdn.example.com"])
+    ///     .connect_src(vec!["https://api.example.com"])
+    ///     .worker_src(vec!["blob:"]);
+    ///
+    /// assert_eq!(
+    ///     base.union(service).to_string(),
+    ///     "default-src 'self'; object-src 'none'; script-src 'self' https://cdn.example.com; connect-src 'self' https://api.example.com; worker-src 'self' blob:"
+    /// );
+    /// ```
+    pub fn union(mut self, other: ContentSecurityPolicy<'a>) -> Self {
+        let seeded = self.seed(other.clone());
+        self.directives.retain_mut(|directive| {
+            if other.sets(directive) || directive.is_reporting() {
+                return true;
+            }
+            match other.fallback_directive(directive) {
+                Some(fallback) => {
+                    directive.union(fallback);
+                    true
+                }
+                None => false,
+            }
+        });
+        self.merge_directives(seeded, ContentSecurityPolicyDirective::union)
+    }
+
+    // Allows the sources of `other` in the policy, see [`ResponseOverrides::extend_content_security_policy`]. Unlike `union`, directives only set by the policy are kept as they are.
+    fn extend(self, other: ContentSecurityPolicy<'a>) -> Self {
+        let seeded = self.seed(other);
+        self.merge_directives(seeded, ContentSecurityPolicyDirective::union)
+    }
+
+    // Directives of `other` the policy doesn't set start from the sources they fall back to in the policy, and are left out when the policy doesn't restrict them.
+    fn seed(&self, mut other: ContentSecurityPolicy<'a>) -> ContentSecurityPolicy<'a> {
+        other.directives.retain_mut(|directive| {
+            if self.sets(directive) || directive.is_reporting() {
+                return true;
+            }
+            match self.fallback_directive(directive) {
+                Some(mut fallback) => {
+                    fallback.union(directive.clone());
+                    *directive = fallback;
+                    true
+                }
+                None => false,
+            }
+        });
+        other
+    }
+
+    fn sets(&self, directive: &ContentSecurityPolicyDirective<'a>) -> bool {
+        self.directives
+            .iter()
+            .any(|existing| existing.name().eq_ignore_ascii_case(directive.name()))
+    }
+
+    // Copy of a fetch directive with the sources it falls back to in the policy
+    fn fallback_directive(
+        &self,
+        directive: &ContentSecurityPolicyDirective<'a>,
+    ) -> Option<ContentSecurityPolicyDirective<'a>> {
+        let sources = self.fallback_sources(directive.name())?;
+        let mut fallback = directive.clone();
+        *fallback.sources_mut()? = sources;
+        Some(fallback)
+    }
+
+    /// Merge `other` into the policy, replacing the directives with the same name
+    ///
+    /// Directives only set by the policy are kept, directives only set by `other` are added.
+    ///
+    /// ```
+    /// use helmet_core::ContentSecurityPolicy;
+    ///
+    /// let base = ContentSecurityPolicy::new()
+    ///     .default_src(vec!["'self'"])
+    ///     .script_src(vec!["'self'"]);
+    /// let service = ContentSecurityPolicy::new().script_src(vec!["https://cdn.example.com"]);
+    ///
+    /// assert_eq!(
+    ///     base.override_directives(service).to_string(),
+    ///     "default-src 'self'; script-src https://cdn.example.com"
+    /// );
+    /// ```
+    pub fn override_directives(self, other: ContentSecurityPolicy<'a>) -> Self {
+        self.merge_directives(other, |directive, other| *directive = other)
+    }
+
+    fn merge_directives(
+        mut self,
+        other: ContentSecurityPolicy<'a>,
+        merge: impl Fn(&mut ContentSecurityPolicyDirective<'a>, ContentSecurityPolicyDirective<'a>),
+    ) -> Self {
+        for directive in other.directives {
+            match self
+                .directives
+                .iter_mut()
+                .find(|existing| existing.name().eq_ignore_ascii_case(directive.name()))
+            {
+                Some(existing) => merge(existing, directive),
+                None => self.directives.push(directive),
+            }
+        }
+        self.script_nonce |= other.script_nonce;
+        self.style_nonce |= other.style_nonce;
+        self
+    }
+
+    /// Convert into a policy that owns its data, so it no longer borrows from the input.
+    ///
+    /// The result is `ContentSecurityPolicy<'static>`, which can be added to `Helmet`.
+    pub fn into_owned(self) -> ContentSecurityPolicy<'static> {
+        ContentSecurityPolicy {
+            directives: self
+                .directives
+                .into_iter()
+                .map(ContentSecurityPolicyDirective::into_owned)
+                .collect(),
+            report_only: self.report_only,
+            script_nonce: self.script_nonce,
+            style_nonce: self.style_nonce,
+        }
+    }
+
+    // Drops `upgrade-insecure-requests` and allows WebSockets to any port of `localhost`, see [`Helmet::relaxed_for_development`].
+    fn relax_for_development(&mut self) {
+        self.directives.retain(|directive| {
+            *directive != ContentSecurityPolicyDirective::UpgradeInsecureRequests
+        });
+
+        // connect-src falls back to default-src, which has to be kept when adding the WebSocket source
+        if !self
+            .directives
+            .iter()
+            .any(|directive| matches!(directive, ContentSecurityPolicyDirective::ConnectSrc(_)))
+        {
+            let Some(sources) = self.fallback_sources("connect-src") else {
+                return;
+            };
+            self.directives
+                .push(ContentSecurityPolicyDirective::ConnectSrc(sources));
+        }
+        for directive in self.directives.iter_mut() {
+            if let ContentSecurityPolicyDirective::ConnectSrc(_) = directive {
+                directive.union(ContentSecurityPolicyDirective::connect_src(vec![
+                    "ws://localhost:*",
+                ]));
+            }
+        }
+    }
+
+    // Sources a fetch directive that isn't set falls back to, following the fallback lists of CSP Level 3, e.g. `worker-src` to `child-src`, `script-src` and then `default-src`.
+    fn fallback_sources(&self, name: &str) -> Option<Vec<Source<'a>>> {
+        let fallbacks: &[&str] = match name.to_ascii_lowercase().as_str() {
+            "script-src-elem" | "script-src-attr" => &["script-src", "default-src"],
+            "style-src-elem" | "style-src-attr" => &["style-src", "default-src"],
+            "frame-src" => &["child-src", "default-src"],
+            "worker-src" => &["child-src", "script-src", "default-src"],
+            "child-src" | "connect-src" | "font-src" | "img-src" | "manifest-src" | "media-src"
+            | "object-src" | "prefetch-src" | "script-src" | "style-src" => &["default-src"],
+            _ => &[],
+        };
+        fallbacks
+            .iter()
+            .find_map(|fallback| {
+                self.directives
+                    .iter()
+                    .find(|directive| directive.name() == *fallback)
+            })
+            .and_then(|directive| directive.clone().sources_mut().map(std::mem::take))
+    }
+
+    fn takes_nonce(&self, directive: &ContentSecurityPolicyDirective<'a>) -> bool {
+        match directive {
+            ContentSecurityPolicyDirective::ScriptSrc(_)
+            | ContentSecurityPolicyDirective::ScriptSrcElem(_) => self.script_nonce,
+            ContentSecurityPolicyDirective::StyleSrc(_)
+            | ContentSecurityPolicyDirective::StyleSrcElem(_) => self.style_nonce,
+            _ => false,
+        }
+    }
+}
+
+impl<'a> Display for ContentSecurityPolicy<'a> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let directives = self
+            .directives
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<String>>()
+            .join("; ");
+        write!(f, "{}", directives)
+    }
+}
+
+impl FromStr for ContentSecurityPolicy<'static> {
+    type Err = ContentSecurityPolicyParseError;
+
+    /// Parse a `Content-Security-Policy` header value.
+    ///
+    /// Empty directives are skipped.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut policy = Self::new();
+        let mut offset = 0;
+
+        for segment in s.split(';') {
+            let position = offset;
+            offset += segment.len() + 1;
+
+            if segment
+                .trim_matches(|c: char| c.is_ascii_whitespace())
+                .is_empty()
+            {
+                continue;
+            }
+
+            let directive = ContentSecurityPolicyDirective::parse_at(segment, position)?;
+            policy.directives.push(directive);
+        }
+
+        Ok(policy)
+    }
+}
+
+impl<'a> Default for ContentSecurityPolicy<'a> {
+    /// Default policy for the Content-Security-Policy header.
+    ///
+    /// values:
+    /// ```text
+    /// default-src 'self';
+    /// base-uri 'self';
+    /// font-src 'self' https: data:;
+    /// form-action 'self';
+    /// frame-ancestors 'self';
+    /// img-src 'self' data:;
+    /// object-src 'none';
+    /// script-src 'self';
+    /// script-src-attr 'none';
+    /// style-src 'self' https: 'unsafe-inline';
+    /// upgrade-insecure-requests
+    /// ```
+    fn default() -> Self {
+        Self::new()
+            .default_src(vec![Source::Self_])
+            .base_uri(vec![Source::Self_])
+            .font_src(vec![
+                Source::Self_,
+                Source::Scheme("https".into()),
+                Source::Scheme("data".into()),
+            ])
+            .form_action(vec![Source::Self_])
+            .frame_ancestors(vec![Source::Self_])
+            .img_src(vec![Source::Self_, Source::Scheme("data".into())])
+            .object_src(vec![Source::None])
+            .script_src(vec![Source::Self_])
+            .script_src_attr(vec![Source::None])
+            .style_src(vec![
+                Source::Self_,
+                Source::Scheme("https".into()),
+                Source::UnsafeInline,
+            ])
+            .upgrade_insecure_requests()
+    }
+}
+
+impl Header for ContentSecurityPolicy<'static> {
+    fn name(&self) -> &'static str {
+        if self.report_only {
+            "Content-Security-Policy-Report-Only"
+        } else {
+            "Content-Security-Policy"
+        }
+    }
+
+    fn value(&self) -> String {
+        self.to_string()
+    }
+
+    fn allows_multiple(&self) -> bool {
+        true
+    }
+
+    fn validate(&self) -> Result<(), HelmetError> {
+        ContentSecurityPolicy::validate(self).map_err(|error| HelmetError::InvalidSource {
+            name: self.name().to_string(),
+            error,
+        })
+    }
+
+    fn nonce_template(&self) -> Option<NonceTemplate> {
+        if !self.script_nonce && !self.style_nonce {
+            return None;
+        }
+
+        let mut directives = self.directives.clone();
+        let has_script = self.directives.iter().any(|directive| {
+            matches!(
+                directive,
+                ContentSecurityPolicyDirective::ScriptSrc(_)
+                    | ContentSecurityPolicyDirective::ScriptSrcElem(_)
+            )
+        });
+        let has_style = self.directives.iter().any(|directive| {
+            matches!(
+                directive,
+                ContentSecurityPolicyDirective::StyleSrc(_)
+                    | ContentSecurityPolicyDirective::StyleSrcElem(_)
+            )
+        });
+        // the added directive replaces the default-src fallback, so it starts from its sources
+        if self.script_nonce && !has_script {
+            directives.push(ContentSecurityPolicyDirective::ScriptSrc(
+                self.fallback_sources("script-src").unwrap_or_default(),
+            ));
+        }
+        if self.style_nonce && !has_style {
+            directives.push(ContentSecurityPolicyDirective::StyleSrc(
+                self.fallback_sources("style-src").unwrap_or_default(),
+            ));
+        }
+
+        let mut segments = Vec::new();
+        let mut current = String::new();
+        for mut directive in directives {
+            if !current.is_empty() || !segments.is_empty() {
+                current.push_str("; ");
+            }
+            if !self.takes_nonce(&directive) {
+                current.push_str(&directive.to_string());
+                continue;
+            }
+            // 'none' has to be the only source, so it is dropped once the nonce is allowed
+            if let Some(sources) = directive.sources_mut() {
+                sources.retain(|source| *source != Source::None);
+            }
+            current.push_str(directive.to_string().trim_end());
+            current.push(' ');
+            segments.push(std::mem::take(&mut current));
+        }
+
+        segments.push(current);
+        Some(NonceTemplate(segments))
+    }
+
+    fn scope(&self) -> HeaderScope {
+        HeaderScope::DocumentOrScript
+    }
+}
+
+/// Error returned when a `Helmet` header can't be sent as an HTTP header
+///
+/// Names the offending header, so configuration mistakes can be traced back to their source.
+///
+/// # Examples
+///
+/// ```
+/// use helmet_core::{Helmet, HelmetError, XPoweredBy};
+///
+/// let error = Helmet::new()
+///     .add(XPoweredBy::new("PHP\r\nSet-Cookie: a=b"))
+///     .validate()
+///     .unwrap_err();
+///
+/// assert_eq!(
+///     error,
+///     HelmetError::InvalidHeaderValue {
+///         name: "X-Powered-By".to_string(),
+///         value: "PHP\r\nSet-Cookie: a=b".to_string(),
+///     }
+/// );
+/// assert_eq!(
+///     error.to_string(),
+///     "invalid value for header `X-Powered-By`: \"PHP\\r\\nSet-Cookie: a=b\""
+/// );
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HelmetError {
+    /// The header name is not a valid HTTP header name.
+    InvalidHeaderName { name: String },
+    /// The header value contains characters that are not allowed in HTTP header values.
+    InvalidHeaderValue { name: String, value: String },
+    /// A CSP source or directive value is not valid.
+    InvalidSource { name: String, error: SourceError },
+    /// An option of imported helmet.js options is not valid, see `Helmet::from_helmetjs_options`. The option is empty when the options as a whole are invalid.
+    InvalidOption { option: String, message: String },
+    /// A trusted proxy is not an IP address or a range in CIDR notation, see [`Helmet::trusted_proxy`].
+    InvalidTrustedProxy { value: String },
+    /// `Strict-Transport-Security` has `preload` without the max-age and includeSubDomains the HSTS preload list requires.
+    InvalidPreload { value: String },
+    /// A path pattern of a [`PathRule`] doesn't start with `/` or contains `**`, see [`Helmet::path`].
+    InvalidPathPattern { pattern: String },
+    /// A header of [`ResponseOverrides`] can't be applied to a single response, e.g. it adds a nonce.
+    InvalidOverride { name: String, message: String },
+}
+
+impl Display for HelmetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HelmetError::InvalidHeaderName { name } => {
+                write!(f, "invalid header name {:?}", name)
+            }
+            HelmetError::InvalidHeaderValue { name, value } => {
+                write!(f, "invalid value for header `{}`: {:?}", name, value)
+            }
+            HelmetError::InvalidSource { name, error } => {
+                write!(f, "invalid header `{}`: {}", name, error)
+            }
+            HelmetError::InvalidOption { option, message } if option.is_empty() => {
+                write!(f, "invalid options: {}", message)
+            }
+            HelmetError::InvalidOption { option, message } => {
+                write!(f, "invalid option `{}`: {}", option, message)
+            }
+            HelmetError::InvalidTrustedProxy { value } => {
+                write!(f, "invalid trusted proxy {:?}", value)
+            }
+            HelmetError::InvalidPreload { value } => {
+                write!(
+                    f,
+                    "invalid header `Strict-Transport-Security`: preload requires a max-age of at least {} and includeSubDomains, got {:?}",
+                    StrictTransportSecurity::PRELOAD_MIN_MAX_AGE,
+                    value
+                )
+            }
+            HelmetError::InvalidPathPattern { pattern } => {
+                write!(f, "invalid path pattern {:?}", pattern)
+            }
+            HelmetError::InvalidOverride { name, message } => {
+                write!(f, "invalid override of header `{}`: {}", name, message)
+            }
+        }
+    }
+}
+
+impl std::error::Error for HelmetError {}
+
+// Checks that the header can be sent, with any nonce
+fn validate_header(header: &dyn Header) -> Result<(), HelmetError> {
+    let name = header.name();
+    if !is_valid_header_name(name) {
+        return Err(HelmetError::InvalidHeaderName {
+            name: name.to_string(),
+        });
+    }
+    header.validate()?;
+
+    let mut values = vec![header.value()];
+    if let Some(template) = header.nonce_template() {
+        values.push(template.render(&Nonce::generate()));
+    }
+    for value in values {
+        if !is_valid_header_value(&value) {
+            return Err(HelmetError::InvalidHeaderValue {
+                name: name.to_string(),
+                value,
+            });
+        }
+    }
+    Ok(())
+}
+
+// token = 1*tchar
+fn is_valid_header_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.bytes().all(|b| {
+            b.is_ascii_alphanumeric()
+                || matches!(
+                    b,
+                    b'!' | b'#'
+                        | b'$'
+                        | b'%'
+                        | b'&'
+                        | b'\''
+                        | b'*'
+                        | b'+'
+                        | b'-'
+                        | b'.'
+                        | b'^'
+                        | b'_'
+                        | b'`'
+                        | b'|'
+                        | b'~'
+                )
+        })
+}
+
+// Visible ASCII, space and horizontal tab. Non-ASCII (obs-text) is rejected, as not every framework accepts it.
+fn is_valid_header_value(value: &str) -> bool {
+    value
+        .bytes()
+        .all(|b| b == b'\t' || (0x20..0x7f).contains(&b))
+}
+
+// URIs and other parameters embedded in a header value, which must not contain separators.
+fn validate_parameter(name: &str, value: &str) -> Result<(), HelmetError> {
+    if value.is_empty()
+        || !value
+            .bytes()
+            .all(|b| (0x21..=0x7e).contains(&b) && b != b';' && b != b',')
+    {
+        return Err(HelmetError::InvalidHeaderValue {
+            name: name.to_string(),
+            value: value.to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// How a header is applied when the response already has a header with the same name
+///
+/// Framework adapters apply the strategy of each header, see [`Helmet::conflict_strategy`] and [`Helmet::header_conflict_strategy`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema),
+    serde(rename_all = "kebab-case"),
+    schemars(
+        description = "How a header is applied when the response already has a header with the same name"
+    )
+)]
+pub enum ConflictStrategy {
+    /// Replace the existing values with the header.
+    Overwrite,
+    /// Add the header next to the existing values. Browsers enforce every `Content-Security-Policy` header they receive, so appending a policy can only make it stricter.
+    Append,
+    /// Keep the existing values and don't add the header.
+    #[default]
+    KeepExisting,
+}
+
+/// Which configuration wins when [`Helmet::merge`] finds a header in both
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MergePrecedence {
+    /// Keep the headers of the base configuration, only add the headers it doesn't set.
+    Base,
+    /// Replace the headers of the base configuration with the headers of the same name.
+    Other,
+    /// Like [`MergePrecedence::Other`], but `Content-Security-Policy` directives replace the directives with the same name, see [`ContentSecurityPolicy::override_directives`].
+    OverrideDirectives,
+    /// Like [`MergePrecedence::Other`], but the `Content-Security-Policy` allows what either policy allows, see [`ContentSecurityPolicy::union`]. Directives one policy doesn't restrict are left out, so both policies should set `default-src`.
+    UnionDirectives,
+}
+
+/// When the relaxations of [`Helmet::relaxed_for_development`] are applied by framework adapters, see [`Helmet::development`]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize, schemars::JsonSchema),
+    serde(rename_all = "kebab-case"),
+    schemars(
+        description = "When the development relaxations, which drop HSTS and upgrade-insecure-requests, are applied"
+    )
+)]
+pub enum DevelopmentMode {
+    /// Never apply the relaxations.
+    #[default]
+    Off,
+    /// Apply the relaxations to every request, e.g. when the application is built for local development.
+    On,
+    /// Apply the relaxations to requests for loopback hosts, i.e. `localhost`, `*.localhost`, `127.0.0.0/8` and `[::1]`, taken from the `Host` header, that come from a loopback address and weren't forwarded by a proxy.
+    Loopback,
+}
+
+impl DevelopmentMode {
+    /// Whether the relaxations apply to a request
+    ///
+    /// `host` is the value of the `Host` header, with or without port, `peer_addr` the address the request came from and `forwarded` whether it has a `Forwarded` or `X-Forwarded-*` header, see [`is_forwarding_header`].
+    ///
+    /// The `Host` header is set by the client, so [`DevelopmentMode::Loopback`] also requires a loopback peer address, and no forwarding headers as a reverse proxy on the same machine connects from a loopback address as well.
+    ///
+    /// ```
+    /// use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+    ///
+    /// use helmet_core::DevelopmentMode;
+    ///
+    /// let loopback = Some(IpAddr::V4(Ipv4Addr::LOCALHOST));
+    ///
+    /// assert!(DevelopmentMode::Loopback.applies_to(Some("localhost:3000"), loopback, false));
+    /// assert!(DevelopmentMode::Loopback.applies_to(Some("[::1]:8080"), Some(IpAddr::V6(Ipv6Addr::LOCALHOST)), false));
+    /// assert!(!DevelopmentMode::Loopback.applies_to(Some("example.com"), loopback, false));
+    /// assert!(!DevelopmentMode::Loopback.applies_to(None, loopback, false));
+    /// // a spoofed `Host`
+    /// assert!(!DevelopmentMode::Loopback.applies_to(Some("localhost"), Some(IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))), false));
+    /// assert!(!DevelopmentMode::Loopback.applies_to(Some("localhost"), None, false));
+    /// // a reverse proxy on the same machine
+    /// assert!(!DevelopmentMode::Loopback.applies_to(Some("localhost"), loopback, true));
+    /// assert!(DevelopmentMode::On.applies_to(Some("example.com"), None, true));
+    /// ```
+    pub fn applies_to(
+        self,
+        host: Option<&str>,
+        peer_addr: Option<IpAddr>,
+        forwarded: bool,
+    ) -> bool {
+        match self {
+            Self::Off => false,
+            Self::On => true,
+            Self::Loopback => {
+                host.is_some_and(is_loopback_host)
+                    && peer_addr.is_some_and(|address| address.is_loopback())
+                    && !forwarded
+            }
+        }
+    }
+}
+
+/// Whether a request header is set by reverse proxies forwarding the request, i.e. `Forwarded` and `X-Forwarded-*`. Header names are case-insensitive.
+///
+/// ```
+/// use helmet_core::is_forwarding_header;
+///
+/// assert!(is_forwarding_header("Forwarded"));
+/// assert!(is_forwarding_header("x-forwarded-for"));
+/// assert!(!is_forwarding_header("Host"));
+/// ```
+pub fn is_forwarding_header(name: &str) -> bool {
+    name.eq_ignore_ascii_case("forwarded")
+        || name
+            .get(..12)
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case("x-forwarded-"))
+}
+
+fn is_loopback_host(host: &str) -> bool {
+    let host = match host.strip_prefix('[') {
+        Some(rest) => match rest.split_once(']') {
+            Some((address, _)) => address,
+            None => return false,
+        },
+        None => match host.rsplit_once(':') {
+            Some((name, port))
+                if !name.contains(':') && port.bytes().all(|b| b.is_ascii_digit()) =>
+            {
+                name
+            }
+            _ => host,
+        },
+    };
+    let host = host.strip_suffix('.').unwrap_or(host).to_ascii_lowercase();
+    host == "localhost"
+        || host.ends_with(".localhost")
+        || host
+            .parse::<IpAddr>()
+            .is_ok_and(|address| address.is_loopback())
+}
+
+/// Reverse proxies whose `Forwarded` and `X-Forwarded-Proto` headers are trusted, see [`Helmet::trusted_proxy`]
+///
+/// Framework adapters use it to tell whether a request arrived over HTTPS, when TLS is terminated by a proxy in front of the application.
+///
+/// ```
+/// use helmet_core::Helmet;
+///
+/// let proxies = Helmet::new().trusted_proxy("10.0.0.0/8").trusted_proxies().unwrap();
+///
+/// assert!(proxies.is_https(false, Some("10.1.2.3".parse().unwrap()), None, Some("https")));
+/// assert!(proxies.is_https(false, Some("10.1.2.3".parse().unwrap()), Some("for=192.0.2.60;proto=https"), None));
+/// assert!(!proxies.is_https(false, Some("192.0.2.60".parse().unwrap()), None, Some("https")));
+/// assert!(proxies.is_https(true, None, None, None));
+///
+/// // IPv4-mapped IPv6 addresses match IPv4 ranges
+/// assert!(proxies.contains("::ffff:10.0.0.1".parse().unwrap()));
+/// let proxies = Helmet::new().trusted_proxy("fd00::/8").trusted_proxies().unwrap();
+/// assert!(proxies.contains("fd12::1".parse().unwrap()));
+/// assert!(!proxies.contains("fe80::1".parse().unwrap()));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TrustedProxies(Vec<(IpAddr, u8)>);
+
+impl TrustedProxies {
+    fn parse(value: &str) -> Result<(IpAddr, u8), HelmetError> {
+        let error = || HelmetError::InvalidTrustedProxy {
+            value: value.to_string(),
+        };
+        let (address, prefix) = match value.split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix)),
+            None => (value, None),
+        };
+        let address = address.parse::<IpAddr>().map_err(|_| error())?;
+        let max = if address.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) if prefix.bytes().all(|b| b.is_ascii_digit()) => {
+                prefix.parse::<u8>().map_err(|_| error())?
+            }
+            Some(_) => return Err(error()),
+            None => max,
+        };
+        if prefix > max {
+            return Err(error());
+        }
+        Ok((address, prefix))
+    }
+
+    /// Whether `peer`, the address of the connected client, is a trusted proxy
+    pub fn contains(&self, peer: IpAddr) -> bool {
+        let peer = peer.to_canonical();
+        self.0
+            .iter()
+            .any(|(address, prefix)| match (address, peer) {
+                (IpAddr::V4(address), IpAddr::V4(peer)) => {
+                    let mask = u32::MAX.checked_shl(32 - *prefix as u32).unwrap_or(0);
+                    u32::from(*address) & mask == u32::from(peer) & mask
+                }
+                (IpAddr::V6(address), IpAddr::V6(peer)) => {
+                    let mask = u128::MAX.checked_shl(128 - *prefix as u32).unwrap_or(0);
+                    u128::from(*address) & mask == u128::from(peer) & mask
+                }
+                _ => false,
+            })
+    }
+
+    /// Whether the request arrived over HTTPS
+    ///
+    /// `connection_is_https` is the scheme of the connection to the application. When `peer` is a trusted proxy, the `proto` of the last element of the `Forwarded` header, or else the last value of `X-Forwarded-Proto`, is the scheme the client used instead. The proxy appends the last one, earlier ones come from the client or from proxies in front of it and can't be trusted.
+    pub fn is_https(
+        &self,
+        connection_is_https: bool,
+        peer: Option<IpAddr>,
+        forwarded: Option<&str>,
+        x_forwarded_proto: Option<&str>,
+    ) -> bool {
+        if !peer.is_some_and(|peer| self.contains(peer)) {
+            return connection_is_https;
+        }
+        let forwarded_proto = forwarded.and_then(|forwarded| {
+            forwarded
+                .split(',')
+                .next_back()?
+                .split(';')
+                .filter_map(|pair| pair.split_once('='))
+                .find(|(name, _)| name.trim().eq_ignore_ascii_case("proto"))
+                .map(|(_, value)| value.trim().trim_matches('"'))
+        });
+        let proto = forwarded_proto.or_else(|| {
+            x_forwarded_proto.and_then(|proto| proto.split(',').next_back().map(str::trim))
+        });
+        match proto {
+            Some(proto) => proto.eq_ignore_ascii_case("https"),
+            None => connection_is_https,
+        }
+    }
+}
+
+/// Headers of a [`Helmet`] prepared for a framework adapter, see [`Helmet::compile`]
+///
+/// Adapters convert the names and values into the header types of their framework once, when the middleware is created.
+#[derive(Clone, Debug)]
+pub struct CompiledHelmet {
+    /// Each header name once, in the order the names were first added, with its conflict strategy and the responses it is sent on.
+    pub names: Vec<(String, ConflictStrategy, HeaderScope)>,
+    /// Every header, in the order they were added.
+    pub headers: Vec<CompiledHeader>,
+}
+
+/// Header of [`CompiledHelmet`]
+#[derive(Clone, Debug)]
+pub struct CompiledHeader {
+    pub name: String,
+    pub value: CompiledValue,
+    /// Condition the request and response have to match for the header to be sent.
+    pub condition: Option<Condition>,
+}
+
+/// Value of a [`CompiledHeader`]
+#[derive(Clone, Debug)]
+pub enum CompiledValue {
+    /// Value sent with every response.
+    Fixed(String),
+    /// Value rendered with the nonce of each response.
+    Nonce(NonceTemplate),
+    /// Value that changes over time, of the header at this index of [`Helmet::iter`].
+    Timed(usize),
+}
+
+/// Header overrides for the requests whose path matches a pattern, see [`Helmet::path`]
+///
+/// Headers added to the rule replace the headers with the same name, removed headers and every header of [`PathRule::exclude_all`] aren't sent.
+#[derive(Clone)]
+pub struct PathRule {
+    headers: Helmet,
+    removed: Vec<String>,
+    exclude_all: bool,
+}
+
+impl PathRule {
+    /// Create a rule that sends the headers of the base configuration
+    pub fn new() -> Self {
+        Self {
+            headers: Helmet::new(),
+            removed: Vec::new(),
+            exclude_all: false,
+        }
+    }
+
+    /// Create a rule that sends no headers at all, e.g. for health checks and metrics
+    pub fn exclude_all() -> Self {
+        Self {
+            exclude_all: true,
+            ..Self::new()
+        }
+    }
+
+    /// Add a header, replacing every header of the base configuration with the same name
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(mut self, header: impl Header + 'static) -> Self {
+        self.headers = self.headers.add(header);
+        self
+    }
+
+    /// Don't send the headers with the given name. Header names are case-insensitive.
+    pub fn remove(mut self, name: &str) -> Self {
+        if !self
+            .removed
+            .iter()
+            .any(|removed| removed.eq_ignore_ascii_case(name))
+        {
+            self.removed.push(name.to_string());
+        }
+        self
+    }
+
+    // Configuration for the requests matching the rule
+    fn apply(&self, base: &Helmet) -> Helmet {
+        let mut helmet = base.clone();
+        helmet.path_rules.clear();
+        if self.exclude_all {
+            helmet.headers.clear();
+            return helmet;
+        }
+        helmet = helmet.merge(self.headers.clone(), MergePrecedence::Other);
+        helmet.headers.retain(|header| {
+            !self
+                .removed
+                .iter()
+                .any(|removed| removed.eq_ignore_ascii_case(header.name()))
+        });
+        helmet
+    }
+}
+
+impl Default for PathRule {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl std::fmt::Debug for PathRule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PathRule")
+            .field("headers", &Headers(&self.headers.headers))
+            .field("removed", &self.removed)
+            .field("exclude_all", &self.exclude_all)
+            .finish()
+    }
+}
+
+/// Parsed path pattern of a [`PathRule`]
+///
+/// Patterns match the request path segment by segment. `*` matches any characters within a segment, and a trailing `/*` matches the path and everything below it. The segments of the request path are percent-decoded first, as routers decode them before matching routes, so an encoded path can't bypass a rule.
+///
+/// ```
+/// use helmet_core::PathPattern;
+///
+/// let pattern = PathPattern::parse("/admin/*").unwrap();
+/// assert!(pattern.matches("/admin"));
+/// assert!(pattern.matches("/admin/users/1"));
+/// assert!(!pattern.matches("/administrator"));
+/// assert!(pattern.matches("/%61dmin/users"));
+///
+/// let pattern = PathPattern::parse("/static/*.js").unwrap();
+/// assert!(pattern.matches("/static/app.js"));
+/// assert!(!pattern.matches("/static/js/app.js"));
+///
+/// let pattern = PathPattern::parse("/healthz").unwrap();
+/// assert!(pattern.matches("/healthz"));
+/// assert!(!pattern.matches("/healthz/ready"));
+///
+/// assert!(PathPattern::parse("healthz").is_err());
+/// assert!(PathPattern::parse("/assets/**").is_err());
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathPattern {
+    segments: Vec<String>,
+    prefix: bool,
+}
+
+impl PathPattern {
+    /// Parse a pattern, which must start with `/`
+    pub fn parse(pattern: &str) -> Result<Self, HelmetError> {
+        let invalid = || HelmetError::InvalidPathPattern {
+            pattern: pattern.to_string(),
+        };
+        let path = pattern.strip_prefix('/').ok_or_else(invalid)?;
+        if path.contains("**") {
+            return Err(invalid());
+        }
+        let mut segments: Vec<String> = path.split('/').map(str::to_string).collect();
+        let prefix = segments.last().is_some_and(|segment| segment == "*");
+        if prefix {
+            segments.pop();
+        }
+        Ok(Self { segments, prefix })
+    }
+
+    /// Whether the pattern matches the request path, without query string
+    pub fn matches(&self, path: &str) -> bool {
+        let Some(path) = path.strip_prefix('/') else {
+            return false;
+        };
+        let mut segments = path.split('/');
+        for pattern in self.segments.iter() {
+            match segments.next() {
+                Some(segment) if glob_matches(pattern.as_bytes(), &percent_decode(segment)) => {}
+                _ => return false,
+            }
+        }
+        self.prefix || segments.next().is_none()
+    }
+}
+
+// Decode `%XX` escapes of a path segment like the ntex router, keeping invalid escapes as they are.
+fn percent_decode(segment: &str) -> Cow<'_, [u8]> {
+    let bytes = segment.as_bytes();
+    if !bytes.contains(&b'%') {
+        return Cow::Borrowed(bytes);
+    }
+    let hex = |b: u8| (b as char).to_digit(16).map(|digit| digit as u8);
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let escaped = (bytes[i] == b'%')
+            .then(|| Some((hex(*bytes.get(i + 1)?)?, hex(*bytes.get(i + 2)?)?)))
+            .flatten();
+        match escaped {
+            Some((high, low)) => {
+                decoded.push(high << 4 | low);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+    Cow::Owned(decoded)
+}
+
+// `*` matches any sequence of bytes, backtracking to the last `*` on a mismatch.
+fn glob_matches(pattern: &[u8], text: &[u8]) -> bool {
+    let (mut p, mut t) = (0, 0);
+    let mut star = None;
+    while t < text.len() {
+        if p < pattern.len() && pattern[p] == b'*' {
+            star = Some((p, t));
+            p += 1;
+        } else if p < pattern.len() && pattern[p] == text[t] {
+            p += 1;
+            t += 1;
+        } else if let Some((star_p, star_t)) = star {
+            p = star_p + 1;
+            t = star_t + 1;
+            star = Some((star_p, star_t + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|&b| b == b'*')
+}
+
+/// Helmet security headers middleware for ntex services
+///
+/// # Examples
+///
+/// ```
+/// use helmet_core::Helmet;
+///
+/// let helmet = Helmet::default();
+/// ```
+///
+/// ## Adding custom headers
+///
+/// ```
+/// use helmet_core::{Helmet, StrictTransportSecurity};
+///
+/// let helmet = Helmet::new()
+///    .add(StrictTransportSecurity::new().max_age(31536000).include_sub_domains());
+/// ```
+///
+/// ## Tweaking the defaults
+///
+/// Each header name is sent once, unless the header allows multiple values and is added with [`Helmet::add_additional`], see [`Header::allows_multiple`]. Adding a header replaces the header with the same name, so the defaults can be adjusted without rebuilding them.
+///
+/// ```
+/// use helmet_core::{Helmet, PreloadMaxAge, StrictTransportSecurity, XXSSProtection};
+///
+/// let helmet = Helmet::default()
+///    .add(StrictTransportSecurity::preloadable(PreloadMaxAge::TWO_YEARS).include_sub_domains().preload())
+///    .remove::<XXSSProtection>();
+///
+/// assert_eq!(
+///     helmet.get::<StrictTransportSecurity>().unwrap().to_string(),
+///     "max-age=63072000; includeSubDomains; preload"
+/// );
+/// assert!(helmet.get::<XXSSProtection>().is_none());
+/// ```
+///
+/// ## Logging the configuration
+///
+/// `Debug` prints the headers as name/value pairs, in the order they are sent.
+///
+/// ```
+/// use helmet_core::{Helmet, XFrameOptions};
+///
+/// let helmet = Helmet::new().add(XFrameOptions::deny());
+///
+/// assert_eq!(
+///     format!("{:?}", helmet.clone()),
+///     "Helmet { headers: {\"X-Frame-Options\": \"DENY\"}, conflict_strategy: KeepExisting, header_conflict_strategies: [], header_scopes: [], development_mode: Off, hsts_https_only: false, trusted_proxies: [], path_rules: [] }"
+/// );
+/// ```
+#[derive(Clone)]
+pub struct Helmet {
+    headers: Vec<Box<dyn Header>>,
+    conflict_strategy: Option<ConflictStrategy>,
+    header_conflict_strategies: Vec<(String, ConflictStrategy)>,
+    header_scopes: Vec<(String, HeaderScope)>,
+    development_mode: Option<DevelopmentMode>,
+    hsts_https_only: Option<bool>,
+    trusted_proxies: Vec<String>,
+    // pattern, the pattern parsed when the rule was added, `None` when invalid, and rule
+    path_rules: Vec<(String, Option<PathPattern>, PathRule)>,
+}
+
+impl Helmet {
+    /// Create new `Helmet` instance without any headers applied
+    pub fn new() -> Self {
+        Self {
+            headers: Vec::new(),
+            conflict_strategy: None,
+            header_conflict_strategies: Vec::new(),
+            header_scopes: Vec::new(),
+            development_mode: None,
+            hsts_https_only: None,
+            trusted_proxies: Vec::new(),
+            path_rules: Vec::new(),
+        }
+    }
+
+    /// Preset for JSON APIs, which never render HTML
+    ///
+    /// Responses can't load any resources or be framed, so an API response opened in a browser can't run scripts. Headers that only apply to HTML documents, e.g. `Cross-Origin-Opener-Policy`, `Referrer-Policy` or `X-XSS-Protection`, are left out. `Content-Security-Policy` and `X-Frame-Options` are sent on every response, including `application/json`, see [`Helmet::header_scope`].
+    ///
+    /// ```text
+    /// Content-Security-Policy: default-src 'none'; frame-ancestors 'none'
+    /// Cross-Origin-Resource-Policy: same-origin
+    /// Strict-Transport-Security: max-age=15552000; includeSubDomains
+    /// X-Content-Type-Options: nosniff
+    /// X-Frame-Options: DENY
+    /// ```
+    pub fn api() -> Self {
+        Self::new()
+            .add(
+                ContentSecurityPolicy::new()
+                    .default_src(vec!["'none'"])
+                    .frame_ancestors(vec!["'none'"]),
+            )
+            .add(CrossOriginResourcePolicy::same_origin())
+            .add(
+                StrictTransportSecurity::new()
+                    .max_age(15552000)
+                    .include_sub_domains(),
+            )
+            .add(XContentTypeOptions::nosniff())
+            .add(XFrameOptions::deny())
+            .header_scope("Content-Security-Policy", HeaderScope::Any)
+            .header_scope("X-Frame-Options", HeaderScope::Any)
+    }
+
+    /// Preset for single-page applications, whose `index.html` is rendered per request
+    ///
+    /// Scripts are allowed by a per-request nonce and `'strict-dynamic'`, so the bundles loaded by the nonced entry script can load further chunks, without allowing arbitrary same-origin scripts. The nonce has to be rendered into the `<script>` tags of `index.html`, see the framework adapters. `'self'` is a fallback for browsers that don't support `'strict-dynamic'`. `Cross-Origin-Opener-Policy` allows popups, as used for signing in with OAuth providers.
+    ///
+    /// ```text
+    /// Content-Security-Policy: default-src 'self'; base-uri 'none'; font-src 'self' https: data:; form-action 'self'; frame-ancestors 'self'; img-src 'self' data: blob:; object-src 'none'; script-src 'self' 'strict-dynamic' 'nonce-<random>'; script-src-attr 'none'; style-src 'self' https: 'unsafe-inline'; upgrade-insecure-requests
+    /// Cross-Origin-Opener-Policy: same-origin-allow-popups
+    /// Cross-Origin-Resource-Policy: same-origin
+    /// Origin-Agent-Cluster: ?1
+    /// Referrer-Policy: no-referrer
+    /// Strict-Transport-Security: max-age=15552000; includeSubDomains
+    /// X-Content-Type-Options: nosniff
+    /// X-DNS-Prefetch-Control: off
+    /// X-Download-Options: noopen
+    /// X-Frame-Options: SAMEORIGIN
+    /// X-Permitted-Cross-Domain-Policies: none
+    /// X-XSS-Protection: 0
+    /// ```
+    pub fn spa() -> Self {
+        Self::default()
+            .replace(
+                ContentSecurityPolicy::new()
+                    .default_src(vec!["'self'"])
+                    .base_uri(vec!["'none'"])
+                    .font_src(vec!["'self'", "https:", "data:"])
+                    .form_action(vec!["'self'"])
+                    .frame_ancestors(vec!["'self'"])
+                    .img_src(vec!["'self'", "data:", "blob:"])
+                    .object_src(vec!["'none'"])
+                    .script_src(vec!["'self'", "'strict-dynamic'"])
+                    .script_src_attr(vec!["'none'"])
+                    .style_src(vec!["'self'", "https:", "'unsafe-inline'"])
+                    .upgrade_insecure_requests()
+                    .script_nonce(),
+            )
+            .add(CrossOriginOpenerPolicy::same_origin_allow_popups())
+    }
+
+    /// Preset for static sites, served as files without per-request rendering
+    ///
+    /// Static files can't carry per-request nonces, and their scripts, styles and fonts are known at build time, so only same-origin resources are allowed, without `'unsafe-inline'` styles or fonts from any `https:` origin.
+    ///
+    /// ```text
+    /// Content-Security-Policy: default-src 'self'; base-uri 'self'; font-src 'self' data:; form-action 'self'; frame-ancestors 'self'; img-src 'self' data:; object-src 'none'; script-src 'self'; script-src-attr 'none'; style-src 'self'; upgrade-insecure-requests
+    /// Cross-Origin-Opener-Policy: same-origin
+    /// Cross-Origin-Resource-Policy: same-origin
+    /// Origin-Agent-Cluster: ?1
+    /// Referrer-Policy: no-referrer
+    /// Strict-Transport-Security: max-age=15552000; includeSubDomains
+    /// X-Content-Type-Options: nosniff
+    /// X-DNS-Prefetch-Control: off
+    /// X-Download-Options: noopen
+    /// X-Frame-Options: SAMEORIGIN
+    /// X-Permitted-Cross-Domain-Policies: none
+    /// X-XSS-Protection: 0
+    /// ```
+    pub fn static_site() -> Self {
+        Self::default().replace(
+            ContentSecurityPolicy::new()
+                .default_src(vec!["'self'"])
+                .base_uri(vec!["'self'"])
+                .font_src(vec!["'self'", "data:"])
+                .form_action(vec!["'self'"])
+                .frame_ancestors(vec!["'self'"])
+                .img_src(vec!["'self'", "data:"])
+                .object_src(vec!["'none'"])
+                .script_src(vec!["'self'"])
+                .script_src_attr(vec!["'none'"])
+                .style_src(vec!["'self'"])
+                .upgrade_insecure_requests(),
+        )
+    }
+
+    /// Preset for WebAssembly applications
+    ///
+    /// `'wasm-unsafe-eval'` allows compiling WebAssembly modules without allowing `eval()` for JavaScript. The document is cross-origin isolated by `Cross-Origin-Embedder-Policy` and `Cross-Origin-Opener-Policy`, which is required for `SharedArrayBuffer` and therefore WebAssembly threads. Cross-origin resources have to opt in to being embedded with `Cross-Origin-Resource-Policy` or CORS.
+    ///
+    /// ```text
+    /// Content-Security-Policy: default-src 'self'; base-uri 'self'; font-src 'self' https: data:; form-action 'self'; frame-ancestors 'self'; img-src 'self' data:; object-src 'none'; script-src 'self' 'wasm-unsafe-eval'; script-src-attr 'none'; style-src 'self' https: 'unsafe-inline'; upgrade-insecure-requests
+    /// Cross-Origin-Opener-Policy: same-origin
+    /// Cross-Origin-Resource-Policy: same-origin
+    /// Origin-Agent-Cluster: ?1
+    /// Referrer-Policy: no-referrer
+    /// Strict-Transport-Security: max-age=15552000; includeSubDomains
+    /// X-Content-Type-Options: nosniff
+    /// X-DNS-Prefetch-Control: off
+    /// X-Download-Options: noopen
+    /// X-Frame-Options: SAMEORIGIN
+    /// X-Permitted-Cross-Domain-Policies: none
+    /// X-XSS-Protection: 0
+    /// Cross-Origin-Embedder-Policy: require-corp
+    /// ```
+    pub fn wasm_app() -> Self {
+        Self::default()
+            .replace(ContentSecurityPolicy::default().override_directives(
+                ContentSecurityPolicy::new().script_src(vec!["'self'", "'wasm-unsafe-eval'"]),
+            ))
+            .add(CrossOriginEmbedderPolicy::require_corp())
+    }
+
+    /// Preset with the strictest setting of every header, as a starting point to relax from
+    ///
+    /// Only same-origin resources are allowed, without inline styles, and DOM XSS sinks require Trusted Types. The page can't be framed, is cross-origin isolated and denies access to powerful browser features. HSTS is sent with `preload`; only use this preset once every subdomain supports HTTPS, as removing a domain from browser preload lists takes months.
+    ///
+    /// ```text
+    /// Content-Security-Policy: default-src 'none'; base-uri 'none'; connect-src 'self'; font-src 'self'; form-action 'self'; frame-ancestors 'none'; img-src 'self'; manifest-src 'self'; object-src 'none'; script-src 'self'; script-src-attr 'none'; style-src 'self'; require-trusted-types-for 'script'; upgrade-insecure-requests
+    /// Cross-Origin-Embedder-Policy: require-corp
+    /// Cross-Origin-Opener-Policy: same-origin
+    /// Cross-Origin-Resource-Policy: same-origin
+    /// Origin-Agent-Cluster: ?1
+    /// Permissions-Policy: accelerometer=(), browsing-topics=(), camera=(), display-capture=(), geolocation=(), gyroscope=(), hid=(), interest-cohort=(), magnetometer=(), microphone=(), midi=(), payment=(), serial=(), usb=(), xr-spatial-tracking=()
+    /// Referrer-Policy: no-referrer
+    /// Strict-Transport-Security: max-age=63072000; includeSubDomains; preload
+    /// X-Content-Type-Options: nosniff
+    /// X-DNS-Prefetch-Control: off
+    /// X-Download-Options: noopen
+    /// X-Frame-Options: DENY
+    /// X-Permitted-Cross-Domain-Policies: none
+    /// X-XSS-Protection: 0
+    /// ```
+    pub fn maximum() -> Self {
+        let permissions_policy = [
+            PermissionsPolicyFeature::Accelerometer,
+            PermissionsPolicyFeature::BrowsingTopics,
+            PermissionsPolicyFeature::Camera,
+            PermissionsPolicyFeature::DisplayCapture,
+            PermissionsPolicyFeature::Geolocation,
+            PermissionsPolicyFeature::Gyroscope,
+            PermissionsPolicyFeature::Hid,
+            PermissionsPolicyFeature::InterestCohort,
+            PermissionsPolicyFeature::Magnetometer,
+            PermissionsPolicyFeature::Microphone,
+            PermissionsPolicyFeature::Midi,
+            PermissionsPolicyFeature::Payment,
+            PermissionsPolicyFeature::Serial,
+            PermissionsPolicyFeature::Usb,
+            PermissionsPolicyFeature::XrSpatialTracking,
+        ]
+        .into_iter()
+        .fold(PermissionsPolicy::new(), PermissionsPolicy::deny);
+
+        Self::new()
+            .add(
+                ContentSecurityPolicy::new()
+                    .default_src(vec!["'none'"])
+                    .base_uri(vec!["'none'"])
+                    .connect_src(vec!["'self'"])
+                    .font_src(vec!["'self'"])
+                    .form_action(vec!["'self'"])
+                    .frame_ancestors(vec!["'none'"])
+                    .img_src(vec!["'self'"])
+                    .manifest_src(vec!["'self'"])
+                    .object_src(vec!["'none'"])
+                    .script_src(vec!["'self'"])
+                    .script_src_attr(vec!["'none'"])
+                    .style_src(vec!["'self'"])
+                    .require_trusted_types_for(vec!["'script'"])
+                    .upgrade_insecure_requests(),
+            )
+            .add(CrossOriginEmbedderPolicy::require_corp())
+            .add(CrossOriginOpenerPolicy::same_origin())
+            .add(CrossOriginResourcePolicy::same_origin())
+            .add(OriginAgentCluster::new(true))
+            .add(permissions_policy)
+            .add(ReferrerPolicy::no_referrer())
+            .add(
+                StrictTransportSecurity::preloadable(PreloadMaxAge::TWO_YEARS)
+                    .include_sub_domains()
+                    .preload(),
+            )
+            .add(XContentTypeOptions::nosniff())
+            .add(XDNSPrefetchControl::off())
+            .add(XDownloadOptions::noopen())
+            .add(XFrameOptions::deny())
+            .add(XPermittedCrossDomainPolicies::none())
+            .add(XXSSProtection::off())
+    }
+
+    /// Add header to the middleware
+    ///
+    /// Replaces every header with the same name and condition, even if the header allows multiple values. A `Content-Security-Policy` replaces the enforced policy and a report-only policy replaces the report-only policy, since they have different header names.
+    ///
+    /// ```
+    /// use helmet_core::{ContentSecurityPolicy, Helmet};
+    ///
+    /// let helmet = Helmet::default()
+    ///     .add(ContentSecurityPolicy::default().img_src(vec!["'self'", "https:"]));
+    ///
+    /// assert_eq!(helmet.iter().filter(|header| header.name() == "Content-Security-Policy").count(), 1);
+    /// ```
+    #[allow(clippy::should_implement_trait)]
+    pub fn add(mut self, header: impl Header + 'static) -> Self {
+        self.insert(Box::new(header));
+        self
+    }
+
+    /// Add a header next to the headers with the same name, if the header allows multiple values, see [`Header::allows_multiple`]
+    ///
+    /// Browsers enforce every `Content-Security-Policy` they receive, so an additional policy can only make the page stricter. Headers that don't allow multiple values replace the header with the same name, like [`Helmet::add`].
+    ///
+    /// ```
+    /// use helmet_core::{ContentSecurityPolicy, Helmet};
+    ///
+    /// let helmet = Helmet::default()
+    ///     .add_additional(ContentSecurityPolicy::new().script_src(vec!["'none'"]));
+    ///
+    /// assert_eq!(helmet.iter().filter(|header| header.name() == "Content-Security-Policy").count(), 2);
+    /// ```
+    pub fn add_additional(mut self, header: impl Header + 'static) -> Self {
+        if header.allows_multiple() {
+            self.headers.push(Box::new(header));
+        } else {
+            self.insert(Box::new(header));
+        }
+        self
+    }
+
+    /// Replace every header with the same name and condition, same as [`Helmet::add`]
+    ///
+    /// ```
+    /// use helmet_core::{ContentSecurityPolicy, Helmet};
+    ///
+    /// let helmet = Helmet::default()
+    ///     .replace(ContentSecurityPolicy::new().default_src(vec!["'none'"]));
+    ///
+    /// assert_eq!(helmet.iter().filter(|header| header.name() == "Content-Security-Policy").count(), 1);
+    /// ```
+    pub fn replace(self, header: impl Header + 'static) -> Self {
+        self.add(header)
+    }
+
+    /// Add a header that is only sent when `condition` matches the request and response, see [`Condition`]
+    ///
+    /// Replaces a header with the same name and condition, so a header can be sent with different values depending on the response. Headers with the same name and other conditions are kept, and each is sent when its condition matches.
+    ///
+    /// ```
+    /// use helmet_core::{Condition, ContentSecurityPolicy, Helmet, StrictTransportSecurity};
+    ///
+    /// let helmet = Helmet::default()
+    ///     // error pages don't load any resources
+    ///     .remove::<ContentSecurityPolicy>()
+    ///     .add_when(ContentSecurityPolicy::default(), !Condition::error())
+    ///     .add_when(ContentSecurityPolicy::new().default_src(vec!["'none'"]), Condition::error())
+    ///     // nothing to protect on WebSocket upgrades and revalidated responses
+    ///     .when("Strict-Transport-Security", !Condition::status(101).or(Condition::status(304)));
+    ///
+    /// assert_eq!(helmet.iter().filter(|header| header.condition().is_some()).count(), 3);
+    /// ```
+    pub fn add_when(self, header: impl Header + 'static, condition: Condition) -> Self {
+        self.add(Conditional::new(header, condition))
+    }
+
+    /// Only send the headers with the given name when `condition` matches, combined with the condition they already have. Header names are case-insensitive.
+    pub fn when(mut self, name: &str, condition: Condition) -> Self {
+        for header in self.headers.iter_mut() {
+            if header.name().eq_ignore_ascii_case(name) {
+                let existing = dyn_clone::clone_box(header.as_ref());
+                *header = Box::new(Conditional::from_boxed(existing, condition.clone()));
+            }
+        }
+        self
+    }
+
+    /// Remove every header of the given type
+    pub fn remove<T: Header>(mut self) -> Self {
+        self.headers
+            .retain(|header| !(header.as_ref() as &dyn Any).is::<T>());
+        self
+    }
+
+    /// Get the first header of the given type
+    ///
+    /// ```
+    /// use helmet_core::{Helmet, ReferrerPolicy, XFrameOptions};
+    ///
+    /// let helmet = Helmet::default();
+    ///
+    /// assert_eq!(helmet.get::<XFrameOptions>().unwrap().to_string(), "SAMEORIGIN");
+    /// assert!(Helmet::new().get::<ReferrerPolicy>().is_none());
+    /// ```
+    pub fn get<T: Header>(&self) -> Option<&T> {
+        self.headers
+            .iter()
+            .find_map(|header| (header.as_ref() as &dyn Any).downcast_ref::<T>())
+    }
+
+    /// Get the first header of the given type, to modify it in place
+    ///
+    /// ```
+    /// use helmet_core::{ContentSecurityPolicy, Helmet};
+    ///
+    /// let mut helmet = Helmet::default();
+    ///
+    /// if let Some(csp) = helmet.get_mut::<ContentSecurityPolicy>() {
+    ///     *csp = csp.clone().script_nonce();
+    /// }
+    /// ```
+    pub fn get_mut<T: Header>(&mut self) -> Option<&mut T> {
+        self.headers
+            .iter_mut()
+            .find_map(|header| (header.as_mut() as &mut dyn Any).downcast_mut::<T>())
+    }
+
+    /// Iterate over the headers, in the order they were added
+    pub fn iter(&self) -> impl Iterator<Item = &dyn Header> {
+        self.headers.iter().map(|header| header.as_ref())
+    }
+
+    // Puts the header in place of the first header with the same name and condition, removing the others.
+    fn insert(&mut self, header: Box<dyn Header>) {
+        let name = header.name();
+        let condition = header.condition().cloned();
+        self.put(header, |existing| {
+            existing.name().eq_ignore_ascii_case(name) && existing.condition() == condition.as_ref()
+        });
+    }
+
+    // Puts the header in place of the first header with the same name, whatever its condition, removing the others.
+    fn insert_by_name(&mut self, header: Box<dyn Header>) {
+        let name = header.name();
+        self.put(header, |existing| {
+            existing.name().eq_ignore_ascii_case(name)
+        });
+    }
+
+    fn put(&mut self, header: Box<dyn Header>, same: impl Fn(&dyn Header) -> bool) {
+        match self
+            .headers
+            .iter()
+            .position(|existing| same(existing.as_ref()))
+        {
+            Some(index) => {
+                self.headers[index] = header;
+                let mut position = 0;
+                self.headers.retain(|existing| {
+                    let keep = position <= index || !same(existing.as_ref());
+                    position += 1;
+                    keep
+                });
+            }
+            None => self.headers.push(header),
+        }
+    }
+
+    /// Merge another configuration into this one, e.g. per-service overrides into a shared base profile
+    ///
+    /// Headers only set by `other` are added, headers set by both are resolved by `precedence`. Headers that allow multiple values, e.g. `Content-Security-Policy`, are merged with the first header of the same name; the other values of `other` are added next to it. `Reporting-Endpoints` keeps the endpoints of both configurations. Conflict strategies, header scopes, the development mode, [`Helmet::hsts_https_only`] and path rules for the same pattern set on `other` follow the same precedence, trusted proxies and the other path rules of both configurations are kept.
+    ///
+    /// ```
+    /// use helmet_core::{ContentSecurityPolicy, Header, Helmet, MergePrecedence, XFrameOptions};
+    ///
+    /// let service = Helmet::new().add(XFrameOptions::deny()).add(
+    ///     ContentSecurityPolicy::new()
+    ///         .default_src(vec!["'self'"])
+    ///         .connect_src(vec!["https://api.example.com"]),
+    /// );
+    ///
+    /// let helmet = Helmet::default().merge(service, MergePrecedence::UnionDirectives);
+    ///
+    /// assert_eq!(helmet.get::<XFrameOptions>().unwrap().value(), "DENY");
+    /// let policy = helmet.get::<ContentSecurityPolicy>().unwrap().value();
+    /// assert!(policy.ends_with("; connect-src 'self' https://api.example.com"));
+    /// // the service doesn't restrict framing, so the union doesn't either
+    /// assert!(!policy.contains("frame-ancestors"));
+    /// ```
+    pub fn merge(mut self, other: Helmet, precedence: MergePrecedence) -> Self {
+        let mut merged: Vec<&'static str> = Vec::new();
+        for header in other.headers {
+            let name = header.name();
+            if merged.iter().any(|n| n.eq_ignore_ascii_case(name)) {
+                self.headers.push(header);
+                continue;
+            }
+            // Endpoints are referenced by name from other headers, so both sets are kept.
+            let endpoints = (header.as_ref() as &dyn Any).downcast_ref::<ReportingEndpoints>();
+            if let (Some(base), Some(endpoints)) = (self.get_mut::<ReportingEndpoints>(), endpoints)
+            {
+                for (endpoint, url) in endpoints.0.iter() {
+                    if precedence != MergePrecedence::Base
+                        || !base.0.iter().any(|(existing, _)| existing == endpoint)
+                    {
+                        *base = base.clone().endpoint(endpoint, url);
+                    }
+                }
+                merged.push(name);
+                continue;
+            }
+            let exists = self
+                .headers
+                .iter()
+                .any(|existing| existing.name().eq_ignore_ascii_case(name));
+            if exists && precedence == MergePrecedence::Base {
+                continue;
+            }
+            merged.push(name);
+            if !exists {
+                self.headers.push(header);
+                continue;
+            }
+            let base = self.headers.iter_mut().find_map(|existing| {
+                if existing.name().eq_ignore_ascii_case(name) {
+                    (existing.as_mut() as &mut dyn Any)
+                        .downcast_mut::<ContentSecurityPolicy<'static>>()
+                } else {
+                    None
+                }
+            });
+            let policy =
+                (header.as_ref() as &dyn Any).downcast_ref::<ContentSecurityPolicy<'static>>();
+            match (base, policy, precedence) {
+                (Some(base), Some(policy), MergePrecedence::OverrideDirectives) => {
+                    *base = base.clone().override_directives(policy.clone());
+                }
+                (Some(base), Some(policy), MergePrecedence::UnionDirectives) => {
+                    *base = base.clone().union(policy.clone());
+                }
+                _ => self.insert_by_name(header),
+            }
+        }
+
+        for proxy in other.trusted_proxies {
+            self = self.trusted_proxy(&proxy);
+        }
+        for (pattern, _, rule) in other.path_rules {
+            if precedence != MergePrecedence::Base
+                || !self.path_rules.iter().any(|(p, _, _)| *p == pattern)
+            {
+                self = self.path(&pattern, rule);
+            }
+        }
+        match precedence {
+            MergePrecedence::Base => {
+                self.conflict_strategy = self.conflict_strategy.or(other.conflict_strategy);
+                self.development_mode = self.development_mode.or(other.development_mode);
+                self.hsts_https_only = self.hsts_https_only.or(other.hsts_https_only);
+                for (name, strategy) in other.header_conflict_strategies {
+                    if !self
+                        .header_conflict_strategies
+                        .iter()
+                        .any(|(n, _)| n.eq_ignore_ascii_case(&name))
+                    {
+                        self.header_conflict_strategies.push((name, strategy));
+                    }
+                }
+                for (name, scope) in other.header_scopes {
+                    if !self
+                        .header_scopes
+                        .iter()
+                        .any(|(n, _)| n.eq_ignore_ascii_case(&name))
+                    {
+                        self.header_scopes.push((name, scope));
+                    }
+                }
+            }
+            _ => {
+                self.conflict_strategy = other.conflict_strategy.or(self.conflict_strategy);
+                self.development_mode = other.development_mode.or(self.development_mode);
+                self.hsts_https_only = other.hsts_https_only.or(self.hsts_https_only);
+                for (name, strategy) in other.header_conflict_strategies {
+                    self = self.header_conflict_strategy(&name, strategy);
+                }
+                for (name, scope) in other.header_scopes {
+                    self = self.header_scope(&name, scope);
+                }
+            }
+        }
+        self
+    }
+
+    /// Register a reporting endpoint, which is sent in the `Reporting-Endpoints` header
+    ///
+    /// The endpoint can then be referenced by name, e.g. from the `report-to` directive of `Content-Security-Policy`, or the `report-to` parameter of `Cross-Origin-Opener-Policy` and `Cross-Origin-Embedder-Policy`.
+    ///
+    /// ```
+    /// use helmet_core::{ContentSecurityPolicy, CrossOriginOpenerPolicy, Header, Helmet, ReportingEndpoints};
+    ///
+    /// let helmet = Helmet::new()
+    ///     .reporting_endpoint("csp", "https://example.com/csp-reports")
+    ///     .reporting_endpoint("coop", "https://example.com/coop-reports")
+    ///     .add(ContentSecurityPolicy::default().report_to("csp"))
+    ///     .add(CrossOriginOpenerPolicy::same_origin().report_to("coop"));
+    ///
+    /// assert_eq!(
+    ///     helmet.get::<ReportingEndpoints>().unwrap().value(),
+    ///     "csp=\"https://example.com/csp-reports\", coop=\"https://example.com/coop-reports\""
+    /// );
+    /// ```
+    pub fn reporting_endpoint(mut self, name: &str, url: &str) -> Self {
+        match self.get_mut::<ReportingEndpoints>() {
+            Some(reporting_endpoints) => {
+                *reporting_endpoints = reporting_endpoints.clone().endpoint(name, url);
+                self
+            }
+            None => self.add(ReportingEndpoints::new().endpoint(name, url)),
+        }
+    }
+
+    /// Set how headers are applied when the response already has a header with the same name, e.g. one set by the handler
+    ///
+    /// Defaults to [`ConflictStrategy::KeepExisting`], so handlers can override the headers set by Helmet.
+    ///
+    /// ```
+    /// use helmet_core::{ConflictStrategy, Helmet};
+    ///
+    /// let helmet = Helmet::default().conflict_strategy(ConflictStrategy::Overwrite);
+    ///
+    /// assert_eq!(helmet.conflict_strategy_for("X-Frame-Options"), ConflictStrategy::Overwrite);
+    /// ```
+    pub fn conflict_strategy(mut self, strategy: ConflictStrategy) -> Self {
+        self.conflict_strategy = Some(strategy);
+        self
+    }
+
+    /// Set the conflict strategy of a single header, taking precedence over [`Helmet::conflict_strategy`]
+    ///
+    /// ```
+    /// use helmet_core::{ConflictStrategy, Helmet};
+    ///
+    /// let helmet = Helmet::default()
+    ///     .header_conflict_strategy("Content-Security-Policy", ConflictStrategy::Append);
+    ///
+    /// assert_eq!(helmet.conflict_strategy_for("content-security-policy"), ConflictStrategy::Append);
+    /// assert_eq!(helmet.conflict_strategy_for("X-Frame-Options"), ConflictStrategy::KeepExisting);
+    /// ```
+    pub fn header_conflict_strategy(mut self, name: &str, strategy: ConflictStrategy) -> Self {
+        self.header_conflict_strategies
+            .retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+        self.header_conflict_strategies
+            .push((name.to_string(), strategy));
+        self
+    }
+
+    /// Get the conflict strategy of the header with the given name. Header names are case-insensitive.
+    pub fn conflict_strategy_for(&self, name: &str) -> ConflictStrategy {
+        self.header_conflict_strategies
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map_or(
+                self.conflict_strategy.unwrap_or_default(),
+                |(_, strategy)| *strategy,
+            )
+    }
+
+    /// Set the responses a header is sent on, taking precedence over [`Header::scope`]
+    ///
+    /// E.g. `X-Frame-Options` is only sent on documents by default, which leaves out `application/json` responses an API may return.
+    ///
+    /// ```
+    /// use helmet_core::{Header, HeaderScope, Helmet, XFrameOptions};
+    ///
+    /// let helmet = Helmet::default().header_scope("X-Frame-Options", HeaderScope::Any);
+    ///
+    /// assert_eq!(helmet.scope_for(&XFrameOptions::deny()), HeaderScope::Any);
+    /// assert_eq!(Helmet::default().scope_for(&XFrameOptions::deny()), HeaderScope::Document);
+    /// ```
+    pub fn header_scope(mut self, name: &str, scope: HeaderScope) -> Self {
+        self.header_scopes
+            .retain(|(n, _)| !n.eq_ignore_ascii_case(name));
+        self.header_scopes.push((name.to_string(), scope));
+        self
+    }
+
+    /// Get the responses `header` is sent on, see [`Helmet::header_scope`]
+    pub fn scope_for(&self, header: &dyn Header) -> HeaderScope {
+        self.header_scopes
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(header.name()))
+            .map_or(header.scope(), |(_, scope)| *scope)
+    }
+
+    /// Set when framework adapters send the headers of [`Helmet::relaxed_for_development`] instead, defaults to [`DevelopmentMode::Off`]
+    ///
+    /// `Strict-Transport-Security` and `upgrade-insecure-requests` pin `localhost` to HTTPS in the browser, which breaks every other project served on `localhost` over HTTP. [`DevelopmentMode::Loopback`] keeps the headers for requests from other machines and requests forwarded by a proxy.
+    ///
+    /// A reverse proxy on the same machine that connects to the application over a loopback address, e.g. nginx with `proxy_pass http://127.0.0.1:8080`, must add `X-Forwarded-For` or `Forwarded`, otherwise every production response loses both headers. Prefer enabling [`DevelopmentMode::On`] in development builds only when that can't be guaranteed.
+    ///
+    /// ```
+    /// use helmet_core::{DevelopmentMode, Helmet};
+    ///
+    /// let helmet = Helmet::default().development(DevelopmentMode::Loopback);
+    ///
+    /// assert_eq!(helmet.development_mode(), DevelopmentMode::Loopback);
+    /// ```
+    pub fn development(mut self, mode: DevelopmentMode) -> Self {
+        self.development_mode = Some(mode);
+        self
+    }
+
+    /// Get the development mode, see [`Helmet::development`]
+    pub fn development_mode(&self) -> DevelopmentMode {
+        self.development_mode.unwrap_or_default()
+    }
+
+    /// Only send `Strict-Transport-Security` in responses to requests that arrived over HTTPS
+    ///
+    /// Browsers ignore the header over plain HTTP, as required by RFC 6797, and security scanners flag it. Framework adapters tell the scheme from the connection, or from the `Forwarded` and `X-Forwarded-Proto` headers set by a [trusted proxy](Helmet::trusted_proxy).
+    ///
+    /// ```
+    /// use helmet_core::Helmet;
+    ///
+    /// let helmet = Helmet::default().hsts_https_only(true).trusted_proxy("10.0.0.0/8");
+    ///
+    /// assert!(helmet.is_hsts_https_only());
+    /// ```
+    pub fn hsts_https_only(mut self, enabled: bool) -> Self {
+        self.hsts_https_only = Some(enabled);
+        self
+    }
+
+    /// Whether `Strict-Transport-Security` is only sent over HTTPS, see [`Helmet::hsts_https_only`]
+    pub fn is_hsts_https_only(&self) -> bool {
+        self.hsts_https_only.unwrap_or_default()
+    }
+
+    /// Trust the `Forwarded` and `X-Forwarded-Proto` headers of requests from `proxy`, an IP address or a range in CIDR notation, e.g. `10.0.0.0/8` or `fd00::/8`
+    ///
+    /// Only add the proxies terminating TLS in front of the application, anyone else could claim a request arrived over HTTPS. Invalid values are reported by [`Helmet::validate`].
+    pub fn trusted_proxy(mut self, proxy: &str) -> Self {
+        if !self
+            .trusted_proxies
+            .iter()
+            .any(|existing| existing == proxy)
+        {
+            self.trusted_proxies.push(proxy.to_string());
+        }
+        self
+    }
+
+    /// Get the trusted proxies, see [`Helmet::trusted_proxy`]
+    pub fn trusted_proxies(&self) -> Result<TrustedProxies, HelmetError> {
+        self.trusted_proxies
+            .iter()
+            .map(|proxy| TrustedProxies::parse(proxy))
+            .collect::<Result<_, _>>()
+            .map(TrustedProxies)
+    }
+
+    /// Send different headers to the requests whose path matches `pattern`, see [`PathPattern`] for the syntax
+    ///
+    /// Rules are checked in the order they were added and the first matching rule applies, requests matching no rule get the headers of this configuration. Adding a rule for a pattern that already has one replaces it. Invalid patterns are reported by [`Helmet::validate`].
+    ///
+    /// ```
+    /// use helmet_core::{ContentSecurityPolicy, Header, Helmet, PathRule, XFrameOptions};
+    ///
+    /// let helmet = Helmet::default()
+    ///     .path("/admin/*", PathRule::new().add(XFrameOptions::deny()))
+    ///     .path(
+    ///         "/docs/*",
+    ///         PathRule::new().add(
+    ///             ContentSecurityPolicy::default()
+    ///                 .script_src(vec!["'self'", "'unsafe-inline'"])
+    ///                 .img_src(vec!["'self'", "data:", "https://validator.swagger.io"]),
+    ///         ),
+    ///     )
+    ///     .path("/healthz", PathRule::exclude_all())
+    ///     .path("/metrics", PathRule::exclude_all());
+    ///
+    /// let admin = helmet.for_path("/admin/users");
+    /// assert_eq!(admin.get::<XFrameOptions>().unwrap().value(), "DENY");
+    /// assert_eq!(helmet.for_path("/healthz").iter().count(), 0);
+    /// assert_eq!(helmet.for_path("/").get::<XFrameOptions>().unwrap().value(), "SAMEORIGIN");
+    /// ```
+    pub fn path(mut self, pattern: &str, rule: PathRule) -> Self {
+        match self.path_rules.iter_mut().find(|(p, _, _)| p == pattern) {
+            Some(existing) => existing.2 = rule,
+            None => {
+                self.path_rules
+                    .push((pattern.to_string(), PathPattern::parse(pattern).ok(), rule))
+            }
+        }
+        self
+    }
+
+    /// Get the path rules with their configuration, in the order they are checked, see [`Helmet::path`]
+    ///
+    /// Framework adapters compile each configuration once and pick the first rule matching the request path.
+    pub fn path_rules(&self) -> Result<Vec<(PathPattern, Helmet)>, HelmetError> {
+        self.path_rules
+            .iter()
+            .map(|(pattern, parsed, rule)| match parsed {
+                Some(parsed) => Ok((parsed.clone(), rule.apply(self))),
+                None => Err(HelmetError::InvalidPathPattern {
+                    pattern: pattern.clone(),
+                }),
+            })
+            .collect()
+    }
+
+    /// Configuration for a request path, i.e. of the first matching path rule, see [`Helmet::path`]
+    ///
+    /// Patterns that don't parse never match. The configuration of a matching rule is built on every call, framework adapters build them once with [`Helmet::path_rules`].
+    pub fn for_path(&self, path: &str) -> Cow<'_, Helmet> {
+        self.path_rules
+            .iter()
+            .find(|(_, parsed, _)| parsed.as_ref().is_some_and(|parsed| parsed.matches(path)))
+            .map_or(Cow::Borrowed(self), |(_, _, rule)| {
+                Cow::Owned(rule.apply(self))
+            })
+    }
+
+    /// Copy of the configuration with the changes a handler attached to a response, see [`ResponseOverrides`]
+    ///
+    /// The copy has no path rules, as it is meant for a single response.
+    pub fn with_overrides(&self, overrides: &ResponseOverrides) -> Self {
+        let mut helmet = self.clone();
+        helmet.path_rules.clear();
+        let (removed, added) = self.overridden_headers(overrides);
+        helmet.headers.retain(|header| {
+            !removed
+                .iter()
+                .any(|name| name.eq_ignore_ascii_case(header.name()))
+        });
+        helmet.headers.extend(added);
+        helmet
+    }
+
+    /// Validate the headers and prepare them for a framework adapter
+    ///
+    /// ```
+    /// use helmet_core::{CompiledValue, Condition, ConflictStrategy, ContentSecurityPolicy, HeaderScope, Helmet, XFrameOptions};
+    ///
+    /// let compiled = Helmet::new()
+    ///     .add(ContentSecurityPolicy::default().script_nonce())
+    ///     .add_when(XFrameOptions::deny(), Condition::status(200))
+    ///     .compile()
+    ///     .unwrap();
+    ///
+    /// assert_eq!(compiled.names, vec![
+    ///     ("Content-Security-Policy".to_string(), ConflictStrategy::KeepExisting, HeaderScope::DocumentOrScript),
+    ///     ("X-Frame-Options".to_string(), ConflictStrategy::KeepExisting, HeaderScope::Document),
+    /// ]);
+    /// assert!(matches!(compiled.headers[0].value, CompiledValue::Nonce(_)));
+    /// assert!(matches!(&compiled.headers[1].value, CompiledValue::Fixed(value) if value == "DENY"));
+    /// assert_eq!(compiled.headers[1].condition, Some(Condition::status(200)));
+    /// ```
+    pub fn compile(&self) -> Result<CompiledHelmet, HelmetError> {
+        self.validate()?;
+
+        let mut names: Vec<(String, ConflictStrategy, HeaderScope)> = Vec::new();
+        let mut headers = Vec::new();
+        for (index, header) in self.iter().enumerate() {
+            let name = header.name();
+            if !names.iter().any(|(n, _, _)| n.eq_ignore_ascii_case(name)) {
+                names.push((
+                    name.to_string(),
+                    self.conflict_strategy_for(name),
+                    self.scope_for(header),
+                ));
+            }
+            let value = match header.nonce_template() {
+                Some(template) => CompiledValue::Nonce(template),
+                None if header.changes_over_time() => CompiledValue::Timed(index),
+                None => CompiledValue::Fixed(header.value()),
+            };
+            headers.push(CompiledHeader {
+                name: name.to_string(),
+                value,
+                condition: header.condition().cloned(),
+            });
+        }
+        Ok(CompiledHelmet { names, headers })
+    }
+
+    /// Headers changed by `overrides`, for framework adapters applying them to their compiled headers
+    ///
+    /// Returns the names of the configured headers to leave out, and the headers to send in their place: the replacements, and the `Content-Security-Policy` headers extended by [`ResponseOverrides::extend_content_security_policy`]. The other headers are not affected by the overrides.
+    ///
+    /// ```
+    /// use helmet_core::{ContentSecurityPolicy, Header, Helmet, ResponseOverrides, XFrameOptions};
+    ///
+    /// let overrides = ResponseOverrides::new()
+    ///     .skip("X-Frame-Options")
+    ///     .extend_content_security_policy(ContentSecurityPolicy::new().img_src(vec!["https:"]));
+    ///
+    /// let (removed, added) = Helmet::default().overridden_headers(&overrides);
+    ///
+    /// assert_eq!(removed, vec!["X-Frame-Options", "Content-Security-Policy"]);
+    /// assert_eq!(added.len(), 1);
+    /// assert!(added[0].value().contains("img-src 'self' data: https:"));
+    /// ```
+    pub fn overridden_headers<'a>(
+        &self,
+        overrides: &'a ResponseOverrides,
+    ) -> (Vec<&'a str>, Vec<Box<dyn Header>>) {
+        let is_skipped = |name: &str| {
+            overrides
+                .skipped
+                .iter()
+                .any(|skipped| skipped.eq_ignore_ascii_case(name))
+        };
+        let is_replaced = |name: &str| {
+            overrides
+                .headers
+                .iter()
+                .any(|header| header.name().eq_ignore_ascii_case(name))
+        };
+        let mut removed: Vec<&'a str> = overrides.skipped.iter().map(String::as_str).collect();
+        let mut added = Vec::new();
+        for header in overrides.headers.iter() {
+            if !is_skipped(header.name()) {
+                removed.push(header.name());
+                added.push(header.clone());
+            }
+        }
+        if let Some(extension) = &overrides.policy {
+            for header in self.headers.iter() {
+                let name = header.name();
+                let is_policy = |header: &dyn Header| {
+                    (header as &dyn Any).is::<ContentSecurityPolicy<'static>>()
+                };
+                let policy = match (header.as_ref() as &dyn Any).downcast_ref::<Conditional>() {
+                    Some(conditional) => is_policy(conditional.header()),
+                    None => is_policy(header.as_ref()),
+                };
+                if !policy || is_skipped(name) || is_replaced(name) {
+                    continue;
+                }
+                if !removed
+                    .iter()
+                    .any(|removed| removed.eq_ignore_ascii_case(name))
+                {
+                    removed.push(name);
+                }
+                let mut header = header.clone();
+                let target = match (header.as_mut() as &mut dyn Any).downcast_mut::<Conditional>() {
+                    Some(conditional) => &mut conditional.header,
+                    None => &mut header,
+                };
+                if let Some(policy) = (target.as_mut() as &mut dyn Any)
+                    .downcast_mut::<ContentSecurityPolicy<'static>>()
+                {
+                    *policy = policy.clone().extend(extension.clone());
+                }
+                added.push(header);
+            }
+        }
+        (removed, added)
+    }
+
+    /// Copy of the configuration for local development over HTTP
+    ///
+    /// `Strict-Transport-Security` is removed and `Content-Security-Policy` loses `upgrade-insecure-requests`, so browsers don't switch `localhost` to HTTPS. `ws://localhost:*` is added to `connect-src`, or to a copy of `default-src` when the policy has no `connect-src`, for the WebSockets of hot-reload tooling.
+    ///
+    /// ```
+    /// use helmet_core::{ContentSecurityPolicy, Header, Helmet, StrictTransportSecurity};
+    ///
+    /// let helmet = Helmet::default().relaxed_for_development();
+    ///
+    /// assert!(helmet.get::<StrictTransportSecurity>().is_none());
+    /// assert_eq!(
+    ///     helmet.get::<ContentSecurityPolicy>().unwrap().value(),
+    ///     "default-src 'self'; base-uri 'self'; font-src 'self' https: data:; form-action 'self'; frame-ancestors 'self'; img-src 'self' data:; object-src 'none'; script-src 'self'; script-src-attr 'none'; style-src 'self' https: 'unsafe-inline'; connect-src 'self' ws://localhost:*"
+    /// );
+    /// ```
+    pub fn relaxed_for_development(&self) -> Self {
+        let mut helmet = self.clone();
+        helmet.headers.retain(|header| {
+            !header
+                .name()
+                .eq_ignore_ascii_case("Strict-Transport-Security")
+        });
+        for header in helmet.headers.iter_mut() {
+            let header = match (header.as_mut() as &mut dyn Any).downcast_mut::<Conditional>() {
+                Some(conditional) => &mut conditional.header,
+                None => header,
+            };
+            if let Some(policy) =
+                (header.as_mut() as &mut dyn Any).downcast_mut::<ContentSecurityPolicy<'static>>()
+            {
+                policy.relax_for_development();
+            }
+        }
+        helmet.development_mode = Some(DevelopmentMode::Off);
+        helmet
+    }
+
+    /// Create `Helmet` from [helmet.js](https://helmetjs.github.io/) v7 options, e.g. to share the configuration of Node.js services
+    ///
+    /// `options` is the options object passed to `helmet()`, as JSON. Headers are configured like helmet.js does: `false` disables a header, `true` or leaving the option out uses the helmet.js defaults, which match [`Helmet::default`]. The legacy option names, e.g. `hsts` and `frameguard`, are accepted as well.
+    ///
+    /// Invalid values are errors, as helmet.js throws for them. Options without a counterpart in Helmet are returned as warnings, e.g. unknown options, referrer policy fallbacks and `xPoweredBy`, since removing a header set by the handler is not supported. helmet.js removes `X-Powered-By` by default, so leaving `xPoweredBy` out warns as well, set it to `false` to keep the header as helmet.js would.
+    ///
+    /// ```
+    /// use helmet_core::{Header, Helmet, StrictTransportSecurity, XFrameOptions};
+    ///
+    /// let (helmet, warnings) = Helmet::from_helmetjs_options(r#"{
+    ///     "contentSecurityPolicy": {
+    ///         "directives": { "scriptSrc": ["'self'", "https://cdn.example.com"], "upgrade-insecure-requests": null }
+    ///     },
+    ///     "strictTransportSecurity": { "maxAge": 63072000, "preload": true },
+    ///     "frameguard": { "action": "deny" },
+    ///     "xDownloadOptions": false,
+    ///     "xPoweredBy": true
+    /// }"#).unwrap();
+    ///
+    /// assert_eq!(
+    ///     helmet.get::<StrictTransportSecurity>().unwrap().value(),
+    ///     "max-age=63072000; includeSubDomains; preload"
+    /// );
+    /// assert_eq!(helmet.get::<XFrameOptions>().unwrap().value(), "DENY");
+    /// assert_eq!(warnings.len(), 1);
+    /// assert_eq!(warnings[0].option, "xPoweredBy");
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn from_helmetjs_options(
+        options: &str,
+    ) -> Result<(Self, Vec<HelmetJsWarning>), HelmetError> {
+        helmetjs::import(options)
+    }
+
+    /// JSON Schema of the configuration format, for validating configuration files in editors
+    ///
+    /// The schema is checked in as `helmet.schema.json`, regenerate it with `cargo run -p helmet-core --example json_schema --features serde > packages/helmet-core/helmet.schema.json`.
+    ///
+    /// ```
+    /// use helmet_core::Helmet;
+    ///
+    /// let schema: serde_json::Value = serde_json::from_str(include_str!("../helmet.schema.json")).unwrap();
+    ///
+    /// assert_eq!(Helmet::json_schema(), schema);
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn json_schema() -> serde_json::Value {
+        schemars::schema_for!(Helmet).to_value()
+    }
+
+    /// Check that every header has a valid name and value
+    ///
+    /// Framework adapters call this when they are constructed. Calling it directly allows reporting configuration errors before the server starts.
+    pub fn validate(&self) -> Result<(), HelmetError> {
+        self.trusted_proxies()?;
+        for (_, helmet) in self.path_rules()? {
+            helmet.validate()?;
+        }
+        self.headers
+            .iter()
+            .try_for_each(|header| validate_header(header.as_ref()))
+    }
+
+    /// Validate the headers, returning the `Helmet` instance if they are all valid
+    ///
+    /// ```
+    /// use helmet_core::{Helmet, XFrameOptions};
+    ///
+    /// let helmet = Helmet::default().try_build();
+    /// assert!(helmet.is_ok());
+    ///
+    /// let helmet = Helmet::new()
+    ///     .add(XFrameOptions::allow_from("https://example.com\nX-Injected: 1"))
+    ///     .try_build();
+    /// assert!(helmet.is_err());
+    /// ```
+    pub fn try_build(self) -> Result<Self, HelmetError> {
+        self.validate()?;
+        Ok(self)
+    }
+}
+
+impl Default for Helmet {
+    /// Default `Helmet` instance with all headers applied
+    ///
+    /// ```text
+    /// Content-Security-Policy: default-src 'self'; base-uri 'self'; font-src 'self' https: data:; form-action 'self'; frame-ancestors 'self'; img-src 'self' data:; object-src 'none'; script-src 'self'; script-src-attr 'none'; style-src 'self' https: 'unsafe-inline'; upgrade-insecure-requests
+    /// Cross-Origin-Opener-Policy: same-origin
+    /// Cross-Origin-Resource-Policy: same-origin
+    /// Origin-Agent-Cluster: ?1
+    /// Referrer-Policy: no-referrer
+    /// Strict-Transport-Security: max-age=15552000; includeSubDomains
+    /// X-Content-Type-Options: nosniff
+    /// X-DNS-Prefetch-Control: off
+    /// X-Download-Options: noopen
+    /// X-Frame-Options: sameorigin
+    /// X-Permitted-Cross-Domain-Policies: none
+    /// X-XSS-Protection: 0
+    /// ```
+    fn default() -> Self {
+        Self::new()
+            .add(ContentSecurityPolicy::default())
+            .add(CrossOriginOpenerPolicy::same_origin())
+            .add(CrossOriginResourcePolicy::same_origin())
+            .add(OriginAgentCluster(true))
+            .add(ReferrerPolicy::no_referrer())
+            .add(
+                StrictTransportSecurity::new()
+                    .max_age(15552000)
+                    .include_sub_domains(),
+            )
+            .add(XContentTypeOptions::nosniff())
+            .add(XDNSPrefetchControl::off())
+            .add(XDownloadOptions::noopen())
+            .add(XFrameOptions::same_origin())
+            .add(XPermittedCrossDomainPolicies::none())
+            .add(XXSSProtection::off())
+    }
+}
+
+// Prints headers as name/value pairs.
+struct Headers<'a>(&'a [Box<dyn Header>]);
+
+impl std::fmt::Debug for Headers<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_map()
+            .entries(self.0.iter().map(|header| (header.name(), header.value())))
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for Helmet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Helmet")
+            .field("headers", &Headers(&self.headers))
+            .field(
+                "conflict_strategy",
+                &self.conflict_strategy.unwrap_or_default(),
+            )
+            .field(
+                "header_conflict_strategies",
+                &self.header_conflict_strategies,
+            )
+            .field("header_scopes", &self.header_scopes)
+            .field("development_mode", &self.development_mode())
+            .field("hsts_https_only", &self.is_hsts_https_only())
+            .field("trusted_proxies", &self.trusted_proxies)
+            .field(
+                "path_rules",
+                &self
+                    .path_rules
+                    .iter()
+                    .map(|(pattern, _, rule)| (pattern, rule))
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+#[cfg(feature = "serde")]
+pub use helmetjs::HelmetJsWarning;
+
+// Import of helmet.js options, see [`Helmet::from_helmetjs_options`]
+#[cfg(feature = "serde")]
+mod helmetjs {
+    use super::*;
+
+    /// Option of imported helmet.js options that has no counterpart in Helmet, see [`Helmet::from_helmetjs_options`]
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub struct HelmetJsWarning {
+        /// Path of the option, e.g. `strictTransportSecurity.foo`.
+        pub option: String,
+        /// What happened to the option.
+        pub message: String,
+    }
+
+    impl Display for HelmetJsWarning {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            write!(f, "`{}`: {}", self.option, self.message)
+        }
+    }
+
+    // Top-level options of helmet.js v7, with their legacy aliases.
+    const HELMETJS_OPTIONS: &[(&str, Option<&str>)] = &[
+        ("contentSecurityPolicy", None),
+        ("crossOriginEmbedderPolicy", None),
+        ("crossOriginOpenerPolicy", None),
+        ("crossOriginResourcePolicy", None),
+        ("originAgentCluster", None),
+        ("referrerPolicy", None),
+        ("strictTransportSecurity", Some("hsts")),
+        ("xContentTypeOptions", Some("noSniff")),
+        ("xDnsPrefetchControl", Some("dnsPrefetchControl")),
+        ("xDownloadOptions", Some("ieNoOpen")),
+        ("xFrameOptions", Some("frameguard")),
+        (
+            "xPermittedCrossDomainPolicies",
+            Some("permittedCrossDomainPolicies"),
+        ),
+        ("xPoweredBy", Some("hidePoweredBy")),
+        ("xXssProtection", Some("xssFilter")),
+    ];
+
+    fn option_error(option: &str, message: impl Into<String>) -> HelmetError {
+        HelmetError::InvalidOption {
+            option: option.to_string(),
+            message: message.into(),
+        }
+    }
+
+    pub(super) fn import(options: &str) -> Result<(Helmet, Vec<HelmetJsWarning>), HelmetError> {
+        let options: JsonValue =
+            serde_json::from_str(options).map_err(|err| option_error("", err.to_string()))?;
+        let JsonValue::Object(options) = &options else {
+            return Err(option_error("", "expected an object"));
+        };
+        let mut import = HelmetJsImport {
+            options,
+            warnings: Vec::new(),
+        };
+        let helmet = import.helmet()?;
+        Ok((helmet, import.warnings))
+    }
+
+    // Builds a `Helmet` from helmet.js options, collecting warnings for the options it can't apply.
+    struct HelmetJsImport<'a> {
+        options: &'a JsonObject,
+        warnings: Vec<HelmetJsWarning>,
+    }
+
+    // JSON value of the options, parsed in a single pass. Objects keep their keys in the order they are written, which `serde_json::Map` sorts, so directives are sent in that order.
+    enum JsonValue {
+        Null,
+        Bool(bool),
+        Number(f64),
+        String(String),
+        Array(Vec<JsonValue>),
+        Object(JsonObject),
+    }
+
+    #[derive(Default)]
+    struct JsonObject(Vec<(String, JsonValue)>);
+
+    impl JsonValue {
+        fn as_str(&self) -> Option<&str> {
+            match self {
+                JsonValue::String(value) => Some(value),
+                _ => None,
+            }
+        }
+
+        fn as_f64(&self) -> Option<f64> {
+            match self {
+                JsonValue::Number(value) => Some(*value),
+                _ => None,
+            }
+        }
+    }
+
+    impl JsonObject {
+        fn get(&self, key: &str) -> Option<&JsonValue> {
+            self.0
+                .iter()
+                .find(|(existing, _)| existing == key)
+                .map(|(_, value)| value)
+        }
+
+        fn contains_key(&self, key: &str) -> bool {
+            self.get(key).is_some()
+        }
+
+        fn keys(&self) -> impl Iterator<Item = &str> {
+            self.0.iter().map(|(key, _)| key.as_str())
+        }
+
+        fn iter(&self) -> impl Iterator<Item = (&str, &JsonValue)> {
+            self.0.iter().map(|(key, value)| (key.as_str(), value))
+        }
+    }
+
+    impl<'de> serde::Deserialize<'de> for JsonValue {
+        fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct JsonValueVisitor;
+
+            impl<'de> serde::de::Visitor<'de> for JsonValueVisitor {
+                type Value = JsonValue;
+
+                fn expecting(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+                    write!(f, "a JSON value")
+                }
+
+                fn visit_bool<E>(self, value: bool) -> Result<JsonValue, E> {
+                    Ok(JsonValue::Bool(value))
+                }
+
+                fn visit_i64<E>(self, value: i64) -> Result<JsonValue, E> {
+                    Ok(JsonValue::Number(value as f64))
+                }
+
+                fn visit_u64<E>(self, value: u64) -> Result<JsonValue, E> {
+                    Ok(JsonValue::Number(value as f64))
+                }
+
+                fn visit_f64<E>(self, value: f64) -> Result<JsonValue, E> {
+                    Ok(JsonValue::Number(value))
+                }
+
+                fn visit_str<E>(self, value: &str) -> Result<JsonValue, E> {
+                    Ok(JsonValue::String(value.to_string()))
+                }
+
+                fn visit_string<E>(self, value: String) -> Result<JsonValue, E> {
+                    Ok(JsonValue::String(value))
+                }
+
+                fn visit_unit<E>(self) -> Result<JsonValue, E> {
+                    Ok(JsonValue::Null)
+                }
+
+                fn visit_seq<A: serde::de::SeqAccess<'de>>(
+                    self,
+                    mut seq: A,
+                ) -> Result<JsonValue, A::Error> {
+                    let mut values = Vec::new();
+                    while let Some(value) = seq.next_element()? {
+                        values.push(value);
+                    }
+                    Ok(JsonValue::Array(values))
+                }
+
+                fn visit_map<A: serde::de::MapAccess<'de>>(
+                    self,
+                    mut map: A,
+                ) -> Result<JsonValue, A::Error> {
+                    let mut object = JsonObject::default();
+                    while let Some((key, value)) = map.next_entry::<String, JsonValue>()? {
+                        // the last value of a duplicate key wins, as in JavaScript
+                        match object.0.iter_mut().find(|(existing, _)| *existing == key) {
+                            Some((_, existing)) => *existing = value,
+                            None => object.0.push((key, value)),
+                        }
+                    }
+                    Ok(JsonValue::Object(object))
+                }
+            }
+
+            deserializer.deserialize_any(JsonValueVisitor)
+        }
+    }
+
+    impl<'a> HelmetJsImport<'a> {
+        fn helmet(&mut self) -> Result<Helmet, HelmetError> {
+            for key in self.options.keys() {
+                if !HELMETJS_OPTIONS
+                    .iter()
+                    .any(|(name, alias)| *name == key || *alias == Some(key))
+                {
+                    self.warn(key, "unknown option, ignored");
+                }
+            }
+
+            let mut helmet = Helmet::new();
+            let known = &["useDefaults", "directives", "reportOnly"];
+            if let Some(options) = self.header("contentSecurityPolicy", true, known)? {
+                helmet = helmet.add(self.content_security_policy(&options)?);
+            }
+            if let Some(options) = self.header("crossOriginEmbedderPolicy", false, &["policy"])? {
+                helmet = helmet.add(match options.string("policy", "require-corp")? {
+                    "require-corp" => CrossOriginEmbedderPolicy::require_corp(),
+                    "credentialless" => CrossOriginEmbedderPolicy::credentialless(),
+                    "unsafe-none" => CrossOriginEmbedderPolicy::unsafe_none(),
+                    policy => return Err(options.unsupported("policy", policy)),
+                });
+            }
+            if let Some(options) = self.header("crossOriginOpenerPolicy", true, &["policy"])? {
+                helmet = helmet.add(match options.string("policy", "same-origin")? {
+                    "same-origin" => CrossOriginOpenerPolicy::same_origin(),
+                    "same-origin-allow-popups" => {
+                        CrossOriginOpenerPolicy::same_origin_allow_popups()
+                    }
+                    "unsafe-none" => CrossOriginOpenerPolicy::unsafe_none(),
+                    policy => return Err(options.unsupported("policy", policy)),
+                });
+            }
+            if let Some(options) = self.header("crossOriginResourcePolicy", true, &["policy"])? {
+                helmet = helmet.add(match options.string("policy", "same-origin")? {
+                    "same-origin" => CrossOriginResourcePolicy::same_origin(),
+                    "same-site" => CrossOriginResourcePolicy::same_site(),
+                    "cross-origin" => CrossOriginResourcePolicy::cross_origin(),
+                    policy => return Err(options.unsupported("policy", policy)),
+                });
+            }
+            if self.header("originAgentCluster", true, &[])?.is_some() {
+                helmet = helmet.add(OriginAgentCluster::new(true));
+            }
+            if let Some(options) = self.header("referrerPolicy", true, &["policy"])? {
+                helmet = helmet.add(self.referrer_policy(&options)?);
+            }
+            let known = &["maxAge", "includeSubDomains", "preload"];
+            if let Some(options) = self.header("strictTransportSecurity", true, known)? {
+                let mut header = options.strict_transport_security()?;
+                if header.preload && !header.is_preloadable() {
+                    header.preload = false;
+                    self.warn(
+                        &options.path("preload"),
+                        "preload requires a maxAge of at least 31536000 and includeSubDomains, ignored",
+                    );
+                }
+                helmet = helmet.add(header);
+            }
+            if self.header("xContentTypeOptions", true, &[])?.is_some() {
+                helmet = helmet.add(XContentTypeOptions::nosniff());
+            }
+            if let Some(options) = self.header("xDnsPrefetchControl", true, &["allow"])? {
+                helmet = helmet.add(match options.bool("allow", false)? {
+                    true => XDNSPrefetchControl::on(),
+                    false => XDNSPrefetchControl::off(),
+                });
+            }
+            if self.header("xDownloadOptions", true, &[])?.is_some() {
+                helmet = helmet.add(XDownloadOptions::noopen());
+            }
+            if let Some(options) = self.header("xFrameOptions", true, &["action"])? {
+                let action = options.string("action", "sameorigin")?;
+                helmet = helmet.add(match action.to_ascii_lowercase().as_str() {
+                    "deny" => XFrameOptions::deny(),
+                    "sameorigin" => XFrameOptions::same_origin(),
+                    "allow-from" => {
+                        return Err(option_error(
+                            &options.path("action"),
+                            "ALLOW-FROM is not supported by helmet.js, use the `frame-ancestors` directive of Content-Security-Policy instead",
+                        ))
+                    }
+                    _ => return Err(options.unsupported("action", action)),
+                });
+            }
+            let known = &["permittedPolicies"];
+            if let Some(options) = self.header("xPermittedCrossDomainPolicies", true, known)? {
+                helmet = helmet.add(match options.string("permittedPolicies", "none")? {
+                    "none" => XPermittedCrossDomainPolicies::none(),
+                    "master-only" => XPermittedCrossDomainPolicies::master_only(),
+                    "by-content-type" => XPermittedCrossDomainPolicies::by_content_type(),
+                    "all" => XPermittedCrossDomainPolicies::all(),
+                    policy => return Err(options.unsupported("permittedPolicies", policy)),
+                });
+            }
+            // helmet.js removes `X-Powered-By` unless `xPoweredBy` is false
+            if let Some(options) = self.header("xPoweredBy", true, &[])? {
+                self.warn(
+                    options.path,
+                    "removing the `X-Powered-By` header is not supported, it is left as set by the handler",
+                );
+            }
+            if self.header("xXssProtection", true, &[])?.is_some() {
+                helmet = helmet.add(XXSSProtection::off());
+            }
+            Ok(helmet)
+        }
+
+        fn warn(&mut self, option: &str, message: impl Into<String>) {
+            self.warnings.push(HelmetJsWarning {
+                option: option.to_string(),
+                message: message.into(),
+            });
+        }
+
+        // Options of a header, `None` when the header is disabled. `true`, as well as leaving the option out for headers that are enabled by default, enables the header with its defaults.
+        fn header(
+            &mut self,
+            name: &'static str,
+            enabled_by_default: bool,
+            fields: &[&str],
+        ) -> Result<Option<HeaderOptions<'a>>, HelmetError> {
+            let alias = HELMETJS_OPTIONS
+                .iter()
+                .find(|(option, _)| *option == name)
+                .and_then(|(_, alias)| *alias);
+            let option = match (self.options.get(name), alias) {
+                (Some(_), Some(alias)) if self.options.contains_key(alias) => {
+                    return Err(option_error(
+                        name,
+                        format!("`{}` and `{}` can't both be set", name, alias),
+                    ))
+                }
+                (Some(value), _) => Some((name, value)),
+                (None, Some(alias)) => self.options.get(alias).map(|value| (alias, value)),
+                (None, None) => None,
+            };
+            match option {
+                None if enabled_by_default => Ok(Some(HeaderOptions {
+                    path: name,
+                    fields: None,
+                })),
+                None | Some((_, JsonValue::Bool(false))) => Ok(None),
+                Some((path, JsonValue::Bool(true))) => {
+                    Ok(Some(HeaderOptions { path, fields: None }))
+                }
+                Some((path, JsonValue::Object(options))) => {
+                    for key in options.keys() {
+                        if !fields.contains(&key) {
+                            self.warn(&format!("{}.{}", path, key), "unknown option, ignored");
+                        }
+                    }
+                    Ok(Some(HeaderOptions {
+                        path,
+                        fields: Some(options),
+                    }))
+                }
+                Some((path, _)) => Err(option_error(path, "expected an object or a boolean")),
+            }
+        }
+
+        fn content_security_policy(
+            &mut self,
+            options: &HeaderOptions<'a>,
+        ) -> Result<ContentSecurityPolicy<'static>, HelmetError> {
+            let mut directives = Vec::new();
+            if options.bool("useDefaults", true)? {
+                directives = ContentSecurityPolicy::default()
+                    .directives
+                    .iter()
+                    .map(|directive| (directive.name().to_string(), directive.values()))
+                    .collect();
+            }
+            let mut configured = Vec::new();
+            match options.get("directives") {
+                None => {}
+                Some(JsonValue::Object(values)) => {
+                    for (key, value) in values.iter() {
+                        let path = format!("{}.{}", options.path("directives"), key);
+                        // helmet.js accepts both `defaultSrc` and `default-src`
+                        let name = key.chars().fold(String::new(), |mut name, c| {
+                            if c.is_ascii_uppercase() {
+                                name.push('-');
+                            }
+                            name.push(c.to_ascii_lowercase());
+                            name
+                        });
+                        if configured.contains(&name) {
+                            return Err(option_error(&path, "directive is set more than once"));
+                        }
+                        configured.push(name.clone());
+                        let values = match value {
+                            JsonValue::Null => {
+                                directives.retain(|(existing, _)| *existing != name);
+                                continue;
+                            }
+                            JsonValue::String(value) => vec![value.clone()],
+                            JsonValue::Array(values) => values
+                                .iter()
+                                .map(|value| {
+                                    value.as_str().map(str::to_string).ok_or_else(|| {
+                                        option_error(&path, "expected a list of strings")
+                                    })
+                                })
+                                .collect::<Result<_, _>>()?,
+                            _ => {
+                                return Err(option_error(
+                                    &path,
+                                    "expected a string, a list of strings or null",
+                                ))
+                            }
+                        };
+                        match directives
+                            .iter_mut()
+                            .find(|(existing, _)| *existing == name)
+                        {
+                            Some((_, existing)) => *existing = values,
+                            None => directives.push((name, values)),
+                        }
+                    }
+                }
+                Some(_) => {
+                    return Err(option_error(
+                        &options.path("directives"),
+                        "expected an object",
+                    ))
+                }
+            }
+            if !directives.iter().any(|(name, _)| name == "default-src") {
+                return Err(option_error(
+                    &options.path("directives"),
+                    "`default-src` is required, as in helmet.js",
+                ));
+            }
+
+            let mut policy = ContentSecurityPolicy::new();
+            for (name, values) in directives {
+                let path = format!("{}.{}", options.path("directives"), name);
+                let directive = std::iter::once(name)
+                    .chain(values)
+                    .collect::<Vec<_>>()
+                    .join(" ")
+                    .parse::<ContentSecurityPolicyDirective>()
+                    .map_err(|err| option_error(&path, err.to_string()))?;
+                policy = policy.directive(directive.into_owned());
+            }
+            if options.bool("reportOnly", false)? {
+                policy = policy.report_only();
+            }
+            Ok(policy)
+        }
+
+        fn referrer_policy(
+            &mut self,
+            options: &HeaderOptions<'a>,
+        ) -> Result<ReferrerPolicy, HelmetError> {
+            let path = options.path("policy");
+            let policies = match options.get("policy") {
+                None => vec!["no-referrer"],
+                Some(JsonValue::String(policy)) => vec![policy.as_str()],
+                Some(JsonValue::Array(policies)) if !policies.is_empty() => policies
+                    .iter()
+                    .map(|policy| {
+                        policy
+                            .as_str()
+                            .ok_or_else(|| option_error(&path, "expected a list of strings"))
+                    })
+                    .collect::<Result<_, _>>()?,
+                Some(_) => {
+                    return Err(option_error(
+                        &path,
+                        "expected a string or a non-empty list of strings",
+                    ))
+                }
+            };
+            // Browsers use the last policy they support, which for current browsers is the last one.
+            let policy = policies[policies.len() - 1];
+            if policies.len() > 1 {
+                self.warn(
+                    &path,
+                    format!(
+                        "fallback policies are not supported, only `{}` is sent",
+                        policy
+                    ),
+                );
+            }
+            Ok(match policy {
+                "no-referrer" => ReferrerPolicy::no_referrer(),
+                "no-referrer-when-downgrade" => ReferrerPolicy::no_referrer_when_downgrade(),
+                "origin" => ReferrerPolicy::origin(),
+                "origin-when-cross-origin" => ReferrerPolicy::origin_when_cross_origin(),
+                "same-origin" => ReferrerPolicy::same_origin(),
+                "strict-origin" => ReferrerPolicy::strict_origin(),
+                "strict-origin-when-cross-origin" => {
+                    ReferrerPolicy::strict_origin_when_cross_origin()
+                }
+                "unsafe-url" => ReferrerPolicy::unsafe_url(),
+                _ => return Err(options.unsupported("policy", policy)),
+            })
+        }
+    }
+
+    // Options of a single enabled header.
+    struct HeaderOptions<'a> {
+        path: &'a str,
+        fields: Option<&'a JsonObject>,
+    }
+
+    impl<'a> HeaderOptions<'a> {
+        fn get(&self, field: &str) -> Option<&'a JsonValue> {
+            self.fields.and_then(|fields| fields.get(field))
+        }
+
+        fn path(&self, field: &str) -> String {
+            format!("{}.{}", self.path, field)
+        }
+
+        fn string(&self, field: &str, default: &'a str) -> Result<&'a str, HelmetError> {
+            match self.get(field) {
+                None => Ok(default),
+                Some(JsonValue::String(value)) => Ok(value),
+                Some(_) => Err(option_error(&self.path(field), "expected a string")),
+            }
+        }
+
+        fn bool(&self, field: &str, default: bool) -> Result<bool, HelmetError> {
+            match self.get(field) {
+                None => Ok(default),
+                Some(JsonValue::Bool(value)) => Ok(*value),
+                Some(_) => Err(option_error(&self.path(field), "expected a boolean")),
+            }
+        }
+
+        fn unsupported(&self, field: &str, value: &str) -> HelmetError {
+            option_error(&self.path(field), format!("unsupported value {:?}", value))
+        }
+
+        fn strict_transport_security(&self) -> Result<StrictTransportSecurity, HelmetError> {
+            // Renamed or removed in helmet.js v4, which throws instead of ignoring them.
+            for (legacy, message) in [
+                ("includeSubdomains", "did you mean `includeSubDomains`?"),
+                ("setIf", "is no longer supported by helmet.js"),
+            ] {
+                if self.get(legacy).is_some() {
+                    return Err(option_error(&self.path(legacy), message));
+                }
+            }
+            let max_age = match self.get("maxAge") {
+                None => 15552000,
+                Some(value) => value
+                    .as_f64()
+                    .filter(|max_age| max_age.is_finite() && *max_age >= 0.0)
+                    .map(f64::round)
+                    .filter(|max_age| *max_age <= u32::MAX as f64)
+                    .ok_or_else(|| {
+                        option_error(&self.path("maxAge"), "expected a positive number")
+                    })? as u32,
+            };
+            let mut header = StrictTransportSecurity::new().max_age(max_age);
+            if self.bool("includeSubDomains", true)? {
+                header = header.include_sub_domains();
+            }
+            // helmet.js doesn't check preload, the caller drops it with a warning
+            header.preload = self.bool("preload", false)?;
+            Ok(header)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+pub use reports::*;
+
+// Violation reports sent by browsers, parsed for the report endpoints of the framework adapters
+#[cfg(feature = "serde")]
+mod reports {
+    use super::*;
+
+    /// Violation of a `Content-Security-Policy`, as reported by the browser
+    ///
+    /// Browsers send violation reports either in the legacy `application/csp-report` format, for the `report-uri` directive, or in the Reporting API `application/reports+json` format, for the `report-to` directive. Both are normalized into this struct.
+    #[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    pub struct CspViolation {
+        /// URL of the document in which the violation occurred.
+        pub document_uri: String,
+        /// Referrer of the document in which the violation occurred.
+        pub referrer: Option<String>,
+        /// URL of the resource that was blocked, or a keyword such as `inline` or `eval`.
+        pub blocked_uri: Option<String>,
+        /// Directive whose enforcement caused the violation, e.g. `script-src-elem`.
+        pub effective_directive: String,
+        /// The policy as received by the browser.
+        pub original_policy: String,
+        /// `enforce` or `report`, depending on whether the policy was enforced or report only.
+        pub disposition: Option<String>,
+        /// URL of the script that caused the violation.
+        pub source_file: Option<String>,
+        pub line_number: Option<u32>,
+        pub column_number: Option<u32>,
+        /// HTTP status code of the document in which the violation occurred.
+        pub status_code: Option<u16>,
+        /// The first characters of the inline script, style or event handler that caused the violation, if the policy contains `'report-sample'`.
+        pub sample: Option<String>,
+    }
+
+    /// Body of an `application/csp-report` request, sent for the `report-uri` directive
+    #[derive(Clone, Debug, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    pub struct LegacyCspReport {
+        #[serde(rename = "csp-report")]
+        pub csp_report: LegacyCspReportBody,
+    }
+
+    /// Violation of a legacy `application/csp-report` report
+    #[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    #[serde(default, rename_all = "kebab-case")]
+    pub struct LegacyCspReportBody {
+        pub document_uri: String,
+        pub referrer: Option<String>,
+        pub blocked_uri: Option<String>,
+        /// Older browsers only send `violated-directive`.
+        pub effective_directive: Option<String>,
+        pub violated_directive: String,
+        pub original_policy: String,
+        pub disposition: Option<String>,
+        pub source_file: Option<String>,
+        pub line_number: Option<u32>,
+        pub column_number: Option<u32>,
+        pub status_code: Option<u16>,
+        pub script_sample: Option<String>,
+    }
+
+    impl From<LegacyCspReportBody> for CspViolation {
+        fn from(body: LegacyCspReportBody) -> Self {
+            Self {
+                document_uri: body.document_uri,
+                referrer: body.referrer.filter(|referrer| !referrer.is_empty()),
+                blocked_uri: body.blocked_uri,
+                effective_directive: body.effective_directive.unwrap_or(body.violated_directive),
+                original_policy: body.original_policy,
+                disposition: body.disposition,
+                source_file: body.source_file,
+                line_number: body.line_number,
+                column_number: body.column_number,
+                status_code: body.status_code,
+                sample: body.script_sample.filter(|sample| !sample.is_empty()),
+            }
+        }
+    }
+
+    /// Report sent through the Reporting API, in an `application/reports+json` request
+    ///
+    /// The body is kept as JSON by default. Convert the report into a [`ViolationReport`] to get a typed body.
+    #[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    pub struct Report<B = serde_json::Value> {
+        /// Type of the report, e.g. `csp-violation` or `deprecation`.
+        #[serde(rename = "type")]
+        pub report_type: String,
+        /// Milliseconds between the report being generated and sent.
+        #[serde(default)]
+        pub age: u64,
+        /// URL of the document the report was generated for.
+        #[serde(default)]
+        pub url: String,
+        #[serde(default)]
+        pub user_agent: String,
+        pub body: B,
+    }
+
+    /// Body of a `csp-violation` report
+    #[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    #[serde(default, rename_all = "camelCase")]
+    pub struct CspViolationReportBody {
+        #[serde(rename = "documentURL")]
+        pub document_url: String,
+        pub referrer: Option<String>,
+        #[serde(rename = "blockedURL")]
+        pub blocked_url: Option<String>,
+        pub effective_directive: String,
+        pub original_policy: String,
+        pub disposition: Option<String>,
+        pub source_file: Option<String>,
+        pub line_number: Option<u32>,
+        pub column_number: Option<u32>,
+        pub status_code: Option<u16>,
+        pub sample: Option<String>,
+    }
+
+    impl From<CspViolationReportBody> for CspViolation {
+        fn from(body: CspViolationReportBody) -> Self {
+            Self {
+                document_uri: body.document_url,
+                referrer: body.referrer.filter(|referrer| !referrer.is_empty()),
+                blocked_uri: body.blocked_url,
+                effective_directive: body.effective_directive,
+                original_policy: body.original_policy,
+                disposition: body.disposition,
+                source_file: body.source_file,
+                line_number: body.line_number,
+                column_number: body.column_number,
+                status_code: body.status_code,
+                sample: body.sample.filter(|sample| !sample.is_empty()),
+            }
+        }
+    }
+
+    /// Body of a `coop` report, sent for violations of `Cross-Origin-Opener-Policy`
+    #[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    #[serde(default, rename_all = "camelCase")]
+    pub struct CoopViolationReportBody {
+        /// Kind of violation, e.g. `navigation-to-response` or `access-from-coop-page-to-opener`.
+        #[serde(rename = "type")]
+        pub violation_type: String,
+        /// `enforce` or `reporting`.
+        pub disposition: Option<String>,
+        /// The opener policy of the document, e.g. `same-origin`.
+        pub effective_policy: Option<String>,
+        #[serde(rename = "previousResponseURL")]
+        pub previous_response_url: Option<String>,
+        #[serde(rename = "nextResponseURL")]
+        pub next_response_url: Option<String>,
+        pub referrer: Option<String>,
+        /// Window property that was accessed, e.g. `postMessage`.
+        pub property: Option<String>,
+        #[serde(rename = "openerURL")]
+        pub opener_url: Option<String>,
+        #[serde(rename = "openedWindowURL")]
+        pub opened_window_url: Option<String>,
+        #[serde(rename = "otherDocumentURL")]
+        pub other_document_url: Option<String>,
+        #[serde(rename = "initialPopupURL")]
+        pub initial_popup_url: Option<String>,
+        pub source_file: Option<String>,
+        pub line_number: Option<u32>,
+        pub column_number: Option<u32>,
+    }
+
+    /// Body of a `coep` report, sent for violations of `Cross-Origin-Embedder-Policy`
+    #[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    #[serde(default, rename_all = "camelCase")]
+    pub struct CoepViolationReportBody {
+        /// Kind of violation, e.g. `corp`, `navigation` or `worker initialization`.
+        #[serde(rename = "type")]
+        pub violation_type: String,
+        #[serde(rename = "blockedURL")]
+        pub blocked_url: String,
+        /// Request destination of the blocked resource, e.g. `script` or `iframe`.
+        pub destination: Option<String>,
+        /// `enforce` or `reporting`.
+        pub disposition: Option<String>,
+    }
+
+    /// Body of a `permissions-policy-violation` report, sent for violations of `Permissions-Policy`
+    #[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    #[serde(default, rename_all = "camelCase")]
+    pub struct PermissionsPolicyViolationReportBody {
+        /// The feature that was used, e.g. `geolocation`.
+        pub feature_id: String,
+        /// `enforce` or `report`.
+        pub disposition: Option<String>,
+        pub message: Option<String>,
+        pub source_file: Option<String>,
+        pub line_number: Option<u32>,
+        pub column_number: Option<u32>,
+        pub allow_attribute: Option<String>,
+        pub src_attribute: Option<String>,
+    }
+
+    /// Body of a `deprecation` report, sent when the document uses a deprecated API
+    #[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    #[serde(default, rename_all = "camelCase")]
+    pub struct DeprecationReportBody {
+        /// Identifier of the deprecated feature.
+        pub id: String,
+        pub message: String,
+        /// Date after which the feature is expected to be removed.
+        pub anticipated_removal: Option<String>,
+        pub source_file: Option<String>,
+        pub line_number: Option<u32>,
+        pub column_number: Option<u32>,
+    }
+
+    /// Body of an `intervention` report, sent when the browser declined a request of the document, e.g. for security or performance reasons
+    #[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    #[serde(default, rename_all = "camelCase")]
+    pub struct InterventionReportBody {
+        /// Identifier of the intervention.
+        pub id: String,
+        pub message: String,
+        pub source_file: Option<String>,
+        pub line_number: Option<u32>,
+        pub column_number: Option<u32>,
+    }
+
+    /// Body of a `crash` report, sent after the document crashed
+    #[derive(Clone, Debug, Default, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
+    #[serde(default)]
+    pub struct CrashReportBody {
+        /// Reason of the crash, e.g. `oom` or `unresponsive`.
+        pub reason: Option<String>,
+        pub stack: Option<String>,
+        pub is_top_level: Option<bool>,
+        pub page_visibility: Option<String>,
+    }
+
+    /// Report received from a browser, normalized from any of the supported formats
+    ///
+    /// CSP violations are normalized into a [`CspViolation`], regardless of whether they were sent in the legacy `application/csp-report` format or through the Reporting API. Other reports are only sent through the Reporting API and keep their envelope.
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum ViolationReport {
+        Csp(CspViolation),
+        Coop(Report<CoopViolationReportBody>),
+        Coep(Report<CoepViolationReportBody>),
+        PermissionsPolicy(Report<PermissionsPolicyViolationReportBody>),
+        Deprecation(Report<DeprecationReportBody>),
+        Intervention(Report<InterventionReportBody>),
+        Crash(Report<CrashReportBody>),
+        /// Report of a type not known to Helmet, or that doesn't match its type, with its body kept as JSON. When the report isn't even a valid Reporting API report, the body is the whole report.
+        Other(Report),
+    }
+
+    impl ViolationReport {
+        // Report of an `application/reports+json` list, `Other` when it doesn't match its type
+        fn from_entry(entry: serde_json::Value) -> Self {
+            let report = match serde_json::from_value::<Report>(entry.clone()) {
+                Ok(report) => report,
+                // not even a report envelope, the whole entry is kept as the body
+                Err(_) => {
+                    return Self::Other(Report {
+                        report_type: entry
+                            .get("type")
+                            .and_then(serde_json::Value::as_str)
+                            .unwrap_or_default()
+                            .to_string(),
+                        age: 0,
+                        url: String::new(),
+                        user_agent: String::new(),
+                        body: entry,
+                    })
+                }
+            };
+            Self::try_from(report.clone()).unwrap_or(Self::Other(report))
+        }
+    }
+
+    impl TryFrom<Report> for ViolationReport {
+        type Error = serde_json::Error;
+
+        /// Deserialize the body of a report, based on its type.
+        fn try_from(report: Report) -> Result<Self, Self::Error> {
+            fn typed<B: serde::de::DeserializeOwned>(
+                report: Report,
+            ) -> Result<Report<B>, serde_json::Error> {
+                Ok(Report {
+                    body: serde_json::from_value(report.body)?,
+                    report_type: report.report_type,
+                    age: report.age,
+                    url: report.url,
+                    user_agent: report.user_agent,
+                })
+            }
+
+            Ok(match report.report_type.as_str() {
+                "csp-violation" => {
+                    Self::Csp(serde_json::from_value::<CspViolationReportBody>(report.body)?.into())
+                }
+                "coop" => Self::Coop(typed(report)?),
+                "coep" => Self::Coep(typed(report)?),
+                "permissions-policy-violation" => Self::PermissionsPolicy(typed(report)?),
+                "deprecation" => Self::Deprecation(typed(report)?),
+                "intervention" => Self::Intervention(typed(report)?),
+                "crash" => Self::Crash(typed(report)?),
+                _ => Self::Other(report),
+            })
+        }
+    }
+
+    /// Error returned when a violation report request can't be parsed
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    pub enum ReportError {
+        /// The request has a content type other than `application/csp-report`, `application/json` or `application/reports+json`.
+        UnsupportedContentType(String),
+        /// The request body is not a valid report.
+        InvalidBody(String),
+    }
+
+    impl Display for ReportError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                ReportError::UnsupportedContentType(content_type) => {
+                    write!(f, "unsupported report content type {:?}", content_type)
+                }
+                ReportError::InvalidBody(message) => write!(f, "invalid report body: {}", message),
+            }
+        }
+    }
+
+    impl std::error::Error for ReportError {}
+
+    /// Parse the body of a report request, based on its `Content-Type`
+    ///
+    /// `application/csp-report` (and `application/json`, which some browsers use instead) bodies contain a single CSP violation. `application/reports+json` bodies contain a list of reports of any type. Each report of the list is parsed on its own, a malformed one is returned as [`ViolationReport::Other`] and doesn't fail the others.
+    ///
+    /// ```
+    /// use helmet_core::{parse_reports, ViolationReport};
+    ///
+    /// let body = br#"[
+    ///     {
+    ///         "type": "coep",
+    ///         "age": 10,
+    ///         "url": "https://example.com/",
+    ///         "user_agent": "Mozilla/5.0",
+    ///         "body": {
+    ///             "type": "corp",
+    ///             "blockedURL": "https://cdn.example.com/image.png",
+    ///             "destination": "image",
+    ///             "disposition": "enforce"
+    ///         }
+    ///     },
+    ///     {
+    ///         "type": "deprecation",
+    ///         "age": 20,
+    ///         "url": "https://example.com/",
+    ///         "user_agent": "Mozilla/5.0",
+    ///         "body": {
+    ///             "id": "WebSQL",
+    ///             "message": "WebSQL is deprecated"
+    ///         }
+    ///     }
+    /// ]"#;
+    ///
+    /// let reports = parse_reports("application/reports+json", body).unwrap();
+    /// assert!(matches!(
+    ///     &reports[0],
+    ///     ViolationReport::Coep(report) if report.body.blocked_url == "https://cdn.example.com/image.png"
+    /// ));
+    /// assert!(matches!(&reports[1], ViolationReport::Deprecation(report) if report.body.id == "WebSQL"));
+    ///
+    /// let body = br#"[
+    ///     {"type": "deprecation", "url": "https://example.com/", "body": {"id": "WebSQL", "message": "WebSQL is deprecated"}},
+    ///     {"type": "coep", "url": "https://example.com/", "body": {"blockedURL": 42}},
+    ///     {"type": "crash"},
+    ///     "not a report"
+    /// ]"#;
+    ///
+    /// let reports = parse_reports("application/reports+json", body).unwrap();
+    /// assert_eq!(reports.len(), 4);
+    /// assert!(matches!(&reports[0], ViolationReport::Deprecation(report) if report.body.id == "WebSQL"));
+    /// assert!(matches!(
+    ///     &reports[1],
+    ///     ViolationReport::Other(report) if report.report_type == "coep" && report.body["blockedURL"] == 42
+    /// ));
+    /// assert!(matches!(&reports[2], ViolationReport::Other(report) if report.report_type == "crash"));
+    /// assert!(matches!(&reports[3], ViolationReport::Other(report) if report.body == "not a report"));
+    ///
+    /// assert!(parse_reports("application/reports+json", b"{").is_err());
+    /// assert!(parse_reports("text/plain", b"").is_err());
+    /// ```
+    pub fn parse_reports(
+        content_type: &str,
+        body: &[u8],
+    ) -> Result<Vec<ViolationReport>, ReportError> {
+        let media_type = content_type
+            .split(';')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_ascii_lowercase();
+        let invalid_body = |err: serde_json::Error| ReportError::InvalidBody(err.to_string());
+
+        match media_type.as_str() {
+            "application/csp-report" | "application/json" => {
+                let report: LegacyCspReport = serde_json::from_slice(body).map_err(invalid_body)?;
+                Ok(vec![ViolationReport::Csp(report.csp_report.into())])
+            }
+            "application/reports+json" => {
+                // each report on its own, so a malformed one doesn't drop the rest of the batch
+                let reports: Vec<serde_json::Value> =
+                    serde_json::from_slice(body).map_err(invalid_body)?;
+                Ok(reports
+                    .into_iter()
+                    .map(ViolationReport::from_entry)
+                    .collect())
+            }
+            _ => Err(ReportError::UnsupportedContentType(
+                content_type.to_string(),
+            )),
+        }
+    }
+
+    /// Parse the body of a report request, keeping only CSP violations
+    ///
+    /// ```
+    /// use helmet_core::parse_csp_reports;
+    ///
+    /// let body = br#"{"csp-report": {
+    ///     "document-uri": "https://example.com/",
+    ///     "blocked-uri": "https://evil.com/script.js",
+    ///     "violated-directive": "script-src-elem",
+    ///     "effective-directive": "script-src-elem",
+    ///     "original-policy": "script-src 'self'; report-uri /csp-reports",
+    ///     "disposition": "enforce"
+    /// }}"#;
+    ///
+    /// let violations = parse_csp_reports("application/csp-report", body).unwrap();
+    /// assert_eq!(violations[0].blocked_uri.as_deref(), Some("https://evil.com/script.js"));
+    ///
+    /// let body = br#"[{
+    ///     "type": "csp-violation",
+    ///     "age": 10,
+    ///     "url": "https://example.com/",
+    ///     "user_agent": "Mozilla/5.0",
+    ///     "body": {
+    ///         "documentURL": "https://example.com/",
+    ///         "blockedURL": "inline",
+    ///         "effectiveDirective": "script-src-elem",
+    ///         "originalPolicy": "script-src 'self'; report-to csp",
+    ///         "disposition": "enforce"
+    ///     }
+    /// }]"#;
+    ///
+    /// let violations = parse_csp_reports("application/reports+json", body).unwrap();
+    /// assert_eq!(violations[0].effective_directive, "script-src-elem");
+    /// ```
+    pub fn parse_csp_reports(
+        content_type: &str,
+        body: &[u8],
+    ) -> Result<Vec<CspViolation>, ReportError> {
+        Ok(parse_reports(content_type, body)?
+            .into_iter()
+            .filter_map(|report| match report {
+                ViolationReport::Csp(violation) => Some(violation),
+                _ => None,
+            })
+            .collect())
+    }
+
+    /// Receiver of violation reports
+    ///
+    /// The report endpoints of the framework adapters hand every parsed report to a `ReportSink`, e.g. to log it or forward it to a monitoring service. Closures taking a [`ViolationReport`] implement this trait.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use helmet_core::{ReportSink, ViolationReport};
+    ///
+    /// struct LogSink;
+    ///
+    /// impl ReportSink for LogSink {
+    ///     fn report(&self, report: ViolationReport) {
+    ///         if let ViolationReport::Csp(violation) = report {
+    ///             eprintln!("CSP violation on {}: {}", violation.document_uri, violation.effective_directive);
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    pub trait ReportSink: Send + Sync + 'static {
+        fn report(&self, report: ViolationReport);
+    }
+
+    impl<F> ReportSink for F
+    where
+        F: Fn(ViolationReport) + Send + Sync + 'static,
+    {
+        fn report(&self, report: ViolationReport) {
+            self(report)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_support {
+    use std::{borrow::Cow, fmt, marker::PhantomData};
+
+    use schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+    use serde::{
+        de::{self, value::MapAccessDeserializer, MapAccess, SeqAccess, Visitor},
+        ser::{self, SerializeMap},
+        Deserialize, Deserializer, Serialize, Serializer,
+    };
+
+    use super::*;
+
+    const DIRECTIVES: &[&str] = &[
+        "child-src",
+        "connect-src",
+        "default-src",
+        "font-src",
+        "frame-src",
+        "img-src",
+        "manifest-src",
+        "media-src",
+        "object-src",
+        "prefetch-src",
+        "script-src",
+        "script-src-elem",
+        "script-src-attr",
+        "style-src",
+        "style-src-elem",
+        "style-src-attr",
+        "worker-src",
+        "base-uri",
+        "sandbox",
+        "form-action",
+        "frame-ancestors",
+        "report-to",
+        "report-uri",
+        "require-trusted-types-for",
+        "trusted-types",
+        "upgrade-insecure-requests",
+    ];
+
+    fn is_false(value: &bool) -> bool {
+        !*value
+    }
+
+    // Map that keeps the order of its entries, e.g. CSP directives.
+    struct Entries<K, V>(Vec<(K, V)>);
+
+    impl<K, V> Entries<K, V> {
+        fn is_empty(&self) -> bool {
+            self.0.is_empty()
+        }
+    }
+
+    impl<K, V> Default for Entries<K, V> {
+        fn default() -> Self {
+            Self(Vec::new())
+        }
+    }
+
+    impl<K: Serialize, V: Serialize> Serialize for Entries<K, V> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let mut map = serializer.serialize_map(Some(self.0.len()))?;
+            for (key, value) in self.0.iter() {
+                map.serialize_entry(key, value)?;
+            }
+            map.end()
+        }
+    }
+
+    impl<'de, K: Deserialize<'de>, V: Deserialize<'de>> Deserialize<'de> for Entries<K, V> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct EntriesVisitor<K, V>(PhantomData<(K, V)>);
+
+            impl<'de, K: Deserialize<'de>, V: Deserialize<'de>> Visitor<'de> for EntriesVisitor<K, V> {
+                type Value = Entries<K, V>;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    write!(f, "a map")
+                }
+
+                fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+                    let mut entries = Vec::new();
+                    while let Some(entry) = map.next_entry()? {
+                        entries.push(entry);
+                    }
+                    Ok(Entries(entries))
+                }
+            }
+
+            deserializer.deserialize_map(EntriesVisitor(PhantomData))
+        }
+    }
+
+    impl<K: JsonSchema, V: JsonSchema> JsonSchema for Entries<K, V> {
+        fn inline_schema() -> bool {
+            true
+        }
+
+        fn schema_name() -> Cow<'static, str> {
+            format!("Entries_{}_{}", K::schema_name(), V::schema_name()).into()
+        }
+
+        fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+            json_schema!({
+                "type": "object",
+                "propertyNames": generator.subschema_for::<K>(),
+                "additionalProperties": generator.subschema_for::<V>(),
+            })
+        }
+    }
+
+    // A single value, or a list for headers that can be sent more than once.
+    struct OneOrMany<T>(Vec<T>);
+
+    impl<T> OneOrMany<T> {
+        fn is_empty(&self) -> bool {
+            self.0.is_empty()
+        }
+    }
+
+    impl<T> Default for OneOrMany<T> {
+        fn default() -> Self {
+            Self(Vec::new())
+        }
+    }
+
+    impl<T: Serialize> Serialize for OneOrMany<T> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match self.0.as_slice() {
+                [value] => value.serialize(serializer),
+                values => values.serialize(serializer),
+            }
+        }
+    }
+
+    impl<'de, T: Deserialize<'de>> Deserialize<'de> for OneOrMany<T> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            struct OneOrManyVisitor<T>(PhantomData<T>);
+
+            impl<'de, T: Deserialize<'de>> Visitor<'de> for OneOrManyVisitor<T> {
+                type Value = OneOrMany<T>;
+
+                fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                    write!(f, "a map or a list of maps")
+                }
+
+                fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                    Ok(OneOrMany(vec![T::deserialize(
+                        MapAccessDeserializer::new(map),
+                    )?]))
+                }
+
+                fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+                    let mut values = Vec::new();
+                    while let Some(value) = seq.next_element()? {
+                        values.push(value);
+                    }
+                    Ok(OneOrMany(values))
+                }
+            }
+
+            deserializer.deserialize_any(OneOrManyVisitor(PhantomData))
+        }
+    }
+
+    impl<T: JsonSchema> JsonSchema for OneOrMany<T> {
+        fn inline_schema() -> bool {
+            true
+        }
+
+        fn schema_name() -> Cow<'static, str> {
+            format!("OneOrMany_{}", T::schema_name()).into()
+        }
+
+        fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+            let schema = generator.subschema_for::<T>();
+            json_schema!({
+                "anyOf": [schema, { "type": "array", "items": schema }],
+            })
+        }
+    }
+
+    // `Cross-Origin-Embedder-Policy` or `Cross-Origin-Opener-Policy`, with the endpoint of a `ReportingPolicy`
+    #[derive(Serialize, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    struct CrossOriginPolicyConfig<P> {
+        policy: P,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        report_to: Option<String>,
+    }
+
+    // `Cross-Origin-Embedder-Policy` and `Cross-Origin-Opener-Policy` only differ in their policies.
+    macro_rules! cross_origin_policy {
+        ($header:ident, $policies:expr, $description:expr) => {
+            impl CrossOriginPolicyConfig<$header> {
+                fn from_header(header: &dyn Header) -> Option<Self> {
+                    let any = header as &dyn Any;
+                    if let Some(policy) = any.downcast_ref::<$header>() {
+                        return Some(Self {
+                            policy: policy.clone(),
+                            report_to: None,
+                        });
+                    }
+                    any.downcast_ref::<ReportingPolicy<$header>>()
+                        .map(|header| Self {
+                            policy: header.policy().clone(),
+                            report_to: Some(header.endpoint().to_string()),
+                        })
+                }
+
+                fn add_to(self, helmet: Helmet) -> Helmet {
+                    match self.report_to {
+                        Some(endpoint) => helmet.add(self.policy.report_to(&endpoint)),
+                        None => helmet.add(self.policy),
+                    }
+                }
+            }
+
+            impl JsonSchema for CrossOriginPolicyConfig<$header> {
+                fn schema_name() -> Cow<'static, str> {
+                    stringify!($header).into()
+                }
+
+                fn json_schema(_: &mut SchemaGenerator) -> Schema {
+                    json_schema!({
+                        "description": $description,
+                        "type": "object",
+                        "properties": {
+                            "policy": { "enum": $policies },
+                            "report_to": {
+                                "description": "Name of the reporting endpoint violations are sent to",
+                                "type": "string",
+                            },
+                        },
+                        "required": ["policy"],
+                        "additionalProperties": false,
+                    })
+                }
+            }
+        };
+    }
+
+    cross_origin_policy!(
+        CrossOriginEmbedderPolicy,
+        ["unsafe-none", "require-corp", "credentialless"],
+        "`Cross-Origin-Embedder-Policy` header"
+    );
+
+    cross_origin_policy!(
+        CrossOriginOpenerPolicy,
+        ["same-origin", "same-origin-allow-popups", "unsafe-none"],
+        "`Cross-Origin-Opener-Policy` header"
+    );
+
+    impl Serialize for AllowlistEntry {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            match self {
+                AllowlistEntry::Origin(origin) => serializer.serialize_str(origin),
+                keyword => serializer.collect_str(keyword),
+            }
+        }
+    }
+
+    impl<'de> Deserialize<'de> for AllowlistEntry {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            String::deserialize(deserializer).map(AllowlistEntry::from)
+        }
+    }
+
+    impl JsonSchema for AllowlistEntry {
+        fn schema_name() -> Cow<'static, str> {
+            "AllowlistEntry".into()
+        }
+
+        fn json_schema(_: &mut SchemaGenerator) -> Schema {
+            json_schema!({
+                "description": "`*`, `self`, `src` or an origin, e.g. `https://example.com`",
+                "type": "string",
+            })
+        }
+    }
+
+    /// `Permissions-Policy` header
+    #[derive(Serialize, Deserialize, JsonSchema)]
+    #[serde(deny_unknown_fields)]
+    #[schemars(rename = "PermissionsPolicy")]
+    struct PermissionsPolicyConfig {
+        /// Allowlist of each feature, an empty allowlist disables the feature
+        features: Entries<PermissionsPolicyFeature, Vec<AllowlistEntry>>,
+        /// Send the policy as `Permissions-Policy-Report-Only`
+        #[serde(default, skip_serializing_if = "is_false")]
+        report_only: bool,
+    }
+
+    impl Serialize for PermissionsPolicy {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            PermissionsPolicyConfig {
+                features: Entries(self.features.clone()),
+                report_only: self.report_only,
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for PermissionsPolicy {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let config = PermissionsPolicyConfig::deserialize(deserializer)?;
+            let mut policy = config
+                .features
+                .0
+                .into_iter()
+                .fold(PermissionsPolicy::new(), |policy, (feature, allowlist)| {
+                    policy.allow(feature, allowlist)
+                });
+            policy.report_only = config.report_only;
+            Ok(policy)
+        }
+    }
+
+    impl JsonSchema for PermissionsPolicy {
+        fn schema_name() -> Cow<'static, str> {
+            PermissionsPolicyConfig::schema_name()
+        }
+
+        fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+            PermissionsPolicyConfig::json_schema(generator)
+        }
+    }
+
+    impl Serialize for ReportingEndpoints {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            Entries(self.0.clone()).serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for ReportingEndpoints {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let endpoints = Entries::<String, String>::deserialize(deserializer)?;
+            Ok(endpoints
+                .0
+                .iter()
+                .fold(ReportingEndpoints::new(), |endpoints, (name, url)| {
+                    endpoints.endpoint(name, url)
+                }))
+        }
+    }
+
+    impl JsonSchema for ReportingEndpoints {
+        fn schema_name() -> Cow<'static, str> {
+            "ReportingEndpoints".into()
+        }
+
+        fn json_schema(_: &mut SchemaGenerator) -> Schema {
+            json_schema!({
+                "description": "`Reporting-Endpoints` header, mapping endpoint names to URLs",
+                "type": "object",
+                "additionalProperties": { "type": "string" },
+            })
+        }
+    }
+
+    /// `Content-Security-Policy` header
+    #[derive(Serialize, Deserialize, JsonSchema)]
+    #[serde(deny_unknown_fields)]
+    #[schemars(rename = "ContentSecurityPolicy")]
+    struct ContentSecurityPolicyConfig {
+        /// Values of each directive, e.g. `"script-src": ["'self'", "https://cdn.example.com"]`. Directives without values, such as `upgrade-insecure-requests`, take an empty list.
+        #[schemars(with = "Entries<DirectiveName, Vec<String>>")]
+        directives: Entries<String, Vec<String>>,
+        /// Directives not known to Helmet, e.g. `"fenced-frame-src": ["'none'"]`, sent as they are
+        #[serde(default, skip_serializing_if = "Entries::is_empty")]
+        other_directives: Entries<String, Vec<String>>,
+        /// Send the policy as `Content-Security-Policy-Report-Only`
+        #[serde(default, skip_serializing_if = "is_false")]
+        report_only: bool,
+        /// Add a per-request nonce to `script-src` and `script-src-elem`
+        #[serde(default, skip_serializing_if = "is_false")]
+        script_nonce: bool,
+        /// Add a per-request nonce to `style-src` and `style-src-elem`
+        #[serde(default, skip_serializing_if = "is_false")]
+        style_nonce: bool,
+    }
+
+    struct DirectiveName;
+
+    impl JsonSchema for DirectiveName {
+        fn schema_name() -> Cow<'static, str> {
+            "DirectiveName".into()
+        }
+
+        fn json_schema(_: &mut SchemaGenerator) -> Schema {
+            json_schema!({ "enum": DIRECTIVES })
+        }
+    }
+
+    impl Serialize for ContentSecurityPolicy<'_> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            let (directives, other_directives) = self
+                .directives
+                .iter()
+                .map(|directive| (directive.name().to_string(), directive.values()))
+                .partition(|(name, _)| DIRECTIVES.contains(&name.as_str()));
+            ContentSecurityPolicyConfig {
+                directives: Entries(directives),
+                other_directives: Entries(other_directives),
+                report_only: self.report_only,
+                script_nonce: self.script_nonce,
+                style_nonce: self.style_nonce,
+            }
+            .serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for ContentSecurityPolicy<'static> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let config = ContentSecurityPolicyConfig::deserialize(deserializer)?;
+            let mut policy = ContentSecurityPolicy::new();
+            for (name, values) in config.directives.0 {
+                if !DIRECTIVES.contains(&name.as_str()) {
+                    return Err(de::Error::unknown_field(&name, DIRECTIVES));
+                }
+                policy = policy.directive(parse_directive(&name, &values)?);
+            }
+            for (name, values) in config.other_directives.0 {
+                if DIRECTIVES.contains(&name.as_str()) {
+                    return Err(de::Error::custom(format!(
+                        "`{}` is a known directive, set it in `directives`",
+                        name
+                    )));
+                }
+                policy = policy.directive(parse_directive(&name, &values)?);
+            }
+            policy.report_only = config.report_only;
+            policy.script_nonce = config.script_nonce;
+            policy.style_nonce = config.style_nonce;
+            Ok(policy)
+        }
+    }
+
+    fn parse_directive<E: de::Error>(
+        name: &str,
+        values: &[String],
+    ) -> Result<ContentSecurityPolicyDirective<'static>, E> {
+        let directive = std::iter::once(name)
+            .chain(values.iter().map(String::as_str))
+            .collect::<Vec<_>>()
+            .join(" ")
+            .parse::<ContentSecurityPolicyDirective>()
+            .map_err(|err| E::custom(format!("invalid `{}` directive: {}", name, err)))?;
+        if directive.name() != name {
+            return Err(E::custom(format!("invalid directive name `{}`", name)));
+        }
+        Ok(directive.into_owned())
+    }
+
+    impl JsonSchema for ContentSecurityPolicy<'static> {
+        fn schema_name() -> Cow<'static, str> {
+            ContentSecurityPolicyConfig::schema_name()
+        }
+
+        fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+            ContentSecurityPolicyConfig::json_schema(generator)
+        }
+    }
+
+    /// Helmet configuration. Every header is optional, headers that are not set are not sent.
+    #[derive(Default, Serialize, Deserialize, JsonSchema)]
+    #[serde(default, deny_unknown_fields)]
+    #[schemars(rename = "Helmet")]
+    struct HelmetConfig {
+        /// `Content-Security-Policy` header, or a list of policies which are all enforced
+        #[serde(skip_serializing_if = "OneOrMany::is_empty")]
+        content_security_policy: OneOrMany<ContentSecurityPolicy<'static>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cross_origin_embedder_policy: Option<CrossOriginPolicyConfig<CrossOriginEmbedderPolicy>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cross_origin_opener_policy: Option<CrossOriginPolicyConfig<CrossOriginOpenerPolicy>>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        cross_origin_resource_policy: Option<CrossOriginResourcePolicy>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        origin_agent_cluster: Option<OriginAgentCluster>,
+        /// `Permissions-Policy` header, or a list of policies, e.g. an enforced and a report-only policy
+        #[serde(skip_serializing_if = "OneOrMany::is_empty")]
+        permissions_policy: OneOrMany<PermissionsPolicy>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        feature_policy: Option<FeaturePolicy>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        referrer_policy: Option<ReferrerPolicy>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        reporting_endpoints: Option<ReportingEndpoints>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        report_to: Option<ReportTo>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        strict_transport_security: Option<StrictTransportSecurity>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        x_content_type_options: Option<XContentTypeOptions>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        x_dns_prefetch_control: Option<XDNSPrefetchControl>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        x_download_options: Option<XDownloadOptions>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        x_frame_options: Option<XFrameOptions>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        x_permitted_cross_domain_policies: Option<XPermittedCrossDomainPolicies>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        x_xss_protection: Option<XXSSProtection>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        x_powered_by: Option<XPoweredBy>,
+        /// Default conflict strategy, `keep-existing` when not set
+        #[serde(skip_serializing_if = "Option::is_none")]
+        conflict_strategy: Option<ConflictStrategy>,
+        /// Conflict strategy of individual headers, by header name
+        #[serde(skip_serializing_if = "Entries::is_empty")]
+        header_conflict_strategies: Entries<String, ConflictStrategy>,
+        /// Responses individual headers are sent on, by header name
+        #[serde(skip_serializing_if = "Entries::is_empty")]
+        header_scopes: Entries<String, HeaderScope>,
+        /// When the development relaxations are applied, `off` when not set
+        #[serde(skip_serializing_if = "Option::is_none")]
+        development_mode: Option<DevelopmentMode>,
+        /// Only send `Strict-Transport-Security` over HTTPS, `false` when not set
+        #[serde(skip_serializing_if = "Option::is_none")]
+        hsts_https_only: Option<bool>,
+        /// IP addresses or CIDR ranges of proxies whose `Forwarded` and `X-Forwarded-Proto` headers are trusted
+        #[serde(skip_serializing_if = "Vec::is_empty")]
+        trusted_proxies: Vec<String>,
+    }
+
+    impl Serialize for Helmet {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if !self.path_rules.is_empty() {
+                return Err(ser::Error::custom("path rules can't be serialized"));
+            }
+            let mut config = HelmetConfig {
+                conflict_strategy: self.conflict_strategy,
+                development_mode: self.development_mode,
+                hsts_https_only: self.hsts_https_only,
+                trusted_proxies: self.trusted_proxies.clone(),
+                ..Default::default()
+            };
+            for header in self.headers.iter() {
+                let any = header.as_ref() as &dyn Any;
+                macro_rules! set {
+                    ($field:ident, $header:ty) => {
+                        if let Some(header) = any.downcast_ref::<$header>() {
+                            config.$field = Some(header.clone());
+                            continue;
+                        }
+                    };
+                }
+                if let Some(policy) = any.downcast_ref::<ContentSecurityPolicy<'static>>() {
+                    config.content_security_policy.0.push(policy.clone());
+                    continue;
+                }
+                if let Some(policy) = any.downcast_ref::<PermissionsPolicy>() {
+                    config.permissions_policy.0.push(policy.clone());
+                    continue;
+                }
+                if let Some(policy) =
+                    CrossOriginPolicyConfig::<CrossOriginEmbedderPolicy>::from_header(
+                        header.as_ref(),
+                    )
+                {
+                    config.cross_origin_embedder_policy = Some(policy);
+                    continue;
+                }
+                if let Some(policy) =
+                    CrossOriginPolicyConfig::<CrossOriginOpenerPolicy>::from_header(header.as_ref())
+                {
+                    config.cross_origin_opener_policy = Some(policy);
+                    continue;
+                }
+                set!(cross_origin_resource_policy, CrossOriginResourcePolicy);
+                set!(origin_agent_cluster, OriginAgentCluster);
+                set!(feature_policy, FeaturePolicy);
+                set!(referrer_policy, ReferrerPolicy);
+                set!(reporting_endpoints, ReportingEndpoints);
+                set!(report_to, ReportTo);
+                set!(strict_transport_security, StrictTransportSecurity);
+                set!(x_content_type_options, XContentTypeOptions);
+                set!(x_dns_prefetch_control, XDNSPrefetchControl);
+                set!(x_download_options, XDownloadOptions);
+                set!(x_frame_options, XFrameOptions);
+                set!(
+                    x_permitted_cross_domain_policies,
+                    XPermittedCrossDomainPolicies
+                );
+                set!(x_xss_protection, XXSSProtection);
+                set!(x_powered_by, XPoweredBy);
+                if header.condition().is_some() {
+                    return Err(ser::Error::custom(format!(
+                        "conditional header `{}` can't be serialized",
+                        header.name()
+                    )));
+                }
+                return Err(ser::Error::custom(format!(
+                    "custom header `{}` can't be serialized",
+                    header.name()
+                )));
+            }
+            config.header_conflict_strategies = Entries(self.header_conflict_strategies.clone());
+            config.header_scopes = Entries(self.header_scopes.clone());
+            config.serialize(serializer)
+        }
+    }
+
+    impl<'de> Deserialize<'de> for Helmet {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            let config = HelmetConfig::deserialize(deserializer)?;
+            let mut helmet = Helmet::new();
+            for policy in config.content_security_policy.0 {
+                helmet = helmet.add_additional(policy);
+            }
+            for policy in config.permissions_policy.0 {
+                helmet = helmet.add(policy);
+            }
+            if let Some(policy) = config.cross_origin_embedder_policy {
+                helmet = policy.add_to(helmet);
+            }
+            if let Some(policy) = config.cross_origin_opener_policy {
+                helmet = policy.add_to(helmet);
+            }
+            macro_rules! add {
+                ($($field:ident),*) => {
+                    $(if let Some(header) = config.$field {
+                        helmet = helmet.add(header);
+                    })*
+                };
+            }
+            add!(
+                cross_origin_resource_policy,
+                origin_agent_cluster,
+                feature_policy,
+                referrer_policy,
+                reporting_endpoints,
+                report_to,
+                strict_transport_security,
+                x_content_type_options,
+                x_dns_prefetch_control,
+                x_download_options,
+                x_frame_options,
+                x_permitted_cross_domain_policies,
+                x_xss_protection,
+                x_powered_by
+            );
+            helmet.conflict_strategy = config.conflict_strategy;
+            helmet.development_mode = config.development_mode;
+            helmet.hsts_https_only = config.hsts_https_only;
+            helmet.trusted_proxies = config.trusted_proxies;
+            for (name, strategy) in config.header_conflict_strategies.0 {
+                helmet = helmet.header_conflict_strategy(&name, strategy);
+            }
+            for (name, scope) in config.header_scopes.0 {
+                helmet = helmet.header_scope(&name, scope);
+            }
+            helmet.validate().map_err(de::Error::custom)?;
+            Ok(helmet)
+        }
+    }
+
+    impl JsonSchema for Helmet {
+        fn schema_name() -> Cow<'static, str> {
+            HelmetConfig::schema_name()
+        }
+
+        fn json_schema(generator: &mut SchemaGenerator) -> Schema {
+            HelmetConfig::json_schema(generator)
+        }
     }
 }
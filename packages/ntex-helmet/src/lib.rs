@@ -40,6 +40,8 @@
 //! X-XSS-Protection: 0
 //! ```
 //!
+//! Headers that only apply to documents, such as `X-Frame-Options`, are skipped on responses whose `Content-Type` isn't a document, e.g. JSON or images, see [`Header::scope`].
+//!
 //! This might be a good starting point for most users, but it is highly recommended to spend some time with the documentation for each header, and adjust them to your needs.
 //!
 //! # Configuration
@@ -74,16 +76,170 @@
 //!     .await
 //! }
 //! ```
+//!
+//! # Local development
+//!
+//! `Strict-Transport-Security` and `upgrade-insecure-requests` make browsers switch `localhost` to HTTPS for every project served on it. With [`DevelopmentMode::Loopback`], requests whose `Host` is a loopback address get the headers without them, and `ws://localhost:*` is allowed in `connect-src` for hot-reload tooling. Only requests from a loopback peer address without `Forwarded` or `X-Forwarded-*` headers are relaxed, so a reverse proxy on the same machine has to add `X-Forwarded-For`, see [`helmet_core::Helmet::development`].
+//!
+//! ```no_run
+//! use ntex::web;
+//! use ntex_helmet::{DevelopmentMode, Helmet};
+//!
+//! #[ntex::main]
+//! async fn main() -> std::io::Result<()> {
+//!     web::HttpServer::new(move || {
+//!         web::App::new()
+//!             .wrap(Helmet::default().development(DevelopmentMode::Loopback))
+//!             .service(web::resource("/").to(|| async { "Hello, world!" }))
+//!     })
+//!     .bind(("127.0.0.1", 8080))?
+//!     .run()
+//!     .await
+//! }
+//! ```
+//!
+//! # HTTPS detection
+//!
+//! With `hsts_https_only(true)`, `Strict-Transport-Security` is only sent when the request arrived over HTTPS, as browsers ignore it over HTTP. Behind a reverse proxy terminating TLS, add the proxy with `trusted_proxy` so the `Forwarded` and `X-Forwarded-Proto` headers it sets are used.
+//!
+//! ```
+//! use ntex_helmet::Helmet;
+//!
+//! let helmet = Helmet::default()
+//!     .hsts_https_only(true)
+//!     .trusted_proxy("10.0.0.0/8")
+//!     .try_build()
+//!     .unwrap();
+//! ```
+//!
+//! # Path rules
+//!
+//! Parts of an application often need different headers. Path rules override or remove headers for the requests whose path matches a pattern, the first matching rule applies.
+//!
+//! ```
+//! use ntex_helmet::{ContentSecurityPolicy, Helmet, PathRule, XFrameOptions};
+//!
+//! let helmet = Helmet::default()
+//!     .path("/admin/*", PathRule::new().add(XFrameOptions::deny()))
+//!     .path(
+//!         "/docs/*",
+//!         PathRule::new().add(ContentSecurityPolicy::default().script_src(vec!["'self'", "'unsafe-inline'"])),
+//!     )
+//!     .path("/healthz", PathRule::exclude_all())
+//!     .path("/metrics", PathRule::exclude_all())
+//!     .try_build()
+//!     .unwrap();
+//! ```
+//!
+//! # Response overrides
+//!
+//! Handlers can change the headers of a single response by inserting [`ResponseOverrides`] into the response extensions, e.g. to make one page embeddable.
+//! Only the overridden headers are rendered for the response. Overrides that can't be applied, see [`ResponseOverrides::validate`], are logged with `log` and the configured headers are sent instead.
+//!
+//! ```no_run
+//! use ntex::web;
+//! use ntex_helmet::{ContentSecurityPolicy, Helmet, ResponseOverrides};
+//!
+//! async fn widget() -> web::HttpResponse {
+//!     let res = web::HttpResponse::Ok()
+//!         .content_type("text/html")
+//!         .body("<div id=\"widget\"></div>");
+//!     res.extensions_mut().insert(
+//!         ResponseOverrides::new()
+//!             .skip("X-Frame-Options")
+//!             .extend_content_security_policy(
+//!                 ContentSecurityPolicy::new().frame_ancestors(vec!["https://partner.example.com"]),
+//!             ),
+//!     );
+//!     res
+//! }
+//!
+//! #[ntex::main]
+//! async fn main() -> std::io::Result<()> {
+//!     web::HttpServer::new(move || {
+//!         web::App::new()
+//!             .wrap(Helmet::default())
+//!             .service(web::resource("/widget").to(widget))
+//!     })
+//!     .bind(("127.0.0.1", 8080))?
+//!     .run()
+//!     .await
+//! }
+//! ```
+//!
+//! # Error responses
+//!
+//! Errors returned by handlers and extractors, and the `404 Not Found` of unmatched routes, are rendered into responses by ntex before they reach the middleware, so they get the same headers as any other response.
+//! A service or middleware that returns `Err` instead is rendered by the server after the middleware chain has finished, without any of the headers. `Helmet` can't render it itself: a response needs the request, which the wrapped service takes ownership of, and ntex doesn't allow keeping a copy of the request while the service runs. Inside services and middleware wrapped by `Helmet`, render errors with [`WebRequest::error_response`](ntex::web::WebRequest::error_response) instead of returning them.
+//! Middleware registered after `Helmet` with `wrap` runs outside of it, so responses it produces on its own don't pass through `Helmet`; register `Helmet` last to cover them.
+//!
+//! ```no_run
+//! use ntex::web::{self, error, DefaultError, WebRequest};
+//! use ntex_helmet::Helmet;
+//!
+//! #[ntex::main]
+//! async fn main() -> std::io::Result<()> {
+//!     web::HttpServer::new(move || {
+//!         web::App::new()
+//!             .wrap(Helmet::default())
+//!             .service(web::service("/").finish(|req: WebRequest<DefaultError>| async move {
+//!                 Ok::<_, web::Error>(req.error_response(error::ErrorForbidden("forbidden")))
+//!             }))
+//!     })
+//!     .bind(("127.0.0.1", 8080))?
+//!     .run()
+//!     .await
+//! }
+//! ```
+//!
+//! # Nonces
+//!
+//! When the `Content-Security-Policy` is configured with `script_nonce()` or `style_nonce()`, a fresh [`Nonce`] is generated for every request.
+//! It is stored in the request extensions, so handlers can render it into the `nonce` attribute of inline elements.
+//!
+//! ```no_run
+//! use ntex::web::{self, HttpRequest};
+//! use ntex_helmet::{ContentSecurityPolicy, Helmet, Nonce};
+//!
+//! async fn index(req: HttpRequest) -> web::HttpResponse {
+//!     let nonce = req.extensions().get::<Nonce>().cloned().unwrap();
+//!     web::HttpResponse::Ok()
+//!         .content_type("text/html")
+//!         .body(format!("<script nonce=\"{}\">alert(1)</script>", nonce))
+//! }
+//!
+//! #[ntex::main]
+//! async fn main() -> std::io::Result<()> {
+//!     web::HttpServer::new(move || {
+//!         web::App::new()
+//!             .wrap(Helmet::new().add(ContentSecurityPolicy::default().script_nonce()))
+//!             .service(web::resource("/").to(index))
+//!     })
+//!     .bind(("127.0.0.1", 4200))?
+//!     .run()
+//!     .await
+//! }
+//! ```
+use std::borrow::Cow;
+#[cfg(feature = "serde")]
+use std::sync::Arc;
+
 use ntex::{
     forward_poll_ready, forward_poll_shutdown,
     http::{
-        header::{HeaderName, HeaderValue},
+        header::{self, HeaderName, HeaderValue},
+        uri::Scheme,
         HeaderMap,
     },
     util::BoxFuture,
     web::{WebRequest, WebResponse},
     Middleware, Service, ServiceCtx,
 };
+#[cfg(feature = "serde")]
+use ntex::{
+    util::BytesMut,
+    web::{self, types::Payload, ErrorRenderer, HttpRequest, HttpResponse},
+};
 
 use helmet_core::Helmet as HelmetCore;
 
@@ -92,7 +248,12 @@ pub use helmet_core::*;
 
 pub struct HelmetMiddleware<S> {
     service: S,
-    headers: HeaderMap,
+    headers: CompiledHeaders,
+    development_mode: DevelopmentMode,
+    development_headers: Option<CompiledHeaders>,
+    hsts_https_only: bool,
+    trusted_proxies: TrustedProxies,
+    path_rules: Vec<(PathPattern, CompiledHeaders, Option<CompiledHeaders>)>,
 }
 
 impl<S, E> Service<WebRequest<E>> for HelmetMiddleware<S>
@@ -102,19 +263,171 @@ where
 {
     type Response = WebResponse;
     type Error = S::Error;
-    type Future<'f> =
-        BoxFuture<'f, Result<Self::Response, Self::Error>> where S: 'f, E: 'f;
+    type Future<'f>
+        = BoxFuture<'f, Result<Self::Response, Self::Error>>
+    where
+        S: 'f,
+        E: 'f;
 
     forward_poll_ready!(service);
     forward_poll_shutdown!(service);
 
     fn call<'a>(&'a self, req: WebRequest<E>, ctx: ServiceCtx<'a, Self>) -> Self::Future<'a> {
         Box::pin(async move {
+            let host = req
+                .headers()
+                .get(header::HOST)
+                .and_then(|value| value.to_str().ok())
+                .or_else(|| req.uri().host());
+            let (headers, development_headers) = self
+                .path_rules
+                .iter()
+                .find(|(pattern, _, _)| pattern.matches(req.path()))
+                .map_or(
+                    (&self.headers, &self.development_headers),
+                    |(_, headers, development_headers)| (headers, development_headers),
+                );
+            let headers = match development_headers {
+                Some(headers)
+                    if self.development_mode.applies_to(
+                        host,
+                        req.peer_addr().map(|addr| addr.ip()),
+                        req.headers()
+                            .keys()
+                            .any(|name| is_forwarding_header(name.as_str())),
+                    ) =>
+                {
+                    headers
+                }
+                _ => headers,
+            };
+            let hsts = !self.hsts_https_only || self.is_https(&req);
+
+            let nonce = if headers.nonce_headers.is_empty() {
+                None
+            } else {
+                let nonce = Nonce::generate();
+                req.extensions_mut().insert(nonce.clone());
+                Some(nonce)
+            };
+            // kept for evaluating the header conditions against the response, overrides can't add
+            // conditions (see `ResponseOverrides::replace`), so only the configured headers need it
+            let request = headers
+                .is_conditional()
+                .then(|| (req.method().clone(), req.headers().clone()));
+
+            // handler and extractor errors arrive here as responses. An `Err` can't be rendered
+            // here: the wrapped service owns the `WebRequest`, and keeping a clone of its
+            // `HttpRequest` to build the response from would make `WebRequest::into_parts` panic
             let mut res = ctx.call(&self.service, req).await?;
 
+            // invalid overrides are not applied, the configured headers are still sent
+            let overrides = res
+                .response()
+                .extensions_mut()
+                .remove::<ResponseOverrides>()
+                .filter(|overrides| match overrides.validate() {
+                    Ok(()) => true,
+                    Err(err) => {
+                        log::warn!("response overrides not applied: {}", err);
+                        false
+                    }
+                });
+
             // set response headers
-            for (name, value) in self.headers.iter() {
-                res.headers_mut().append(name.clone(), value.clone());
+            if nonce.is_none()
+                && hsts
+                && request.is_none()
+                && headers.timed_headers.is_empty()
+                && overrides.is_none()
+            {
+                apply_headers(
+                    res.headers_mut(),
+                    &headers.headers,
+                    &headers.conflict_strategies,
+                    &headers.scopes,
+                );
+            } else {
+                let mut values = headers.headers.clone();
+                let exchange = request
+                    .as_ref()
+                    .map(|(method, request_headers)| RequestResponse {
+                        method: method.as_str(),
+                        status: res.status().as_u16(),
+                        request_header: |name: &str| {
+                            request_headers
+                                .get(name)
+                                .and_then(|value| value.to_str().ok())
+                        },
+                        response_header: |name: &str| {
+                            res.headers()
+                                .get(name)
+                                .and_then(|value| value.to_str().ok())
+                        },
+                    });
+                let applies = |condition: Option<&Condition>| {
+                    condition.is_none_or(|condition| {
+                        exchange
+                            .as_ref()
+                            .is_some_and(|exchange| condition.matches(exchange))
+                    })
+                };
+                if let Some(nonce) = &nonce {
+                    for (name, template, condition) in headers.nonce_headers.iter() {
+                        if applies(condition.as_ref()) {
+                            let value = HeaderValue::from_str(&template.render(nonce))
+                                .expect("invalid header value");
+                            values.append(name.clone(), value);
+                        }
+                    }
+                }
+                for (name, index, condition) in headers.timed_headers.iter() {
+                    if applies(condition.as_ref()) {
+                        values.append(name.clone(), headers.timed_value(*index));
+                    }
+                }
+                for (name, value, condition) in headers.conditional_headers.iter() {
+                    if applies(Some(condition)) {
+                        values.append(name.clone(), value.clone());
+                    }
+                }
+                let mut conflict_strategies = Cow::Borrowed(&headers.conflict_strategies);
+                let mut scopes = Cow::Borrowed(&headers.scopes);
+                if let Some(overrides) = &overrides {
+                    // only the overridden headers are rendered, the others keep their compiled values
+                    let (removed, added) = headers.core.overridden_headers(overrides);
+                    for name in removed {
+                        if let Ok(name) = HeaderName::try_from(name) {
+                            values.remove(&name);
+                        }
+                    }
+                    for header in added {
+                        let name =
+                            HeaderName::try_from(header.name()).expect("invalid header name");
+                        if !conflict_strategies.iter().any(|(n, _)| *n == name) {
+                            let strategy = headers.core.conflict_strategy_for(header.name());
+                            conflict_strategies.to_mut().push((name.clone(), strategy));
+                        }
+                        scopes.to_mut().retain(|(n, _)| *n != name);
+                        let scope = headers.core.scope_for(header.as_ref());
+                        if scope != HeaderScope::Any {
+                            scopes.to_mut().push((name.clone(), scope));
+                        }
+                        if !applies(header.condition()) {
+                            continue;
+                        }
+                        let value = match (header.nonce_template(), &nonce) {
+                            (Some(template), Some(nonce)) => template.render(nonce),
+                            _ => header.value(),
+                        };
+                        let value = HeaderValue::from_str(&value).expect("invalid header value");
+                        values.append(name, value);
+                    }
+                }
+                if !hsts {
+                    values.remove(header::STRICT_TRANSPORT_SECURITY);
+                }
+                apply_headers(res.headers_mut(), &values, &conflict_strategies, &scopes);
             }
 
             Ok(res)
@@ -122,53 +435,479 @@ where
     }
 }
 
+impl<S> HelmetMiddleware<S> {
+    fn is_https<E>(&self, req: &WebRequest<E>) -> bool {
+        // the last value, a proxy appends its own after the ones it received
+        let header = |name: &str| {
+            req.headers()
+                .get_all(name)
+                .last()
+                .and_then(|value| value.to_str().ok())
+        };
+        self.trusted_proxies.is_https(
+            req.uri().scheme() == Some(&Scheme::HTTPS) || req.app_config().secure(),
+            req.peer_addr().map(|addr| addr.ip()),
+            header("forwarded"),
+            header("x-forwarded-proto"),
+        )
+    }
+}
+
+// Applies the headers in the order of `conflict_strategies`, which lists every header name once. Names without values, e.g. `Strict-Transport-Security` over HTTP, and names outside their scope for the response `Content-Type` are skipped.
+fn apply_headers(
+    target: &mut HeaderMap,
+    headers: &HeaderMap,
+    conflict_strategies: &[(HeaderName, ConflictStrategy)],
+    scopes: &[(HeaderName, HeaderScope)],
+) {
+    let kind = ResponseKind::from_content_type(
+        target
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok()),
+    );
+    for (name, strategy) in conflict_strategies.iter() {
+        if !headers.contains_key(name)
+            || scopes
+                .iter()
+                .any(|(n, scope)| n == name && !scope.includes(kind))
+        {
+            continue;
+        }
+        match strategy {
+            ConflictStrategy::KeepExisting if target.contains_key(name) => continue,
+            ConflictStrategy::Overwrite => {
+                target.remove(name);
+            }
+            _ => {}
+        }
+        for value in headers.get_all(name) {
+            target.append(name.clone(), value.clone());
+        }
+    }
+}
+
 /// Helmet middleware
 /// ```rust
 /// use ntex::web;
 /// use ntex_helmet::Helmet;
+#[derive(Clone, Default)]
 pub struct Helmet(HelmetCore);
 
+impl std::fmt::Debug for Helmet {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
 impl Helmet {
     pub fn new() -> Self {
         Self(HelmetCore::new())
     }
 
+    /// Create the middleware from helmet.js options, returning warnings for options without a counterpart. See [`helmet_core::Helmet::from_helmetjs_options`].
+    #[cfg(feature = "serde")]
+    pub fn from_helmetjs_options(
+        options: &str,
+    ) -> Result<(Self, Vec<HelmetJsWarning>), HelmetError> {
+        let (core, warnings) = HelmetCore::from_helmetjs_options(options)?;
+        Ok((Self(core), warnings))
+    }
+
+    /// Create the middleware with the preset for JSON APIs. See [`helmet_core::Helmet::api`].
+    pub fn api() -> Self {
+        Self(HelmetCore::api())
+    }
+
+    /// Create the middleware with the preset for single-page applications. See [`helmet_core::Helmet::spa`].
+    pub fn spa() -> Self {
+        Self(HelmetCore::spa())
+    }
+
+    /// Create the middleware with the preset for static sites. See [`helmet_core::Helmet::static_site`].
+    pub fn static_site() -> Self {
+        Self(HelmetCore::static_site())
+    }
+
+    /// Create the middleware with the preset for WebAssembly applications. See [`helmet_core::Helmet::wasm_app`].
+    pub fn wasm_app() -> Self {
+        Self(HelmetCore::wasm_app())
+    }
+
+    /// Create the middleware with the strictest setting of every header. See [`helmet_core::Helmet::maximum`].
+    pub fn maximum() -> Self {
+        Self(HelmetCore::maximum())
+    }
+
+    #[allow(clippy::should_implement_trait)]
     pub fn add(self, middleware: impl helmet_core::Header + 'static) -> Self {
         Self(self.0.add(middleware))
     }
+
+    /// Add a header next to the headers with the same name, if the header allows multiple values. See [`helmet_core::Helmet::add_additional`].
+    pub fn add_additional(self, header: impl helmet_core::Header + 'static) -> Self {
+        Self(self.0.add_additional(header))
+    }
+
+    /// Replace every header with the same name and condition, same as [`Helmet::add`].
+    pub fn replace(self, header: impl helmet_core::Header + 'static) -> Self {
+        Self(self.0.replace(header))
+    }
+
+    /// Add a header that is only sent when `condition` matches the request and response. See [`helmet_core::Helmet::add_when`].
+    pub fn add_when(
+        self,
+        header: impl helmet_core::Header + 'static,
+        condition: Condition,
+    ) -> Self {
+        Self(self.0.add_when(header, condition))
+    }
+
+    /// Only send the headers with the given name when `condition` matches. See [`helmet_core::Helmet::when`].
+    pub fn when(self, name: &str, condition: Condition) -> Self {
+        Self(self.0.when(name, condition))
+    }
+
+    /// Remove every header of the given type.
+    pub fn remove<T: helmet_core::Header>(self) -> Self {
+        Self(self.0.remove::<T>())
+    }
+
+    /// Get the first header of the given type.
+    pub fn get<T: helmet_core::Header>(&self) -> Option<&T> {
+        self.0.get::<T>()
+    }
+
+    /// Get the first header of the given type, to modify it in place.
+    pub fn get_mut<T: helmet_core::Header>(&mut self) -> Option<&mut T> {
+        self.0.get_mut::<T>()
+    }
+
+    /// Iterate over the headers, in the order they were added.
+    pub fn iter(&self) -> impl Iterator<Item = &dyn helmet_core::Header> {
+        self.0.iter()
+    }
+
+    /// Merge another configuration into this one, resolving headers set by both according to `precedence`.
+    pub fn merge(self, other: Helmet, precedence: MergePrecedence) -> Self {
+        Self(self.0.merge(other.0, precedence))
+    }
+
+    /// Set when the relaxations for local development are applied, e.g. only for requests to loopback hosts. See [`helmet_core::Helmet::development`].
+    pub fn development(self, mode: DevelopmentMode) -> Self {
+        Self(self.0.development(mode))
+    }
+
+    /// Only send `Strict-Transport-Security` over HTTPS. See [`helmet_core::Helmet::hsts_https_only`].
+    pub fn hsts_https_only(self, enabled: bool) -> Self {
+        Self(self.0.hsts_https_only(enabled))
+    }
+
+    /// Trust the `Forwarded` and `X-Forwarded-Proto` headers of requests from `proxy`, an IP address or CIDR range. See [`helmet_core::Helmet::trusted_proxy`].
+    pub fn trusted_proxy(self, proxy: &str) -> Self {
+        Self(self.0.trusted_proxy(proxy))
+    }
+
+    /// Send different headers to the requests whose path matches `pattern`, e.g. `/admin/*`. See [`helmet_core::Helmet::path`].
+    pub fn path(self, pattern: &str, rule: PathRule) -> Self {
+        Self(self.0.path(pattern, rule))
+    }
+
+    /// Register a reporting endpoint, sent in the `Reporting-Endpoints` header, which other headers can reference by name.
+    pub fn reporting_endpoint(self, name: &str, url: &str) -> Self {
+        Self(self.0.reporting_endpoint(name, url))
+    }
+
+    /// Set how headers are applied when the response already has a header with the same name. Defaults to [`ConflictStrategy::KeepExisting`].
+    pub fn conflict_strategy(self, strategy: ConflictStrategy) -> Self {
+        Self(self.0.conflict_strategy(strategy))
+    }
+
+    /// Set the conflict strategy of a single header.
+    pub fn header_conflict_strategy(self, name: &str, strategy: ConflictStrategy) -> Self {
+        Self(self.0.header_conflict_strategy(name, strategy))
+    }
+
+    /// Set the responses a single header is sent on. See [`helmet_core::Helmet::header_scope`].
+    pub fn header_scope(self, name: &str, scope: HeaderScope) -> Self {
+        Self(self.0.header_scope(name, scope))
+    }
+
+    /// Validate the headers, returning an error naming the offending header if any header is invalid.
+    ///
+    /// Without validation, an invalid header panics when the middleware is created in each worker.
+    ///
+    /// ```
+    /// use ntex_helmet::{Helmet, HelmetError, XPoweredBy};
+    ///
+    /// let helmet = Helmet::new().add(XPoweredBy::new("PHP\n4.2.0")).try_build();
+    ///
+    /// assert!(matches!(
+    ///     helmet,
+    ///     Err(HelmetError::InvalidHeaderValue { name, .. }) if name == "X-Powered-By"
+    /// ));
+    /// ```
+    pub fn try_build(self) -> Result<Self, HelmetError> {
+        compile_headers(&self.0)?;
+        Ok(self)
+    }
+}
+
+struct CompiledHeaders {
+    // configuration the headers were compiled from
+    core: HelmetCore,
+    headers: HeaderMap,
+    nonce_headers: Vec<(HeaderName, NonceTemplate, Option<Condition>)>,
+    // headers rendered for every response, by their index in `core`
+    timed_headers: Vec<(HeaderName, usize, Option<Condition>)>,
+    conditional_headers: Vec<(HeaderName, HeaderValue, Condition)>,
+    conflict_strategies: Vec<(HeaderName, ConflictStrategy)>,
+    // headers not sent on every response
+    scopes: Vec<(HeaderName, HeaderScope)>,
+}
+
+impl CompiledHeaders {
+    fn is_conditional(&self) -> bool {
+        !self.conditional_headers.is_empty()
+            || self
+                .nonce_headers
+                .iter()
+                .any(|(_, _, condition)| condition.is_some())
+            || self
+                .timed_headers
+                .iter()
+                .any(|(_, _, condition)| condition.is_some())
+    }
+
+    // Current value of a header that changes over time
+    fn timed_value(&self, index: usize) -> HeaderValue {
+        let header = self.core.iter().nth(index).expect("timed header of `core`");
+        HeaderValue::from_str(&header.value()).expect("invalid header value")
+    }
 }
 
-impl Default for Helmet {
-    fn default() -> Self {
-        Self(HelmetCore::default())
+fn compile_headers(core: &HelmetCore) -> Result<CompiledHeaders, HelmetError> {
+    let compiled = core.compile()?;
+    let header_name = |name: &str| {
+        HeaderName::try_from(name).map_err(|_| HelmetError::InvalidHeaderName {
+            name: name.to_string(),
+        })
+    };
+
+    let mut conflict_strategies = Vec::new();
+    let mut scopes = Vec::new();
+    for (name, strategy, scope) in compiled.names {
+        let name = header_name(&name)?;
+        if scope != HeaderScope::Any {
+            scopes.push((name.clone(), scope));
+        }
+        conflict_strategies.push((name, strategy));
     }
+    let mut headers = HeaderMap::new();
+    let mut nonce_headers = Vec::new();
+    let mut timed_headers = Vec::new();
+    let mut conditional_headers = Vec::new();
+    for header in compiled.headers {
+        let name = header_name(&header.name)?;
+        let value = match header.value {
+            CompiledValue::Nonce(template) => {
+                nonce_headers.push((name, template, header.condition));
+                continue;
+            }
+            CompiledValue::Timed(index) => {
+                timed_headers.push((name, index, header.condition));
+                continue;
+            }
+            CompiledValue::Fixed(value) => {
+                HeaderValue::from_str(&value).map_err(|_| HelmetError::InvalidHeaderValue {
+                    name: header.name.clone(),
+                    value,
+                })?
+            }
+        };
+        match header.condition {
+            Some(condition) => conditional_headers.push((name, value, condition)),
+            None => {
+                headers.append(name, value);
+            }
+        }
+    }
+    Ok(CompiledHeaders {
+        core: core.clone(),
+        headers,
+        nonce_headers,
+        timed_headers,
+        conditional_headers,
+        conflict_strategies,
+        scopes,
+    })
 }
 
 impl<S> Middleware<S> for Helmet {
     type Service = HelmetMiddleware<S>;
 
     fn create(&self, service: S) -> Self::Service {
-        let mut headers = HeaderMap::new();
-        for header in self.0.headers.iter() {
-            let name = HeaderName::try_from(header.name()).expect("invalid header name");
-            let value = HeaderValue::from_str(&header.value()).expect("invalid header value");
-            headers.append(name, value);
+        let development_mode = self.0.development_mode();
+        let compile = |core: &HelmetCore| {
+            let headers = compile_headers(core).unwrap_or_else(|err| panic!("{}", err));
+            let development_headers = (development_mode != DevelopmentMode::Off).then(|| {
+                compile_headers(&core.relaxed_for_development())
+                    .unwrap_or_else(|err| panic!("{}", err))
+            });
+            (headers, development_headers)
+        };
+        let (headers, development_headers) = compile(&self.0);
+        let path_rules = self
+            .0
+            .path_rules()
+            .unwrap_or_else(|err| panic!("{}", err))
+            .iter()
+            .map(|(pattern, core)| {
+                let (headers, development_headers) = compile(core);
+                (pattern.clone(), headers, development_headers)
+            })
+            .collect();
+
+        HelmetMiddleware {
+            service,
+            headers,
+            development_mode,
+            development_headers,
+            path_rules,
+            hsts_https_only: self.0.is_hsts_https_only(),
+            trusted_proxies: self
+                .0
+                .trusted_proxies()
+                .unwrap_or_else(|err| panic!("{}", err)),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+/// Endpoint receiving violation reports
+///
+/// Accepts `application/csp-report` reports, sent for the `report-uri` directive, and `application/reports+json` reports, sent for the `report-to` directive, and hands each report to a [`ReportSink`].
+///
+/// Besides CSP violations, the Reporting API is used for e.g. `Cross-Origin-Opener-Policy`, `Cross-Origin-Embedder-Policy` and `Permissions-Policy` violations, which are received as well.
+///
+/// Responds with `204 No Content` when the reports were received, `400 Bad Request` for malformed reports, `413 Payload Too Large` when the body exceeds the size limit and `415 Unsupported Media Type` for other content types.
+///
+/// ```no_run
+/// use ntex::web;
+/// use ntex_helmet::{ContentSecurityPolicy, Helmet, ReportReceiver, ViolationReport};
+///
+/// #[ntex::main]
+/// async fn main() -> std::io::Result<()> {
+///     web::HttpServer::new(move || {
+///         web::App::new()
+///             .wrap(
+///                 Helmet::new()
+///                     .reporting_endpoint("csp", "/csp-reports")
+///                     .add(
+///                         ContentSecurityPolicy::default()
+///                             .report_to("csp")
+///                             .report_uri(vec!["/csp-reports"]),
+///                     ),
+///             )
+///             .service(
+///                 ReportReceiver::new(|report: ViolationReport| {
+///                     eprintln!("violation report: {:?}", report);
+///                 })
+///                 .resource("/csp-reports"),
+///             )
+///             .service(web::resource("/").to(|| async { "Hello, world!" }))
+///     })
+///     .bind(("127.0.0.1", 4200))?
+///     .run()
+///     .await
+/// }
+/// ```
+#[derive(Clone)]
+pub struct ReportReceiver {
+    sink: Arc<dyn ReportSink>,
+    max_body_size: usize,
+}
+
+#[cfg(feature = "serde")]
+impl ReportReceiver {
+    /// Create a receiver handing reports to the given sink, accepting bodies up to 64 KiB.
+    pub fn new(sink: impl ReportSink) -> Self {
+        Self {
+            sink: Arc::new(sink),
+            max_body_size: 64 * 1024,
+        }
+    }
+
+    /// Set the maximum size of a report request body in bytes.
+    pub fn max_body_size(mut self, max_body_size: usize) -> Self {
+        self.max_body_size = max_body_size;
+        self
+    }
+
+    /// Create a resource accepting reports with `POST` requests to `path`.
+    pub fn resource<Err: ErrorRenderer>(self, path: &str) -> web::Resource<Err> {
+        web::resource(path)
+            .state(self)
+            .route(web::post().to(receive_reports))
+    }
+}
+
+#[cfg(feature = "serde")]
+async fn receive_reports(req: HttpRequest, mut payload: Payload) -> HttpResponse {
+    let receiver = req
+        .app_state::<ReportReceiver>()
+        .expect("report receiver is registered with the resource");
+
+    let mut body = BytesMut::new();
+    while let Some(chunk) = payload.recv().await {
+        match chunk {
+            Ok(chunk) if body.len() + chunk.len() <= receiver.max_body_size => {
+                body.extend_from_slice(&chunk)
+            }
+            Ok(_) => return HttpResponse::PayloadTooLarge().finish(),
+            Err(_) => return HttpResponse::BadRequest().finish(),
         }
+    }
+
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
 
-        HelmetMiddleware { service, headers }
+    match parse_reports(content_type, &body) {
+        Ok(reports) => {
+            for report in reports {
+                receiver.sink.report(report);
+            }
+            HttpResponse::NoContent().finish()
+        }
+        Err(ReportError::UnsupportedContentType(_)) => {
+            HttpResponse::UnsupportedMediaType().finish()
+        }
+        Err(ReportError::InvalidBody(_)) => HttpResponse::BadRequest().finish(),
     }
 }
 
 #[cfg(test)]
 mod tests {
+    #[cfg(feature = "serde")]
+    use std::sync::Mutex;
+
     use ntex::{
-        web::test::{ok_service, TestRequest},
+        http::{Method, StatusCode},
+        into_service,
+        web::{
+            self,
+            test::{call_service, init_service, ok_service, read_body, TestRequest},
+            App, DefaultError, HttpRequest,
+        },
         Pipeline,
     };
 
     use helmet_core::{
         ContentSecurityPolicy, CrossOriginEmbedderPolicy, CrossOriginOpenerPolicy,
-        CrossOriginResourcePolicy, OriginAgentCluster, ReferrerPolicy, StrictTransportSecurity,
+        CrossOriginResourcePolicy, OriginAgentCluster, PermissionsPolicy, PermissionsPolicyFeature,
+        ReferrerPolicy, ReportTo, ReportToGroup, Source, StrictTransportSecurity,
         XContentTypeOptions, XDNSPrefetchControl, XDownloadOptions, XFrameOptions,
         XPermittedCrossDomainPolicies, XPoweredBy, XXSSProtection,
     };
@@ -359,6 +1098,88 @@ mod tests {
         );
     }
 
+    #[ntex::test]
+    async fn test_permissions_policy() {
+        let mw = Pipeline::new(
+            Helmet::new()
+                .add(
+                    PermissionsPolicy::new()
+                        .deny(PermissionsPolicyFeature::Camera)
+                        .allow(PermissionsPolicyFeature::Fullscreen, vec!["*"])
+                        .allow(
+                            PermissionsPolicyFeature::Geolocation,
+                            vec!["self", "https://example.com"],
+                        ),
+                )
+                .create(ok_service()),
+        );
+
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(
+            resp.headers().get("Permissions-Policy").unwrap(),
+            "camera=(), fullscreen=*, geolocation=(self \"https://example.com\")"
+        );
+    }
+
+    #[ntex::test]
+    async fn test_permissions_policy_report_only() {
+        let mw = Pipeline::new(
+            Helmet::new()
+                .add(
+                    PermissionsPolicy::new()
+                        .deny(PermissionsPolicyFeature::Microphone)
+                        .report_only(),
+                )
+                .create(ok_service()),
+        );
+
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert!(resp.headers().get("Permissions-Policy").is_none());
+        assert_eq!(
+            resp.headers()
+                .get("Permissions-Policy-Report-Only")
+                .unwrap(),
+            "microphone=()"
+        );
+    }
+
+    #[ntex::test]
+    async fn test_permissions_policy_feature_policy() {
+        let permissions_policy = PermissionsPolicy::new()
+            .deny(PermissionsPolicyFeature::Usb)
+            .allow(PermissionsPolicyFeature::Payment, vec!["self"]);
+        let mw = Pipeline::new(
+            Helmet::new()
+                .add(permissions_policy.feature_policy())
+                .add(permissions_policy)
+                .create(ok_service()),
+        );
+
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(
+            resp.headers().get("Permissions-Policy").unwrap(),
+            "usb=(), payment=(self)"
+        );
+        assert_eq!(
+            resp.headers().get("Feature-Policy").unwrap(),
+            "usb 'none'; payment 'self'"
+        );
+    }
+
+    #[test]
+    fn test_permissions_policy_invalid_origin() {
+        let result = Helmet::new()
+            .add(PermissionsPolicy::new().allow(
+                PermissionsPolicyFeature::Camera,
+                vec!["https://example.com\")"],
+            ))
+            .try_build();
+        assert!(result.is_err());
+    }
+
     #[ntex::test]
     async fn test_referrer_policy_no_referrer() {
         let mw = Pipeline::new(
@@ -537,11 +1358,40 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_strict_transport_security_max_age_preload() {
+        // the HSTS preload list requires a max-age of at least one year
+        assert_eq!(PreloadMaxAge::new(31535999), None);
+        assert_eq!(PreloadMaxAge::new(31536000), Some(PreloadMaxAge::ONE_YEAR));
+
+        // helmet.js options can still ask for it, the import drops preload with a warning
+        #[cfg(feature = "serde")]
+        {
+            let (helmet, warnings) = Helmet::from_helmetjs_options(
+                r#"{ "strictTransportSecurity": { "maxAge": 300, "preload": true }, "xPoweredBy": false }"#,
+            )
+            .unwrap();
+            assert_eq!(
+                helmet.get::<StrictTransportSecurity>().unwrap().value(),
+                "max-age=300; includeSubDomains"
+            );
+            assert_eq!(warnings.len(), 1);
+            assert_eq!(warnings[0].option, "strictTransportSecurity.preload");
+        }
+    }
+
     #[ntex::test]
-    async fn test_strict_transport_security_max_age_preload() {
+    async fn test_strict_transport_security_duration() {
         let mw = Pipeline::new(
             Helmet::new()
-                .add(StrictTransportSecurity::new().max_age(31536000).preload())
+                .add(
+                    StrictTransportSecurity::preloadable(
+                        PreloadMaxAge::new(std::time::Duration::from_secs(2 * 365 * 24 * 60 * 60))
+                            .unwrap(),
+                    )
+                    .include_sub_domains()
+                    .preload(),
+                )
                 .create(ok_service()),
         );
 
@@ -554,7 +1404,7 @@ mod tests {
                 .unwrap()
                 .to_str()
                 .unwrap(),
-            "max-age=31536000; preload"
+            "max-age=63072000; includeSubDomains; preload"
         );
     }
 
@@ -563,8 +1413,7 @@ mod tests {
         let mw = Pipeline::new(
             Helmet::new()
                 .add(
-                    StrictTransportSecurity::new()
-                        .max_age(31536000)
+                    StrictTransportSecurity::preloadable(PreloadMaxAge::ONE_YEAR)
                         .include_sub_domains()
                         .preload(),
                 )
@@ -584,6 +1433,40 @@ mod tests {
         );
     }
 
+    #[ntex::test]
+    async fn test_strict_transport_security_schedule() {
+        const DAY: std::time::Duration = std::time::Duration::from_secs(24 * 60 * 60);
+        let step = 7 * DAY;
+        let now = std::time::SystemTime::now();
+
+        // the step at the time of the request is sent
+        for (start, expected) in [
+            (now, "max-age=300; includeSubDomains"),
+            (now - step - DAY, "max-age=604800; includeSubDomains"),
+            (now - 2 * step - DAY, "max-age=2592000; includeSubDomains"),
+            (
+                now - 3 * step - DAY,
+                "max-age=63072000; includeSubDomains; preload",
+            ),
+        ] {
+            let mw = Pipeline::new(
+                Helmet::default()
+                    .add(StrictTransportSecuritySchedule::preload(start, step))
+                    .create(ok_service()),
+            );
+            let resp = mw
+                .call(TestRequest::default().to_srv_request())
+                .await
+                .unwrap();
+            let values = resp
+                .headers()
+                .get_all("Strict-Transport-Security")
+                .map(|value| value.to_str().unwrap())
+                .collect::<Vec<_>>();
+            assert_eq!(values, vec![expected]);
+        }
+    }
+
     #[ntex::test]
     async fn test_x_content_type_options_nosniff() {
         let mw = Pipeline::new(
@@ -1339,7 +2222,11 @@ mod tests {
     async fn test_content_security_policy_report_to() {
         let mw = Pipeline::new(
             Helmet::new()
-                .add(ContentSecurityPolicy::new().report_to(vec!["default", "endpoint", "group"]))
+                .add(
+                    ContentSecurityPolicy::new()
+                        .report_to("csp")
+                        .report_uri(vec!["https://example.com/csp", "/csp"]),
+                )
                 .create(ok_service()),
         );
 
@@ -1352,28 +2239,96 @@ mod tests {
                 .unwrap()
                 .to_str()
                 .unwrap(),
-            "report-to default endpoint group; report-uri default endpoint group"
+            "report-to csp; report-uri https://example.com/csp /csp"
         );
     }
 
     #[ntex::test]
-    async fn test_content_security_policy_trusted_types() {
+    async fn test_reporting_endpoints() {
         let mw = Pipeline::new(
             Helmet::new()
-                .add(
-                    ContentSecurityPolicy::new()
-                        .trusted_types(vec!["'self'", "https://youtube.com"]),
-                )
+                .reporting_endpoint("csp", "https://example.com/csp")
+                .add(ContentSecurityPolicy::new().report_to("csp"))
+                .add(CrossOriginOpenerPolicy::same_origin().report_to("coop"))
+                .add(CrossOriginEmbedderPolicy::require_corp().report_to("coep"))
+                .reporting_endpoint("coop", "https://example.com/coop")
+                .reporting_endpoint("coep", "https://example.com/coep")
                 .create(ok_service()),
         );
 
         let req = TestRequest::default().to_srv_request();
         let resp = mw.call(req).await.unwrap();
 
+        assert_eq!(resp.headers().get_all("Reporting-Endpoints").count(), 1);
         assert_eq!(
-            resp.headers()
-                .get("Content-Security-Policy")
-                .unwrap()
+            resp.headers().get("Reporting-Endpoints").unwrap(),
+            "csp=\"https://example.com/csp\", coop=\"https://example.com/coop\", coep=\"https://example.com/coep\""
+        );
+        assert_eq!(
+            resp.headers().get("Content-Security-Policy").unwrap(),
+            "report-to csp"
+        );
+        assert_eq!(
+            resp.headers().get("Cross-Origin-Opener-Policy").unwrap(),
+            "same-origin; report-to=\"coop\""
+        );
+        assert_eq!(
+            resp.headers().get("Cross-Origin-Embedder-Policy").unwrap(),
+            "require-corp; report-to=\"coep\""
+        );
+    }
+
+    #[ntex::test]
+    async fn test_report_to() {
+        let mw = Pipeline::new(
+            Helmet::new()
+                .add(
+                    ReportTo::new()
+                        .group(
+                            ReportToGroup::new("csp", 10886400)
+                                .endpoint("https://example.com/csp")
+                                .include_subdomains(),
+                        )
+                        .group(ReportToGroup::new("default", 86400).endpoint("/reports")),
+                )
+                .create(ok_service()),
+        );
+
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+
+        assert_eq!(
+            resp.headers().get("Report-To").unwrap(),
+            r#"{"group":"csp","max_age":10886400,"endpoints":[{"url":"https://example.com/csp"}],"include_subdomains":true}, {"group":"default","max_age":86400,"endpoints":[{"url":"/reports"}]}"#
+        );
+    }
+
+    #[test]
+    fn test_reporting_endpoint_invalid_url() {
+        let result = Helmet::new()
+            .reporting_endpoint("csp", "https://example.com/\"csp")
+            .try_build();
+        assert!(result.is_err());
+    }
+
+    #[ntex::test]
+    async fn test_content_security_policy_trusted_types() {
+        let mw = Pipeline::new(
+            Helmet::new()
+                .add(
+                    ContentSecurityPolicy::new()
+                        .trusted_types(vec!["'self'", "https://youtube.com"]),
+                )
+                .create(ok_service()),
+        );
+
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+
+        assert_eq!(
+            resp.headers()
+                .get("Content-Security-Policy")
+                .unwrap()
                 .to_str()
                 .unwrap(),
             "trusted-types 'self' https://youtube.com"
@@ -1462,6 +2417,200 @@ mod tests {
         );
     }
 
+    #[ntex::test]
+    async fn test_content_security_policy_typed_sources() {
+        let mw = Pipeline::new(
+            Helmet::new()
+                .add(
+                    ContentSecurityPolicy::new()
+                        .script_src(vec![
+                            Source::Self_,
+                            Source::WasmUnsafeEval,
+                            Source::nonce("cmFuZG9t").unwrap(),
+                        ])
+                        .connect_src(vec![
+                            Source::scheme("wss:").unwrap(),
+                            Source::host("https://*.example.com:*/api/").unwrap(),
+                        ]),
+                )
+                .create(ok_service()),
+        );
+
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(
+            resp.headers().get("Content-Security-Policy").unwrap(),
+            "script-src 'self' 'wasm-unsafe-eval' 'nonce-cmFuZG9t'; connect-src wss: https://*.example.com:*/api/"
+        );
+    }
+
+    #[ntex::test]
+    async fn test_content_security_policy_owned() {
+        let config = String::from("'self' https://cdn.example.com");
+        let policy = ContentSecurityPolicy::new()
+            .script_src(config.split(' ').map(String::from).collect())
+            .sandbox(vec![String::from("allow-scripts")]);
+
+        let mw = Pipeline::new(Helmet::new().add(policy).create(ok_service()));
+        drop(config);
+
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(
+            resp.headers().get("Content-Security-Policy").unwrap(),
+            "script-src 'self' https://cdn.example.com; sandbox allow-scripts"
+        );
+    }
+
+    #[ntex::test]
+    async fn test_content_security_policy_nonce() {
+        let app = init_service(
+            App::new()
+                .wrap(
+                    Helmet::new().add(
+                        ContentSecurityPolicy::new()
+                            .default_src(vec!["'self'"])
+                            .script_src(vec!["'self'"])
+                            .script_nonce()
+                            .style_nonce(),
+                    ),
+                )
+                .service(web::resource("/").to(|req: HttpRequest| async move {
+                    req.extensions().get::<Nonce>().unwrap().to_string()
+                })),
+        )
+        .await;
+
+        let resp = call_service(&app, TestRequest::with_uri("/").to_request()).await;
+        let csp = resp
+            .headers()
+            .get("Content-Security-Policy")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+        let nonce = String::from_utf8(read_body(resp).await.to_vec()).unwrap();
+
+        assert_eq!(
+            csp,
+            format!(
+                "default-src 'self'; script-src 'self' 'nonce-{}'; style-src 'self' 'nonce-{}'",
+                nonce, nonce
+            )
+        );
+
+        let resp = call_service(&app, TestRequest::with_uri("/").to_request()).await;
+        let next_nonce = String::from_utf8(read_body(resp).await.to_vec()).unwrap();
+
+        assert_ne!(nonce, next_nonce);
+    }
+
+    #[ntex::test]
+    async fn test_content_security_policy_nonce_replaces_none() {
+        let app = init_service(
+            App::new()
+                .wrap(
+                    Helmet::new().add(
+                        ContentSecurityPolicy::new()
+                            .default_src(vec!["'none'"])
+                            .script_src(vec!["'none'"])
+                            .script_nonce()
+                            .style_nonce(),
+                    ),
+                )
+                .service(web::resource("/").to(|req: HttpRequest| async move {
+                    req.extensions().get::<Nonce>().unwrap().to_string()
+                })),
+        )
+        .await;
+
+        let resp = call_service(&app, TestRequest::with_uri("/").to_request()).await;
+        let csp = resp
+            .headers()
+            .get("Content-Security-Policy")
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .to_string();
+        let nonce = String::from_utf8(read_body(resp).await.to_vec()).unwrap();
+
+        assert_eq!(
+            csp,
+            format!(
+                "default-src 'none'; script-src 'nonce-{}'; style-src 'nonce-{}'",
+                nonce, nonce
+            )
+        );
+    }
+
+    #[test]
+    fn test_helmet_try_build_invalid_header_value() {
+        let err = Helmet::new()
+            .add(XFrameOptions::allow_from(
+                "https://example.com\r\nX-Injected: 1",
+            ))
+            .try_build()
+            .err()
+            .unwrap();
+
+        assert_eq!(
+            err,
+            HelmetError::InvalidHeaderValue {
+                name: "X-Frame-Options".to_string(),
+                value: "ALLOW-FROM https://example.com\r\nX-Injected: 1".to_string(),
+            }
+        );
+        assert!(Helmet::default().try_build().is_ok());
+    }
+
+    #[test]
+    fn test_helmet_try_build_invalid_source() {
+        let err = Helmet::new()
+            .add(
+                ContentSecurityPolicy::new()
+                    .img_src(vec!["'self'", "https://cdn.example.com,https://evil.com"]),
+            )
+            .try_build()
+            .err()
+            .unwrap();
+
+        assert!(matches!(
+            err,
+            HelmetError::InvalidSource { ref name, .. } if name == "Content-Security-Policy"
+        ));
+    }
+
+    #[test]
+    fn test_helmet_try_build_unquoted_keyword() {
+        let policies = [
+            ContentSecurityPolicy::new().default_src(vec!["self"]),
+            ContentSecurityPolicy::new().script_src(vec!["'self'", "unsafe-inline"]),
+            ContentSecurityPolicy::new().object_src(vec!["NONE"]),
+            ContentSecurityPolicy::new().script_src(vec!["strict-dynamic"]),
+        ];
+        for policy in policies {
+            let err = Helmet::new().add(policy).try_build().err().unwrap();
+            assert!(
+                err.to_string().ends_with("missing quotes around keyword"),
+                "{}",
+                err
+            );
+        }
+
+        assert!(Helmet::new()
+            .add(ContentSecurityPolicy::new().img_src(vec!["https://self.example.com"]))
+            .try_build()
+            .is_ok());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid value for header `X-Powered-By`")]
+    fn test_helmet_create_invalid_header_value() {
+        Helmet::new()
+            .add(XPoweredBy::new("PHP\n4.2.0"))
+            .create(ok_service::<DefaultError>());
+    }
+
     #[ntex::test]
     async fn test_content_security_policy_report_only() {
         let mw = Pipeline::new(
@@ -1488,4 +2637,1667 @@ mod tests {
             "base-uri 'self'"
         );
     }
+
+    #[cfg(feature = "serde")]
+    fn report_receiver(max_body_size: usize) -> (ReportReceiver, Arc<Mutex<Vec<ViolationReport>>>) {
+        let violations = Arc::new(Mutex::new(Vec::new()));
+        let sink = violations.clone();
+        let receiver =
+            ReportReceiver::new(move |report: ViolationReport| sink.lock().unwrap().push(report))
+                .max_body_size(max_body_size);
+        (receiver, violations)
+    }
+
+    #[cfg(feature = "serde")]
+    fn csp_violations(reports: &Mutex<Vec<ViolationReport>>) -> Vec<CspViolation> {
+        reports
+            .lock()
+            .unwrap()
+            .iter()
+            .filter_map(|report| match report {
+                ViolationReport::Csp(violation) => Some(violation.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[cfg(feature = "serde")]
+    #[ntex::test]
+    async fn test_report_receiver_csp_report() {
+        let (receiver, violations) = report_receiver(64 * 1024);
+        let app = init_service(App::new().service(receiver.resource("/csp-reports"))).await;
+
+        let req = TestRequest::post()
+            .uri("/csp-reports")
+            .header("Content-Type", "application/csp-report")
+            .set_payload(
+                r#"{"csp-report": {
+                    "document-uri": "https://example.com/",
+                    "blocked-uri": "eval",
+                    "violated-directive": "script-src",
+                    "effective-directive": "script-src",
+                    "original-policy": "script-src 'self'; report-uri /csp-reports",
+                    "script-sample": "alert(1)"
+                }}"#,
+            )
+            .to_request();
+        let resp = call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 204);
+        let violations = csp_violations(&violations);
+        assert_eq!(violations.len(), 1);
+        assert_eq!(violations[0].blocked_uri.as_deref(), Some("eval"));
+        assert_eq!(violations[0].sample.as_deref(), Some("alert(1)"));
+    }
+
+    #[cfg(feature = "serde")]
+    #[ntex::test]
+    async fn test_report_receiver_reports_json() {
+        let (receiver, violations) = report_receiver(64 * 1024);
+        let app = init_service(App::new().service(receiver.resource("/csp-reports"))).await;
+
+        let req = TestRequest::post()
+            .uri("/csp-reports")
+            .header("Content-Type", "application/reports+json")
+            .set_payload(
+                r#"[
+                    {"type": "csp-violation", "age": 1, "url": "https://example.com/", "user_agent": "test", "body": {
+                        "documentURL": "https://example.com/",
+                        "blockedURL": "https://evil.com/image.png",
+                        "effectiveDirective": "img-src",
+                        "originalPolicy": "img-src 'self'; report-to csp",
+                        "disposition": "enforce",
+                        "statusCode": 200
+                    }},
+                    {"type": "csp-violation", "age": 2, "url": "https://example.com/about", "user_agent": "test", "body": {
+                        "documentURL": "https://example.com/about",
+                        "blockedURL": "inline",
+                        "effectiveDirective": "script-src-elem",
+                        "originalPolicy": "img-src 'self'; report-to csp",
+                        "disposition": "enforce"
+                    }}
+                ]"#,
+            )
+            .to_request();
+        let resp = call_service(&app, req).await;
+
+        assert_eq!(resp.status(), 204);
+        let violations = csp_violations(&violations);
+        assert_eq!(violations.len(), 2);
+        assert_eq!(violations[0].effective_directive, "img-src");
+        assert_eq!(violations[0].status_code, Some(200));
+        assert_eq!(violations[1].document_uri, "https://example.com/about");
+    }
+
+    #[cfg(feature = "serde")]
+    #[ntex::test]
+    async fn test_report_receiver_rejects_invalid_requests() {
+        let (receiver, violations) = report_receiver(128);
+        let app = init_service(App::new().service(receiver.resource("/csp-reports"))).await;
+
+        let req = TestRequest::post()
+            .uri("/csp-reports")
+            .header("Content-Type", "text/plain")
+            .set_payload("hello")
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 415);
+
+        let req = TestRequest::post()
+            .uri("/csp-reports")
+            .header("Content-Type", "application/csp-report")
+            .set_payload("{")
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 400);
+
+        let req = TestRequest::post()
+            .uri("/csp-reports")
+            .header("Content-Type", "application/csp-report")
+            .set_payload(format!(
+                r#"{{"csp-report": {{"document-uri": "https://example.com/{}"}}}}"#,
+                "a".repeat(256)
+            ))
+            .to_request();
+        assert_eq!(call_service(&app, req).await.status(), 413);
+
+        assert!(violations.lock().unwrap().is_empty());
+    }
+
+    async fn handler_with_headers() -> web::HttpResponse {
+        web::HttpResponse::Ok()
+            .header("X-Frame-Options", "DENY")
+            .header("Content-Security-Policy", "default-src 'none'")
+            .finish()
+    }
+
+    #[ntex::test]
+    async fn test_conflict_strategy_keep_existing() {
+        let app = init_service(
+            App::new()
+                .wrap(Helmet::default())
+                .service(web::resource("/").to(handler_with_headers)),
+        )
+        .await;
+
+        let resp = call_service(&app, TestRequest::with_uri("/").to_request()).await;
+        assert_eq!(resp.headers().get_all("X-Frame-Options").count(), 1);
+        assert_eq!(resp.headers().get("X-Frame-Options").unwrap(), "DENY");
+        assert_eq!(resp.headers().get_all("Content-Security-Policy").count(), 1);
+        assert_eq!(
+            resp.headers().get("Content-Security-Policy").unwrap(),
+            "default-src 'none'"
+        );
+        assert_eq!(
+            resp.headers().get("Referrer-Policy").unwrap(),
+            "no-referrer"
+        );
+    }
+
+    #[ntex::test]
+    async fn test_conflict_strategy_overwrite() {
+        let app = init_service(
+            App::new()
+                .wrap(
+                    Helmet::new()
+                        .add(XFrameOptions::same_origin())
+                        .conflict_strategy(ConflictStrategy::Overwrite),
+                )
+                .service(web::resource("/").to(handler_with_headers)),
+        )
+        .await;
+
+        let resp = call_service(&app, TestRequest::with_uri("/").to_request()).await;
+        assert_eq!(resp.headers().get_all("X-Frame-Options").count(), 1);
+        assert_eq!(resp.headers().get("X-Frame-Options").unwrap(), "SAMEORIGIN");
+    }
+
+    #[ntex::test]
+    async fn test_conflict_strategy_per_header() {
+        let app = init_service(
+            App::new()
+                .wrap(
+                    Helmet::new()
+                        .add(ContentSecurityPolicy::new().script_src(vec!["'self'"]))
+                        .add(XFrameOptions::same_origin())
+                        .header_conflict_strategy(
+                            "Content-Security-Policy",
+                            ConflictStrategy::Append,
+                        ),
+                )
+                .service(web::resource("/").to(handler_with_headers)),
+        )
+        .await;
+
+        let resp = call_service(&app, TestRequest::with_uri("/").to_request()).await;
+        let csp = resp
+            .headers()
+            .get_all("Content-Security-Policy")
+            .map(|value| value.to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(csp, vec!["default-src 'none'", "script-src 'self'"]);
+        assert_eq!(resp.headers().get("X-Frame-Options").unwrap(), "DENY");
+    }
+
+    #[ntex::test]
+    async fn test_add_replaces_header_with_same_name() {
+        let mw = Pipeline::new(
+            Helmet::default()
+                .add(
+                    StrictTransportSecurity::preloadable(PreloadMaxAge::TWO_YEARS)
+                        .include_sub_domains()
+                        .preload(),
+                )
+                .remove::<XXSSProtection>()
+                .create(ok_service()),
+        );
+
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        let hsts = resp
+            .headers()
+            .get_all("Strict-Transport-Security")
+            .map(|value| value.to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(hsts, vec!["max-age=63072000; includeSubDomains; preload"]);
+        assert!(resp.headers().get("X-XSS-Protection").is_none());
+    }
+
+    #[ntex::test]
+    async fn test_add_content_security_policy_replaces() {
+        let mw = Pipeline::new(
+            Helmet::default()
+                .add(ContentSecurityPolicy::new().default_src(vec!["'self'"]))
+                .add(
+                    ContentSecurityPolicy::new()
+                        .default_src(vec!["'none'"])
+                        .report_only(),
+                )
+                .create(ok_service()),
+        );
+
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        let csp = resp
+            .headers()
+            .get_all("Content-Security-Policy")
+            .map(|value| value.to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(csp, vec!["default-src 'self'"]);
+        assert_eq!(
+            resp.headers()
+                .get("Content-Security-Policy-Report-Only")
+                .unwrap(),
+            "default-src 'none'"
+        );
+    }
+
+    #[ntex::test]
+    async fn test_add_additional_content_security_policy() {
+        let mw = Pipeline::new(
+            Helmet::new()
+                .add(ContentSecurityPolicy::new().default_src(vec!["'self'"]))
+                .add_additional(ContentSecurityPolicy::new().script_src(vec!["'none'"]))
+                .add_additional(XFrameOptions::deny())
+                .add_additional(XFrameOptions::same_origin())
+                .create(ok_service()),
+        );
+
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(resp.headers().get_all("Content-Security-Policy").count(), 2);
+        assert_eq!(resp.headers().get_all("X-Frame-Options").count(), 1);
+    }
+
+    #[ntex::test]
+    async fn test_helmet_is_clone_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Helmet>();
+
+        let helmet = Helmet::default();
+        let debug = std::thread::spawn({
+            let helmet = helmet.clone();
+            move || format!("{:?}", helmet)
+        })
+        .join()
+        .unwrap();
+        assert_eq!(debug, format!("{:?}", helmet));
+        assert!(debug.contains("\"X-Frame-Options\": \"SAMEORIGIN\""));
+
+        let first = Pipeline::new(helmet.clone().create(ok_service()));
+        let second = Pipeline::new(helmet.create(ok_service()));
+        for mw in [first, second] {
+            let req = TestRequest::default().to_srv_request();
+            let resp = mw.call(req).await.unwrap();
+            assert_eq!(resp.headers().get("X-Frame-Options").unwrap(), "SAMEORIGIN");
+        }
+    }
+
+    fn service_profile() -> Helmet {
+        Helmet::new()
+            .add(XFrameOptions::deny())
+            .add(
+                ContentSecurityPolicy::new()
+                    .script_src(vec!["https://cdn.example.com"])
+                    .connect_src(vec!["https://api.example.com"]),
+            )
+            .header_conflict_strategy("X-Frame-Options", ConflictStrategy::Overwrite)
+    }
+
+    fn base_profile() -> Helmet {
+        Helmet::new()
+            .add(XFrameOptions::same_origin())
+            .add(XContentTypeOptions::nosniff())
+            .add(
+                ContentSecurityPolicy::new()
+                    .default_src(vec!["'self'"])
+                    .script_src(vec!["'self'"]),
+            )
+    }
+
+    #[ntex::test]
+    async fn test_merge_base_precedence() {
+        let mw = Pipeline::new(
+            base_profile()
+                .merge(service_profile(), MergePrecedence::Base)
+                .create(ok_service()),
+        );
+
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(resp.headers().get("X-Frame-Options").unwrap(), "SAMEORIGIN");
+        assert_eq!(
+            resp.headers().get("X-Content-Type-Options").unwrap(),
+            "nosniff"
+        );
+        assert_eq!(
+            resp.headers().get("Content-Security-Policy").unwrap(),
+            "default-src 'self'; script-src 'self'"
+        );
+    }
+
+    #[ntex::test]
+    async fn test_merge_other_precedence() {
+        let helmet = base_profile().merge(service_profile(), MergePrecedence::Other);
+        assert_eq!(
+            helmet.0.conflict_strategy_for("X-Frame-Options"),
+            ConflictStrategy::Overwrite
+        );
+        let mw = Pipeline::new(helmet.create(ok_service()));
+
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(resp.headers().get("X-Frame-Options").unwrap(), "DENY");
+        assert_eq!(
+            resp.headers().get("X-Content-Type-Options").unwrap(),
+            "nosniff"
+        );
+        assert_eq!(
+            resp.headers().get("Content-Security-Policy").unwrap(),
+            "script-src https://cdn.example.com; connect-src https://api.example.com"
+        );
+    }
+
+    #[ntex::test]
+    async fn test_merge_override_directives() {
+        let mw = Pipeline::new(
+            base_profile()
+                .merge(service_profile(), MergePrecedence::OverrideDirectives)
+                .create(ok_service()),
+        );
+
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(
+            resp.headers().get("Content-Security-Policy").unwrap(),
+            "default-src 'self'; script-src https://cdn.example.com; connect-src https://api.example.com"
+        );
+    }
+
+    #[ntex::test]
+    async fn test_merge_union_directives() {
+        let mw = Pipeline::new(
+            base_profile()
+                .merge(service_profile(), MergePrecedence::UnionDirectives)
+                .create(ok_service()),
+        );
+
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(resp.headers().get_all("Content-Security-Policy").count(), 1);
+        assert_eq!(
+            resp.headers().get("Content-Security-Policy").unwrap(),
+            "script-src 'self' https://cdn.example.com; connect-src 'self' https://api.example.com"
+        );
+    }
+
+    #[ntex::test]
+    async fn test_merge_union_directives_without_fallback() {
+        let base = Helmet::new().add(
+            ContentSecurityPolicy::new()
+                .default_src(vec!["'self'"])
+                .script_src(vec!["'self'"])
+                .base_uri(vec!["'self'"])
+                .form_action(vec!["'self'"])
+                .frame_ancestors(vec!["'none'"]),
+        );
+        let service = Helmet::new().add(
+            ContentSecurityPolicy::new()
+                .img_src(vec!["https://images.example.com"])
+                .frame_ancestors(vec!["https://partner.example.com"])
+                .upgrade_insecure_requests(),
+        );
+
+        // directives only one policy restricts, without a fallback in the other, are left out
+        let helmet = base.merge(service, MergePrecedence::UnionDirectives);
+        let headers = response_headers(&helmet, TestRequest::default(), "text/html").await;
+        assert_eq!(
+            headers.get(header::CONTENT_SECURITY_POLICY).unwrap(),
+            "frame-ancestors https://partner.example.com; img-src 'self' https://images.example.com"
+        );
+    }
+
+    #[ntex::test]
+    async fn test_merge_other_replaces_conditional_headers() {
+        let service = Helmet::new().add_when(XFrameOptions::deny(), Condition::method(vec!["GET"]));
+        let helmet = base_profile().merge(service, MergePrecedence::Other);
+        assert_eq!(
+            helmet
+                .iter()
+                .filter(|header| header.name() == "X-Frame-Options")
+                .count(),
+            1
+        );
+
+        let headers = response_headers(&helmet, TestRequest::default(), "text/html").await;
+        assert_eq!(headers.get(header::X_FRAME_OPTIONS).unwrap(), "DENY");
+        let req = TestRequest::default().method(Method::POST);
+        let headers = response_headers(&helmet, req, "text/html").await;
+        assert!(!headers.contains_key(header::X_FRAME_OPTIONS));
+    }
+
+    #[ntex::test]
+    async fn test_merge_keeps_reporting_endpoints_of_both() {
+        let base = Helmet::new()
+            .reporting_endpoint("csp", "https://example.com/csp")
+            .reporting_endpoint("coop", "https://example.com/coop");
+        let service = Helmet::new().reporting_endpoint("csp", "https://service.example.com/csp");
+        let mw = Pipeline::new(
+            base.merge(service, MergePrecedence::Other)
+                .create(ok_service()),
+        );
+
+        let req = TestRequest::default().to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert_eq!(
+            resp.headers().get("Reporting-Endpoints").unwrap(),
+            "csp=\"https://service.example.com/csp\", coop=\"https://example.com/coop\""
+        );
+    }
+
+    // Headers of the response to `req` from a handler answering with `content_type`
+    async fn response_headers(
+        helmet: &Helmet,
+        req: TestRequest,
+        content_type: &'static str,
+    ) -> HeaderMap {
+        let service = into_service(move |req: WebRequest<DefaultError>| async move {
+            let res = web::HttpResponse::Ok().content_type(content_type).finish();
+            Ok::<_, std::convert::Infallible>(req.into_response(res))
+        });
+        let mw = Pipeline::new(helmet.create(service));
+
+        let resp = mw.call(req.to_srv_request()).await.unwrap();
+        resp.headers().clone()
+    }
+
+    // sorted `name: value` lines, without the handler's `Content-Type`
+    fn header_lines(headers: &HeaderMap) -> Vec<String> {
+        let mut lines = headers
+            .iter()
+            .filter(|(name, _)| *name != header::CONTENT_TYPE)
+            .map(|(name, value)| format!("{}: {}", name, value.to_str().unwrap()))
+            .collect::<Vec<_>>();
+        lines.sort();
+        lines
+    }
+
+    #[cfg(feature = "serde")]
+    #[ntex::test]
+    async fn test_helmetjs_default_parity() {
+        // Headers documented for `app.use(helmet())` in the helmet.js v7 README.
+        let expected = vec![
+            ("content-security-policy", "default-src 'self'; base-uri 'self'; font-src 'self' https: data:; form-action 'self'; frame-ancestors 'self'; img-src 'self' data:; object-src 'none'; script-src 'self'; script-src-attr 'none'; style-src 'self' https: 'unsafe-inline'; upgrade-insecure-requests"),
+            ("cross-origin-opener-policy", "same-origin"),
+            ("cross-origin-resource-policy", "same-origin"),
+            ("origin-agent-cluster", "?1"),
+            ("referrer-policy", "no-referrer"),
+            ("strict-transport-security", "max-age=15552000; includeSubDomains"),
+            ("x-content-type-options", "nosniff"),
+            ("x-dns-prefetch-control", "off"),
+            ("x-download-options", "noopen"),
+            ("x-frame-options", "SAMEORIGIN"),
+            ("x-permitted-cross-domain-policies", "none"),
+            ("x-xss-protection", "0"),
+        ];
+
+        let (helmet, warnings) = Helmet::from_helmetjs_options("{}").unwrap();
+        // helmet.js also removes `X-Powered-By`, which Helmet doesn't
+        assert_eq!(warnings.len(), 1);
+        assert_eq!(warnings[0].option, "xPoweredBy");
+        let (_, warnings) = Helmet::from_helmetjs_options(r#"{ "xPoweredBy": false }"#).unwrap();
+        assert!(warnings.is_empty());
+        assert_eq!(
+            header_lines(&response_headers(&helmet, TestRequest::default(), "text/html").await),
+            expected
+                .iter()
+                .map(|(name, value)| format!("{}: {}", name, value))
+                .collect::<Vec<_>>()
+        );
+
+        let headers =
+            response_headers(&Helmet::default(), TestRequest::default(), "text/html").await;
+        for (name, value) in expected {
+            assert_eq!(headers.get(name).unwrap(), value);
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[ntex::test]
+    async fn test_helmetjs_disable_headers() {
+        let headers = response_headers(
+            &Helmet::from_helmetjs_options(
+                r#"{
+                "contentSecurityPolicy": false,
+                "crossOriginOpenerPolicy": false,
+                "crossOriginResourcePolicy": false,
+                "originAgentCluster": false,
+                "referrerPolicy": false,
+                "strictTransportSecurity": false,
+                "xContentTypeOptions": false,
+                "xDnsPrefetchControl": false,
+                "xDownloadOptions": false,
+                "frameguard": false,
+                "xPermittedCrossDomainPolicies": false,
+                "xssFilter": false
+            }"#,
+            )
+            .unwrap()
+            .0,
+            TestRequest::default(),
+            "text/html",
+        )
+        .await;
+        assert!(header_lines(&headers).is_empty());
+    }
+
+    #[cfg(feature = "serde")]
+    #[ntex::test]
+    async fn test_helmetjs_header_options() {
+        let headers = response_headers(
+            &Helmet::from_helmetjs_options(
+                r#"{
+                "contentSecurityPolicy": false,
+                "crossOriginEmbedderPolicy": true,
+                "crossOriginOpenerPolicy": { "policy": "same-origin-allow-popups" },
+                "crossOriginResourcePolicy": { "policy": "cross-origin" },
+                "referrerPolicy": { "policy": "strict-origin-when-cross-origin" },
+                "hsts": { "maxAge": 123456.4, "includeSubDomains": false, "preload": true },
+                "xDnsPrefetchControl": { "allow": true },
+                "xFrameOptions": { "action": "DENY" },
+                "permittedCrossDomainPolicies": { "permittedPolicies": "by-content-type" }
+            }"#,
+            )
+            .unwrap()
+            .0,
+            TestRequest::default(),
+            "text/html",
+        )
+        .await;
+        let get = |name: &str| headers.get(name).map(|value| value.to_str().unwrap());
+        assert_eq!(get("cross-origin-embedder-policy"), Some("require-corp"));
+        assert_eq!(
+            get("cross-origin-opener-policy"),
+            Some("same-origin-allow-popups")
+        );
+        assert_eq!(get("cross-origin-resource-policy"), Some("cross-origin"));
+        assert_eq!(
+            get("referrer-policy"),
+            Some("strict-origin-when-cross-origin")
+        );
+        assert_eq!(get("strict-transport-security"), Some("max-age=123456"));
+        assert_eq!(get("x-dns-prefetch-control"), Some("on"));
+        assert_eq!(get("x-frame-options"), Some("DENY"));
+        assert_eq!(
+            get("x-permitted-cross-domain-policies"),
+            Some("by-content-type")
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[ntex::test]
+    async fn test_helmetjs_content_security_policy() {
+        let headers = response_headers(
+            &Helmet::from_helmetjs_options(
+                r#"{
+                "contentSecurityPolicy": {
+                    "directives": {
+                        "scriptSrc": ["'self'", "https://cdn.example.com"],
+                        "connect-src": "https://api.example.com",
+                        "upgradeInsecureRequests": null
+                    }
+                }
+            }"#,
+            )
+            .unwrap()
+            .0,
+            TestRequest::default(),
+            "text/html",
+        )
+        .await;
+        assert_eq!(
+            headers.get(header::CONTENT_SECURITY_POLICY).unwrap(),
+            "default-src 'self'; base-uri 'self'; font-src 'self' https: data:; form-action 'self'; frame-ancestors 'self'; img-src 'self' data:; object-src 'none'; script-src 'self' https://cdn.example.com; script-src-attr 'none'; style-src 'self' https: 'unsafe-inline'; connect-src https://api.example.com"
+        );
+
+        let headers = response_headers(
+            &Helmet::from_helmetjs_options(
+                r#"{
+                "contentSecurityPolicy": {
+                    "useDefaults": false,
+                    "directives": { "defaultSrc": ["'none'"], "reportUri": ["/csp"] },
+                    "reportOnly": true
+                }
+            }"#,
+            )
+            .unwrap()
+            .0,
+            TestRequest::default(),
+            "text/html",
+        )
+        .await;
+        assert_eq!(
+            headers
+                .get(header::CONTENT_SECURITY_POLICY_REPORT_ONLY)
+                .unwrap(),
+            "default-src 'none'; report-uri /csp"
+        );
+        assert!(!headers.contains_key(header::CONTENT_SECURITY_POLICY));
+
+        // directives keep the order they are written in
+        let headers = response_headers(
+&Helmet::from_helmetjs_options(r#"{
+                "contentSecurityPolicy": {
+                    "useDefaults": false,
+                    "directives": { "scriptSrc": ["'self'"], "defaultSrc": ["'none'"], "imgSrc": ["'self'"] }
+                }
+            }"#).unwrap().0,
+TestRequest::default(),
+"text/html",
+)
+.await;
+        assert_eq!(
+            headers.get(header::CONTENT_SECURITY_POLICY).unwrap(),
+            "script-src 'self'; default-src 'none'; img-src 'self'"
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[ntex::test]
+    async fn test_helmetjs_warnings() {
+        let (_, warnings) = Helmet::from_helmetjs_options(
+            r#"{
+                "referrerPolicy": { "policy": ["no-referrer", "strict-origin-when-cross-origin"] },
+                "strictTransportSecurity": { "maxAge": 300, "force": true, "preload": true },
+                "hidePoweredBy": true,
+                "expectCt": true
+            }"#,
+        )
+        .unwrap();
+        let warnings = warnings
+            .iter()
+            .map(|warning| warning.option.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            warnings,
+            vec![
+                "expectCt",
+                "referrerPolicy.policy",
+                "strictTransportSecurity.force",
+                "strictTransportSecurity.preload",
+                "hidePoweredBy"
+            ]
+        );
+    }
+
+    #[cfg(feature = "serde")]
+    #[ntex::test]
+    async fn test_helmetjs_invalid_options() {
+        let invalid = |options: &str| match Helmet::from_helmetjs_options(options) {
+            Err(HelmetError::InvalidOption { option, .. }) => option,
+            other => panic!("expected an invalid option, got {:?}", other.map(|_| ())),
+        };
+        assert_eq!(invalid("[]"), "");
+        assert_eq!(invalid("{"), "");
+        assert_eq!(
+            invalid(r#"{ "xFrameOptions": { "action": "deny" }, "frameguard": false }"#),
+            "xFrameOptions"
+        );
+        assert_eq!(
+            invalid(r#"{ "xFrameOptions": { "action": "ALLOW-FROM" } }"#),
+            "xFrameOptions.action"
+        );
+        assert_eq!(
+            invalid(r#"{ "strictTransportSecurity": { "maxAge": -1 } }"#),
+            "strictTransportSecurity.maxAge"
+        );
+        assert_eq!(
+            invalid(r#"{ "hsts": { "includeSubdomains": true } }"#),
+            "hsts.includeSubdomains"
+        );
+        assert_eq!(
+            invalid(r#"{ "contentSecurityPolicy": { "directives": { "defaultSrc": null } } }"#),
+            "contentSecurityPolicy.directives"
+        );
+        assert_eq!(
+            invalid(
+                r#"{ "contentSecurityPolicy": { "directives": { "defaultSrc": "'self'", "default-src": "'none'" } } }"#
+            ),
+            "contentSecurityPolicy.directives.default-src"
+        );
+        assert_eq!(
+            invalid(
+                r#"{ "contentSecurityPolicy": { "directives": { "scriptSrc": ["'self'; object-src *"] } } }"#
+            ),
+            "contentSecurityPolicy.directives.script-src"
+        );
+        assert_eq!(
+            invalid(r#"{ "originAgentCluster": "yes" }"#),
+            "originAgentCluster"
+        );
+    }
+
+    #[ntex::test]
+    async fn test_preset_api() {
+        assert_eq!(
+            header_lines(
+                &response_headers(&Helmet::api(), TestRequest::default(), "application/json").await
+            ),
+            vec![
+                "content-security-policy: default-src 'none'; frame-ancestors 'none'",
+                "cross-origin-resource-policy: same-origin",
+                "strict-transport-security: max-age=15552000; includeSubDomains",
+                "x-content-type-options: nosniff",
+                "x-frame-options: DENY",
+            ]
+        );
+    }
+
+    #[ntex::test]
+    async fn test_preset_spa() {
+        let mut headers = header_lines(
+            &response_headers(&Helmet::spa(), TestRequest::default(), "text/html").await,
+        );
+        let csp = &mut headers[0];
+        let start = csp.find("'nonce-").unwrap() + "'nonce-".len();
+        let end = start + csp[start..].find('\'').unwrap();
+        assert!(end - start >= 16);
+        csp.replace_range(start..end, "<random>");
+
+        assert_eq!(
+            headers,
+            vec![
+                "content-security-policy: default-src 'self'; base-uri 'none'; font-src 'self' https: data:; form-action 'self'; frame-ancestors 'self'; img-src 'self' data: blob:; object-src 'none'; script-src 'self' 'strict-dynamic' 'nonce-<random>'; script-src-attr 'none'; style-src 'self' https: 'unsafe-inline'; upgrade-insecure-requests",
+                "cross-origin-opener-policy: same-origin-allow-popups",
+                "cross-origin-resource-policy: same-origin",
+                "origin-agent-cluster: ?1",
+                "referrer-policy: no-referrer",
+                "strict-transport-security: max-age=15552000; includeSubDomains",
+                "x-content-type-options: nosniff",
+                "x-dns-prefetch-control: off",
+                "x-download-options: noopen",
+                "x-frame-options: SAMEORIGIN",
+                "x-permitted-cross-domain-policies: none",
+                "x-xss-protection: 0",
+            ]
+        );
+    }
+
+    #[ntex::test]
+    async fn test_preset_static_site() {
+        assert_eq!(
+            header_lines(&response_headers(&Helmet::static_site(), TestRequest::default(), "text/html").await),
+            vec![
+                "content-security-policy: default-src 'self'; base-uri 'self'; font-src 'self' data:; form-action 'self'; frame-ancestors 'self'; img-src 'self' data:; object-src 'none'; script-src 'self'; script-src-attr 'none'; style-src 'self'; upgrade-insecure-requests",
+                "cross-origin-opener-policy: same-origin",
+                "cross-origin-resource-policy: same-origin",
+                "origin-agent-cluster: ?1",
+                "referrer-policy: no-referrer",
+                "strict-transport-security: max-age=15552000; includeSubDomains",
+                "x-content-type-options: nosniff",
+                "x-dns-prefetch-control: off",
+                "x-download-options: noopen",
+                "x-frame-options: SAMEORIGIN",
+                "x-permitted-cross-domain-policies: none",
+                "x-xss-protection: 0",
+            ]
+        );
+    }
+
+    #[ntex::test]
+    async fn test_preset_wasm_app() {
+        assert_eq!(
+            header_lines(&response_headers(&Helmet::wasm_app(), TestRequest::default(), "text/html").await),
+            vec![
+                "content-security-policy: default-src 'self'; base-uri 'self'; font-src 'self' https: data:; form-action 'self'; frame-ancestors 'self'; img-src 'self' data:; object-src 'none'; script-src 'self' 'wasm-unsafe-eval'; script-src-attr 'none'; style-src 'self' https: 'unsafe-inline'; upgrade-insecure-requests",
+                "cross-origin-embedder-policy: require-corp",
+                "cross-origin-opener-policy: same-origin",
+                "cross-origin-resource-policy: same-origin",
+                "origin-agent-cluster: ?1",
+                "referrer-policy: no-referrer",
+                "strict-transport-security: max-age=15552000; includeSubDomains",
+                "x-content-type-options: nosniff",
+                "x-dns-prefetch-control: off",
+                "x-download-options: noopen",
+                "x-frame-options: SAMEORIGIN",
+                "x-permitted-cross-domain-policies: none",
+                "x-xss-protection: 0",
+            ]
+        );
+    }
+
+    #[ntex::test]
+    async fn test_preset_maximum() {
+        assert_eq!(
+            header_lines(&response_headers(&Helmet::maximum(), TestRequest::default(), "text/html").await),
+            vec![
+                "content-security-policy: default-src 'none'; base-uri 'none'; connect-src 'self'; font-src 'self'; form-action 'self'; frame-ancestors 'none'; img-src 'self'; manifest-src 'self'; object-src 'none'; script-src 'self'; script-src-attr 'none'; style-src 'self'; require-trusted-types-for 'script'; upgrade-insecure-requests",
+                "cross-origin-embedder-policy: require-corp",
+                "cross-origin-opener-policy: same-origin",
+                "cross-origin-resource-policy: same-origin",
+                "origin-agent-cluster: ?1",
+                "permissions-policy: accelerometer=(), browsing-topics=(), camera=(), display-capture=(), geolocation=(), gyroscope=(), hid=(), interest-cohort=(), magnetometer=(), microphone=(), midi=(), payment=(), serial=(), usb=(), xr-spatial-tracking=()",
+                "referrer-policy: no-referrer",
+                "strict-transport-security: max-age=63072000; includeSubDomains; preload",
+                "x-content-type-options: nosniff",
+                "x-dns-prefetch-control: off",
+                "x-download-options: noopen",
+                "x-frame-options: DENY",
+                "x-permitted-cross-domain-policies: none",
+                "x-xss-protection: 0",
+            ]
+        );
+    }
+
+    // `TestRequest` has no peer address, loopback detection needs the test server
+    // whose client connects from 127.0.0.1
+    async fn development_headers(helmet: Helmet, host: &str) -> HeaderMap {
+        let srv = web::test::server(move || {
+            App::new()
+                .wrap(helmet.clone())
+                .service(web::resource("/").to(|| async { "Hello, world!" }))
+        });
+        let res = srv
+            .get("/")
+            .header(header::HOST, host)
+            .send()
+            .await
+            .unwrap();
+        res.headers().clone()
+    }
+
+    #[ntex::test]
+    async fn test_development_loopback() {
+        let helmet = Helmet::default().development(DevelopmentMode::Loopback);
+
+        for host in [
+            "localhost",
+            "localhost:3000",
+            "127.0.0.1:8080",
+            "[::1]",
+            "app.localhost",
+        ] {
+            let headers = development_headers(helmet.clone(), host).await;
+            assert!(headers.get(header::STRICT_TRANSPORT_SECURITY).is_none());
+            assert_eq!(
+                headers.get(header::CONTENT_SECURITY_POLICY).unwrap(),
+                "default-src 'self'; base-uri 'self'; font-src 'self' https: data:; form-action 'self'; frame-ancestors 'self'; img-src 'self' data:; object-src 'none'; script-src 'self'; script-src-attr 'none'; style-src 'self' https: 'unsafe-inline'; connect-src 'self' ws://localhost:*"
+            );
+            assert_eq!(headers.get(header::X_FRAME_OPTIONS).unwrap(), "SAMEORIGIN");
+        }
+
+        for host in ["example.com", "localhost.example.com", "192.168.0.1:3000"] {
+            let headers = development_headers(helmet.clone(), host).await;
+            assert_eq!(
+                headers.get(header::STRICT_TRANSPORT_SECURITY).unwrap(),
+                "max-age=15552000; includeSubDomains"
+            );
+            assert!(headers
+                .get(header::CONTENT_SECURITY_POLICY)
+                .unwrap()
+                .to_str()
+                .unwrap()
+                .ends_with("; upgrade-insecure-requests"));
+        }
+
+        // `Host` is set by the client, requests from an unknown address aren't relaxed
+        let mw = Pipeline::new(helmet.create(ok_service()));
+        let req = TestRequest::default()
+            .header(header::HOST, "localhost")
+            .to_srv_request();
+        let resp = mw.call(req).await.unwrap();
+        assert!(resp
+            .headers()
+            .contains_key(header::STRICT_TRANSPORT_SECURITY));
+
+        // a reverse proxy on the same machine adds forwarding headers
+        let srv = web::test::server(move || {
+            App::new()
+                .wrap(helmet.clone())
+                .service(web::resource("/").to(|| async { "Hello, world!" }))
+        });
+        for (name, value) in [
+            ("x-forwarded-for", "192.0.2.1"),
+            ("forwarded", "for=192.0.2.1"),
+        ] {
+            let res = srv
+                .get("/")
+                .header(header::HOST, "127.0.0.1:8080")
+                .header(name, value)
+                .send()
+                .await
+                .unwrap();
+            assert!(res
+                .headers()
+                .contains_key(header::STRICT_TRANSPORT_SECURITY));
+        }
+    }
+
+    #[ntex::test]
+    async fn test_development_on() {
+        let helmet = Helmet::new()
+            .add(
+                ContentSecurityPolicy::new()
+                    .default_src(vec!["'none'"])
+                    .connect_src(vec!["'none'"])
+                    .upgrade_insecure_requests(),
+            )
+            .add(StrictTransportSecurity::default())
+            .development(DevelopmentMode::On);
+
+        let headers = development_headers(helmet, "example.com").await;
+        assert!(headers.get(header::STRICT_TRANSPORT_SECURITY).is_none());
+        assert_eq!(
+            headers.get(header::CONTENT_SECURITY_POLICY).unwrap(),
+            "default-src 'none'; connect-src ws://localhost:*"
+        );
+    }
+
+    #[ntex::test]
+    async fn test_development_off() {
+        let headers = development_headers(Helmet::default(), "localhost:3000").await;
+        assert_eq!(
+            headers.get(header::STRICT_TRANSPORT_SECURITY).unwrap(),
+            "max-age=15552000; includeSubDomains"
+        );
+    }
+
+    #[ntex::test]
+    async fn test_development_nonce() {
+        let helmet = Helmet::spa().development(DevelopmentMode::Loopback);
+
+        let headers = development_headers(helmet, "localhost:5173").await;
+        let csp = headers
+            .get(header::CONTENT_SECURITY_POLICY)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(csp.contains("'strict-dynamic' 'nonce-"));
+        assert!(csp.ends_with("; connect-src 'self' ws://localhost:*"));
+    }
+
+    #[ntex::test]
+    async fn test_hsts_https_only() {
+        let helmet = Helmet::default().hsts_https_only(true);
+
+        assert!(
+            response_headers(&helmet, TestRequest::with_uri("/"), "text/html")
+                .await
+                .get(header::STRICT_TRANSPORT_SECURITY)
+                .cloned()
+                .is_none()
+        );
+        assert_eq!(
+            response_headers(
+                &helmet,
+                TestRequest::with_uri("https://example.com/"),
+                "text/html"
+            )
+            .await
+            .get(header::STRICT_TRANSPORT_SECURITY)
+            .cloned()
+            .unwrap(),
+            "max-age=15552000; includeSubDomains"
+        );
+        assert!(
+            response_headers(&Helmet::default(), TestRequest::with_uri("/"), "text/html")
+                .await
+                .get(header::STRICT_TRANSPORT_SECURITY)
+                .cloned()
+                .is_some()
+        );
+
+        // other headers are still sent over HTTP
+        let headers = response_headers(&helmet, TestRequest::default(), "text/html").await;
+        assert_eq!(headers.get(header::X_FRAME_OPTIONS).unwrap(), "SAMEORIGIN");
+    }
+
+    #[ntex::test]
+    async fn test_hsts_https_only_trusted_proxy() {
+        // the test client connects from 127.0.0.1
+        let srv = |proxy: &'static str| {
+            web::test::server(move || {
+                App::new()
+                    .wrap(Helmet::default().hsts_https_only(true).trusted_proxy(proxy))
+                    .service(web::resource("/").to(|| async { "Hello, world!" }))
+            })
+        };
+        let hsts = |res: &ntex::http::client::ClientResponse| {
+            res.headers()
+                .get(header::STRICT_TRANSPORT_SECURITY)
+                .cloned()
+        };
+
+        let trusted = srv("127.0.0.0/8");
+        let res = trusted
+            .get("/")
+            .header("x-forwarded-proto", "https")
+            .send()
+            .await
+            .unwrap();
+        assert_eq!(hsts(&res).unwrap(), "max-age=15552000; includeSubDomains");
+        // only the last element, appended by the trusted proxy, counts
+        let res = trusted
+            .get("/")
+            .header(
+                "forwarded",
+                "for=192.0.2.60;proto=https;by=203.0.113.43, proto=http",
+            )
+            .send()
+            .await
+            .unwrap();
+        assert!(hsts(&res).is_none());
+        let res = trusted
+            .get("/")
+            .header("forwarded", "proto=http, for=192.0.2.60;proto=https")
+            .send()
+            .await
+            .unwrap();
+        assert!(hsts(&res).is_some());
+        let res = trusted
+            .get("/")
+            .header("x-forwarded-proto", "https, http")
+            .send()
+            .await
+            .unwrap();
+        assert!(hsts(&res).is_none());
+        // `Forwarded` takes precedence over `X-Forwarded-Proto`
+        let res = trusted
+            .get("/")
+            .header("forwarded", "proto=http")
+            .header("x-forwarded-proto", "https")
+            .send()
+            .await
+            .unwrap();
+        assert!(hsts(&res).is_none());
+        let res = trusted.get("/").send().await.unwrap();
+        assert!(hsts(&res).is_none());
+
+        let untrusted = srv("10.0.0.0/8");
+        let res = untrusted
+            .get("/")
+            .header("x-forwarded-proto", "https")
+            .send()
+            .await
+            .unwrap();
+        assert!(hsts(&res).is_none());
+        let res = untrusted
+            .get("/")
+            .header("forwarded", "proto=https")
+            .send()
+            .await
+            .unwrap();
+        assert!(hsts(&res).is_none());
+    }
+
+    #[test]
+    fn test_trusted_proxy_invalid() {
+        for proxy in [
+            "10.0.0.0/33",
+            "10.0.0.0/",
+            "example.com",
+            "fd00::/129",
+            "10.0.0.0/+8",
+        ] {
+            assert_eq!(
+                Helmet::new().trusted_proxy(proxy).try_build().err(),
+                Some(HelmetError::InvalidTrustedProxy {
+                    value: proxy.to_string()
+                })
+            );
+        }
+        assert!(Helmet::new()
+            .trusted_proxy("10.0.0.0/8")
+            .trusted_proxy("192.0.2.1")
+            .trusted_proxy("::1")
+            .try_build()
+            .is_ok());
+    }
+
+    #[derive(Debug)]
+    enum TestError {
+        Internal,
+        Forbidden,
+    }
+
+    impl std::fmt::Display for TestError {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            match self {
+                TestError::Internal => write!(f, "internal error"),
+                TestError::Forbidden => write!(f, "forbidden"),
+            }
+        }
+    }
+
+    impl web::WebResponseError<DefaultError> for TestError {
+        fn status_code(&self) -> StatusCode {
+            match self {
+                TestError::Internal => StatusCode::INTERNAL_SERVER_ERROR,
+                TestError::Forbidden => StatusCode::FORBIDDEN,
+            }
+        }
+    }
+
+    #[ntex::test]
+    async fn test_error_responses() {
+        let app = init_service(
+            App::new()
+                .wrap(Helmet::default())
+                .service(
+                    web::resource("/internal")
+                        .to(|| async { Err::<&'static str, _>(TestError::Internal) }),
+                )
+                .service(
+                    web::resource("/forbidden")
+                        .to(|| async { Err::<&'static str, _>(TestError::Forbidden) }),
+                )
+                .service(
+                    web::resource("/json")
+                        .route(web::post().to(|_: web::types::Json<u32>| async { "ok" })),
+                )
+                .service(web::service("/service").finish(
+                    |req: web::WebRequest<DefaultError>| async move {
+                        Ok::<_, web::Error>(req.error_response(TestError::Forbidden))
+                    },
+                )),
+        )
+        .await;
+
+        for (req, status) in [
+            (
+                TestRequest::with_uri("/internal"),
+                StatusCode::INTERNAL_SERVER_ERROR,
+            ),
+            (TestRequest::with_uri("/forbidden"), StatusCode::FORBIDDEN),
+            (
+                TestRequest::post()
+                    .uri("/json")
+                    .header(header::CONTENT_TYPE, "application/json")
+                    .set_payload("not json"),
+                StatusCode::BAD_REQUEST,
+            ),
+            (TestRequest::with_uri("/missing"), StatusCode::NOT_FOUND),
+            (TestRequest::with_uri("/service"), StatusCode::FORBIDDEN),
+        ] {
+            let resp = call_service(&app, req.to_request()).await;
+            assert_eq!(resp.status(), status);
+            for (name, value) in [
+                (header::X_FRAME_OPTIONS, "SAMEORIGIN"),
+                (header::X_CONTENT_TYPE_OPTIONS, "nosniff"),
+                (
+                    header::STRICT_TRANSPORT_SECURITY,
+                    "max-age=15552000; includeSubDomains",
+                ),
+            ] {
+                assert_eq!(resp.headers().get(name).unwrap(), value);
+            }
+            assert!(resp.headers().contains_key(header::CONTENT_SECURITY_POLICY));
+        }
+    }
+
+    #[ntex::test]
+    async fn test_content_type_scopes() {
+        let app = init_service(
+            App::new()
+                .wrap(Helmet::default())
+                .service(web::resource("/json").to(|| async {
+                    web::HttpResponse::Ok()
+                        .content_type("application/json")
+                        .body(r#"{"ok":true}"#)
+                }))
+                .service(web::resource("/script").to(|| async {
+                    web::HttpResponse::Ok()
+                        .content_type("text/javascript; charset=utf-8")
+                        .body("postMessage(1)")
+                }))
+                .service(web::resource("/html").to(|| async {
+                    web::HttpResponse::Ok()
+                        .content_type("text/html")
+                        .body("<html></html>")
+                }))
+                .service(
+                    web::resource("/untyped").to(|| async { web::HttpResponse::Ok().finish() }),
+                ),
+        )
+        .await;
+        let get = |path| {
+            let app = &app;
+            async move { call_service(app, TestRequest::with_uri(path).to_request()).await }
+        };
+
+        let resp = get("/json").await;
+        for name in [
+            header::CONTENT_SECURITY_POLICY,
+            header::X_FRAME_OPTIONS,
+            header::X_XSS_PROTECTION,
+            header::X_DNS_PREFETCH_CONTROL,
+            HeaderName::from_static("origin-agent-cluster"),
+            HeaderName::from_static("cross-origin-opener-policy"),
+        ] {
+            assert!(!resp.headers().contains_key(&name), "{}", name);
+        }
+        for name in [
+            header::X_CONTENT_TYPE_OPTIONS,
+            header::STRICT_TRANSPORT_SECURITY,
+            header::REFERRER_POLICY,
+            HeaderName::from_static("cross-origin-resource-policy"),
+        ] {
+            assert!(resp.headers().contains_key(&name), "{}", name);
+        }
+
+        let resp = get("/script").await;
+        assert!(resp.headers().contains_key(header::CONTENT_SECURITY_POLICY));
+        assert!(!resp.headers().contains_key(header::X_FRAME_OPTIONS));
+
+        for path in ["/html", "/untyped"] {
+            let resp = get(path).await;
+            assert!(resp.headers().contains_key(header::CONTENT_SECURITY_POLICY));
+            assert_eq!(
+                resp.headers().get(header::X_FRAME_OPTIONS).unwrap(),
+                "SAMEORIGIN"
+            );
+            assert_eq!(resp.headers().get("origin-agent-cluster").unwrap(), "?1");
+        }
+    }
+
+    #[ntex::test]
+    async fn test_path_rules() {
+        let app = init_service(
+            App::new()
+                .wrap(
+                    Helmet::default()
+                        .path(
+                            "/admin/*",
+                            PathRule::new()
+                                .add(XFrameOptions::deny())
+                                .remove("X-XSS-Protection"),
+                        )
+                        .path(
+                            "/docs/*",
+                            PathRule::new().add(
+                                ContentSecurityPolicy::new()
+                                    .script_src(vec!["'self'", "'unsafe-inline'"]),
+                            ),
+                        )
+                        .path("/healthz", PathRule::exclude_all())
+                        .path("/metrics", PathRule::exclude_all()),
+                )
+                .default_service(web::to(|| async { web::HttpResponse::Ok().finish() })),
+        )
+        .await;
+        let get = |path| {
+            let app = &app;
+            async move { call_service(app, TestRequest::with_uri(path).to_request()).await }
+        };
+
+        let resp = get("/admin/users").await;
+        assert_eq!(resp.headers().get(header::X_FRAME_OPTIONS).unwrap(), "DENY");
+        assert!(!resp.headers().contains_key(header::X_XSS_PROTECTION));
+        assert_eq!(
+            resp.headers().get(header::X_CONTENT_TYPE_OPTIONS).unwrap(),
+            "nosniff"
+        );
+
+        let resp = get("/docs/index.html").await;
+        assert_eq!(
+            resp.headers()
+                .get_all(header::CONTENT_SECURITY_POLICY)
+                .collect::<Vec<_>>(),
+            vec!["script-src 'self' 'unsafe-inline'"]
+        );
+        assert_eq!(
+            resp.headers().get(header::X_FRAME_OPTIONS).unwrap(),
+            "SAMEORIGIN"
+        );
+
+        for path in ["/healthz", "/metrics"] {
+            assert!(get(path).await.headers().is_empty());
+        }
+
+        // the rule doesn't cover siblings
+        let resp = get("/healthz/ready").await;
+        assert_eq!(
+            resp.headers().get(header::X_FRAME_OPTIONS).unwrap(),
+            "SAMEORIGIN"
+        );
+        let resp = get("/administrator").await;
+        assert_eq!(
+            resp.headers().get(header::X_FRAME_OPTIONS).unwrap(),
+            "SAMEORIGIN"
+        );
+        assert!(resp.headers().contains_key(header::X_XSS_PROTECTION));
+    }
+
+    #[ntex::test]
+    async fn test_path_rules_encoded_path() {
+        let app = init_service(
+            App::new()
+                .wrap(
+                    Helmet::default().path("/admin/*", PathRule::new().add(XFrameOptions::deny())),
+                )
+                .route(
+                    "/admin/users",
+                    web::get().to(|| async { web::HttpResponse::Ok().body("users") }),
+                ),
+        )
+        .await;
+
+        // the router decodes the path, so the rule has to apply as well
+        for path in ["/%61dmin/users", "/admin/%75sers", "/%61%64%6D%69%6E/users"] {
+            let resp = call_service(&app, TestRequest::with_uri(path).to_request()).await;
+            assert_eq!(resp.status(), StatusCode::OK);
+            assert_eq!(resp.headers().get(header::X_FRAME_OPTIONS).unwrap(), "DENY");
+        }
+    }
+
+    #[test]
+    fn test_path_rule_invalid_pattern() {
+        assert_eq!(
+            Helmet::default()
+                .path("healthz", PathRule::exclude_all())
+                .try_build()
+                .err(),
+            Some(HelmetError::InvalidPathPattern {
+                pattern: "healthz".to_string()
+            })
+        );
+    }
+
+    #[ntex::test]
+    async fn test_conditions() {
+        let app = init_service(
+            App::new()
+                .wrap(
+                    Helmet::default()
+                        .when(
+                            "Strict-Transport-Security",
+                            !Condition::status(101).or(Condition::status(304)),
+                        )
+                        .add_when(
+                            XPoweredBy::new("ntex"),
+                            Condition::method(vec!["GET", "HEAD"]),
+                        )
+                        .remove::<ContentSecurityPolicy>()
+                        .add_when(ContentSecurityPolicy::default(), !Condition::error())
+                        .add_when(
+                            ContentSecurityPolicy::new().default_src(vec!["'none'"]),
+                            Condition::error(),
+                        )
+                        .add_when(
+                            CrossOriginEmbedderPolicy::require_corp(),
+                            Condition::request_header_value("upgrade", "websocket"),
+                        ),
+                )
+                .service(
+                    web::resource("/not-modified")
+                        .to(|| async { web::HttpResponse::NotModified().finish() }),
+                )
+                .service(
+                    web::resource("/")
+                        .route(web::get().to(|| async { web::HttpResponse::Ok().finish() }))
+                        .route(web::post().to(|| async { web::HttpResponse::Ok().finish() })),
+                ),
+        )
+        .await;
+
+        let resp = call_service(&app, TestRequest::with_uri("/").to_request()).await;
+        assert!(resp
+            .headers()
+            .contains_key(header::STRICT_TRANSPORT_SECURITY));
+        assert_eq!(resp.headers().get("x-powered-by").unwrap(), "ntex");
+        assert!(resp
+            .headers()
+            .get(header::CONTENT_SECURITY_POLICY)
+            .unwrap()
+            .to_str()
+            .unwrap()
+            .starts_with("default-src 'self'"));
+        assert!(!resp.headers().contains_key("cross-origin-embedder-policy"));
+
+        let resp = call_service(&app, TestRequest::post().uri("/").to_request()).await;
+        assert!(!resp.headers().contains_key("x-powered-by"));
+
+        let resp = call_service(&app, TestRequest::with_uri("/not-modified").to_request()).await;
+        assert_eq!(resp.status(), StatusCode::NOT_MODIFIED);
+        assert!(!resp
+            .headers()
+            .contains_key(header::STRICT_TRANSPORT_SECURITY));
+
+        let resp = call_service(&app, TestRequest::with_uri("/missing").to_request()).await;
+        assert_eq!(
+            resp.headers()
+                .get_all(header::CONTENT_SECURITY_POLICY)
+                .collect::<Vec<_>>(),
+            vec!["default-src 'none'"]
+        );
+
+        let resp = call_service(
+            &app,
+            TestRequest::with_uri("/")
+                .header(header::UPGRADE, "WebSocket")
+                .to_request(),
+        )
+        .await;
+        assert_eq!(
+            resp.headers().get("cross-origin-embedder-policy").unwrap(),
+            "require-corp"
+        );
+    }
+
+    #[ntex::test]
+    async fn test_response_overrides() {
+        let app = init_service(
+            App::new()
+                .wrap(Helmet::default())
+                .service(web::resource("/widget").to(|| async {
+                    let res = web::HttpResponse::Ok()
+                        .content_type("text/html")
+                        .body("<div></div>");
+                    res.extensions_mut().insert(
+                        ResponseOverrides::new()
+                            .skip("X-Frame-Options")
+                            .replace(ReferrerPolicy::strict_origin_when_cross_origin())
+                            .extend_content_security_policy(
+                                ContentSecurityPolicy::new()
+                                    .frame_ancestors(vec!["https://partner.example.com"])
+                                    .connect_src(vec!["https://widget.example.com"]),
+                            ),
+                    );
+                    res
+                }))
+                .service(web::resource("/invalid").to(|| async {
+                    let res = web::HttpResponse::Ok().finish();
+                    res.extensions_mut().insert(
+                        ResponseOverrides::new().replace(XPoweredBy::new("ntex\r\nX-Injected: 1")),
+                    );
+                    res
+                }))
+                .service(web::resource("/").to(|| async { web::HttpResponse::Ok().finish() })),
+        )
+        .await;
+
+        let resp = call_service(&app, TestRequest::with_uri("/widget").to_request()).await;
+        assert!(!resp.headers().contains_key(header::X_FRAME_OPTIONS));
+        assert_eq!(
+            resp.headers().get(header::REFERRER_POLICY).unwrap(),
+            "strict-origin-when-cross-origin"
+        );
+        let policy = resp
+            .headers()
+            .get(header::CONTENT_SECURITY_POLICY)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(policy.contains("frame-ancestors 'self' https://partner.example.com"));
+        assert!(policy.ends_with("connect-src 'self' https://widget.example.com"));
+        assert!(resp
+            .response()
+            .extensions()
+            .get::<ResponseOverrides>()
+            .is_none());
+
+        // other responses keep the configured headers
+        let resp = call_service(&app, TestRequest::with_uri("/").to_request()).await;
+        assert_eq!(
+            resp.headers().get(header::X_FRAME_OPTIONS).unwrap(),
+            "SAMEORIGIN"
+        );
+        assert_eq!(
+            resp.headers().get(header::REFERRER_POLICY).unwrap(),
+            "no-referrer"
+        );
+
+        let resp = call_service(&app, TestRequest::with_uri("/invalid").to_request()).await;
+        assert!(!resp.headers().contains_key("x-powered-by"));
+        assert_eq!(
+            resp.headers().get(header::X_FRAME_OPTIONS).unwrap(),
+            "SAMEORIGIN"
+        );
+    }
+
+    #[ntex::test]
+    async fn test_response_overrides_extend_never_stricter() {
+        let app = init_service(
+            App::new()
+                .wrap(Helmet::new().add(ContentSecurityPolicy::new().default_src(vec!["'self'"])))
+                .service(web::resource("/").to(|| async {
+                    let res = web::HttpResponse::Ok().finish();
+                    res.extensions_mut().insert(
+                        ResponseOverrides::new().extend_content_security_policy(
+                            ContentSecurityPolicy::new()
+                                .img_src(vec!["https:"])
+                                .frame_ancestors(vec!["https://partner.example.com"])
+                                .base_uri(vec!["'self'"]),
+                        ),
+                    );
+                    res
+                })),
+        )
+        .await;
+
+        // the configured policy doesn't restrict framing or base URIs, the extension doesn't add them
+        let resp = call_service(&app, TestRequest::default().to_request()).await;
+        assert_eq!(
+            resp.headers().get(header::CONTENT_SECURITY_POLICY).unwrap(),
+            "default-src 'self'; img-src 'self' https:"
+        );
+    }
+
+    #[ntex::test]
+    async fn test_response_overrides_conditions() {
+        let app = init_service(
+            App::new()
+                .wrap(Helmet::new().add(XFrameOptions::same_origin()).add_when(
+                    ContentSecurityPolicy::new().default_src(vec!["'self'"]),
+                    Condition::method(vec!["GET"]),
+                ))
+                .service(web::resource("/replace").to(|| async {
+                    let res = web::HttpResponse::Ok().finish();
+                    res.extensions_mut()
+                        .insert(ResponseOverrides::new().replace(Conditional::new(
+                            XFrameOptions::deny(),
+                            Condition::method(vec!["GET"]),
+                        )));
+                    res
+                }))
+                .service(web::resource("/extend").to(|| async {
+                    let res = web::HttpResponse::Ok().finish();
+                    res.extensions_mut().insert(
+                        ResponseOverrides::new().extend_content_security_policy(
+                            ContentSecurityPolicy::new().img_src(vec!["https:"]),
+                        ),
+                    );
+                    res
+                })),
+        )
+        .await;
+
+        // a replacement can't have a condition, the configured header is sent instead of dropping it
+        let resp = call_service(&app, TestRequest::with_uri("/replace").to_request()).await;
+        assert_eq!(
+            resp.headers().get(header::X_FRAME_OPTIONS).unwrap(),
+            "SAMEORIGIN"
+        );
+
+        // an extended policy keeps the condition of the configured policy
+        let resp = call_service(&app, TestRequest::with_uri("/extend").to_request()).await;
+        assert_eq!(
+            resp.headers().get(header::CONTENT_SECURITY_POLICY).unwrap(),
+            "default-src 'self'; img-src 'self' https:"
+        );
+        let resp = call_service(
+            &app,
+            TestRequest::with_uri("/extend")
+                .method(Method::POST)
+                .to_request(),
+        )
+        .await;
+        assert!(resp
+            .headers()
+            .get(header::CONTENT_SECURITY_POLICY)
+            .is_none());
+        assert_eq!(
+            resp.headers().get(header::X_FRAME_OPTIONS).unwrap(),
+            "SAMEORIGIN"
+        );
+    }
+
+    #[ntex::test]
+    async fn test_response_overrides_nonce() {
+        let app = init_service(
+            App::new()
+                .wrap(Helmet::new().add(ContentSecurityPolicy::default().script_nonce()))
+                .service(web::resource("/extend").to(|| async {
+                    let res = web::HttpResponse::Ok().finish();
+                    res.extensions_mut().insert(
+                        ResponseOverrides::new().extend_content_security_policy(
+                            ContentSecurityPolicy::new()
+                                .connect_src(vec!["https://api.example.com"]),
+                        ),
+                    );
+                    res
+                }))
+                .service(web::resource("/replace").to(|| async {
+                    let res = web::HttpResponse::Ok().finish();
+                    res.extensions_mut().insert(
+                        ResponseOverrides::new().replace(
+                            ContentSecurityPolicy::new()
+                                .default_src(vec!["'none'"])
+                                .style_nonce(),
+                        ),
+                    );
+                    res
+                })),
+        )
+        .await;
+
+        // the extended policy keeps the nonce of the request
+        let resp = call_service(&app, TestRequest::with_uri("/extend").to_request()).await;
+        let policy = resp
+            .headers()
+            .get_all(header::CONTENT_SECURITY_POLICY)
+            .map(|value| value.to_str().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(policy.len(), 1);
+        assert!(policy[0].contains("script-src 'self' 'nonce-"));
+        assert!(policy[0].ends_with("connect-src 'self' https://api.example.com"));
+
+        // a replacement with a nonce of its own is rejected
+        let overrides = ResponseOverrides::new().replace(
+            ContentSecurityPolicy::new()
+                .default_src(vec!["'none'"])
+                .style_nonce(),
+        );
+        assert!(matches!(
+            overrides.validate(),
+            Err(HelmetError::InvalidOverride { name, .. }) if name == "Content-Security-Policy"
+        ));
+        let resp = call_service(&app, TestRequest::with_uri("/replace").to_request()).await;
+        let policy = resp
+            .headers()
+            .get(header::CONTENT_SECURITY_POLICY)
+            .unwrap()
+            .to_str()
+            .unwrap();
+        assert!(policy.starts_with("default-src 'self'"));
+        assert!(policy.contains("'nonce-"));
+    }
 }